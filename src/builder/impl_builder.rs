@@ -10,39 +10,114 @@ impl BuilderBase for Builder {
 			variable_indices: HashMap::new(),
 			constraints: vec![],
 			objective: None,
-			var_names: vec![]
+			var_names: vec![],
+			ranges: vec![],
+			kinds: HashMap::new(),
+			penalties: vec![],
+			default_bounds: vec![],
+			max_variables: None,
+			max_constraints: None
 		}
 	}
 
 	/// Adds a new user-defined Variable struct to this Builder struct.
+	///
+	/// Panics past the limit set with `max_variables`.
 	fn add_variable(&mut self, variable: Variable) {
-		if !(self.variables.contains(&variable.name)) {
-			let num_variables = self.variables.len();
-			self.variable_indices.insert(variable.name.clone(), num_variables);
-			self.variables.insert(variable.name.clone());
-			self.var_names.push(variable.name.clone());
-		}
+		self.try_add_variable(variable).unwrap_or_else(|message| panic!("{}", message));
 	}
 
 	/// Adds a new user-defined Constraint struct to this Builder struct.
+	///
+	/// Repeated terms on the same variable are added together, so `2*a`
+	/// and `3*a` give `a` the coefficient 5 in the built row.
+	///
+	/// Constraint names are kept unique so lookups by name stay
+	/// unambiguous: a repeated name gets the first free suffix out of
+	/// `_2`, `_3`, ... (the second `c1` becomes `c1_2`).
+	///
+	/// Panics on an unknown variable or past the limit set with
+	/// `max_constraints`.
 	fn add_constraint(&mut self, constraint: Constraint) {
-		self.check_variables(&constraint.variables).expect("Unknown variable in constraint");
-		self.constraints.push(constraint);
+		self.try_add_constraint(constraint).unwrap_or_else(|message| panic!("{}", message));
 	}
 
 	/// Adds a new user-defined Objective struct to this Builder struct.
+	///
+	/// As in constraints, repeated terms on a variable are added together.
 	fn add_objective(&mut self, objective: Objective) {
-		self.check_variables(&objective.variables).expect("Unknown variable in objective");
+		self.try_add_objective(objective).unwrap_or_else(|message| panic!("{}", message));
+	}
 
-		let curr_objective = self.objective.take();
-		match curr_objective {
-			Some(_) => {
-				panic!("Attempting to add a second objective!");
-			},
-			None => {
-				self.objective = Some(objective);
+	/// Marks a variable as continuous, integer or binary. Variables are
+	/// continuous unless marked otherwise.
+	///
+	/// Making a variable binary also adds the constraint
+	/// `{name}_binary: name <= 1`, suffixed like any other generated row
+	/// if that name is taken.
+	fn set_variable_kind(&mut self, name: &str, kind: VariableKind) {
+		self.try_set_variable_kind(name, kind).unwrap_or_else(|message| panic!("{}", message));
+	}
+
+	fn try_add_variable(&mut self, variable: Variable) -> Result<(), String> {
+		if !(self.variables.contains(&variable.name)) {
+			if let Some(max) = self.max_variables {
+				if self.var_names.len() >= max {
+					return Err(format!("Too many variables: {} exceeds the limit of {}", self.var_names.len() + 1, max));
+				}
+			}
+
+			self.insert_variable(&variable.name);
+		}
+		Ok(())
+	}
+
+	fn try_add_constraint(&mut self, constraint: Constraint) -> Result<(), String> {
+		self.check_variables(&constraint.variables).ok_or("Unknown variable in constraint")?;
+		if let Some(max) = self.max_constraints {
+			// Only counted when at the limit, as that's the rare case
+			let kept = || self.constraints.iter().filter(|constraint| !is_droppable(constraint)).count();
+			if !is_droppable(&constraint) && self.constraints.len() >= max && kept() >= max {
+				return Err(format!("Too many constraints: {} exceeds the limit of {}", kept() + 1, max));
 			}
 		}
+
+		let mut constraint = constraint;
+		constraint.name = unique_name(&constraint.name, |name| self.has_constraint(name));
+
+		self.constraints.push(constraint);
+		Ok(())
+	}
+
+	fn try_add_objective(&mut self, objective: Objective) -> Result<(), String> {
+		self.check_variables(&objective.variables).ok_or("Unknown variable in objective")?;
+		if self.objective.is_some() {
+			return Err("Attempting to add a second objective!".to_string());
+		}
+
+		self.objective = Some(objective);
+		Ok(())
+	}
+
+	fn try_set_variable_kind(&mut self, name: &str, kind: VariableKind) -> Result<(), String> {
+		if !self.variables.contains(name) {
+			return Err(format!("Unknown variable \"{}\"", name));
+		}
+
+		let previous = self.kinds.insert(name.to_string(), kind);
+		if kind == VariableKind::Binary && previous != Some(VariableKind::Binary) {
+			let row_name = unique_name(&format!("{}_binary", name), |taken| self.has_constraint(taken));
+			self.constraints.push(Constraint {
+				name: row_name,
+				variables: vec![Variable {
+					name: name.to_string(),
+					coefficient: 1.
+				}],
+				constant: 1.,
+				relation: Relation::LessThanOrEqual
+			});
+		}
+		Ok(())
 	}
 
 	/// Constructor for Lp struct.
@@ -130,24 +205,381 @@ impl BuilderBase for Builder {
 	/// # }
 	/// ```
 	fn build_lp(&mut self) -> Lp {
+		self.try_build_lp().unwrap_or_else(|message| panic!("{}", message))
+	}
+
+	fn try_build_lp(&mut self) -> Result<Lp, String> {
+		if self.objective.is_none() {
+			return Err("No objective function!".to_string());
+		}
+		self.drop_satisfied_empty_constraints();
+		self.check_size_limits()?;
+		self.default_bounds = self.var_names.iter()
+			.filter(|name| !self.has_explicit_lower_bound(name))
+			.cloned()
+			.collect();
 		let num_artificial_vars = self.convert_to_standard_form();
 		let A = self.generate_A();
 		let b = self.generate_b();
 		let (c, opt) = self.generate_c();
 
-		Lp {
-			A: A,
-			b: b,
-			c: c,
+		let lp = Lp {
+			A,
+			b,
+			c,
 			optimization: opt,
 			vars: self.var_names.clone(),
-			num_artificial_vars: num_artificial_vars
+			num_artificial_vars,
+			constraint_names: self.constraints.iter().map(|c| c.name.clone()).collect(),
+			ranges: self.ranges.clone(),
+			kinds: self.var_names.iter()
+				.map(|name| self.kinds.get(name).cloned().unwrap_or(VariableKind::Continuous))
+				.collect(),
+			objective_negated: false
+		};
 
+		debug_assert_eq!(lp.validate(), Ok(()));
+		Ok(lp)
+	}
+}
+
+impl Variable {
+	/// Compares two variables, allowing the coefficients to differ by
+	/// at most `epsilon`.
+	pub fn approx_eq(&self, other: &Variable, epsilon: f64) -> bool {
+		self.name == other.name && (self.coefficient - other.coefficient).abs() <= epsilon
+	}
+}
+
+impl Constraint {
+	/// Constructor for Constraint struct from `(variable name, coefficient)`
+	/// pairs, e.g. coefficients computed into a `HashMap`.
+	///
+	/// # Examples
+	/// ```
+	/// use rulp::builder::{Constraint, Relation, Variable};
+	///
+	/// let terms = vec![("a".to_string(), 2.), ("b".to_string(), -1.)];
+	/// let constraint = Constraint::from_terms("c1", terms, Relation::LessThanOrEqual, 4.);
+	///
+	/// assert_eq!(constraint.name, "c1");
+	/// assert_eq!(constraint.variables[1], Variable { name: "b".to_string(), coefficient: -1. });
+	/// ```
+	pub fn from_terms<I: IntoIterator<Item=(String, f64)>>(name: &str, terms: I, relation: Relation, constant: f64) -> Constraint {
+		Constraint {
+			name: name.to_string(),
+			variables: terms.into_iter()
+				.map(|(name, coefficient)| Variable { name, coefficient })
+				.collect(),
+			constant,
+			relation
 		}
 	}
+
+	/// Multiplies both sides of the constraint by `factor`, flipping the
+	/// relation if it is negative. A ranged constraint keeps both its
+	/// bounds, which swap sides when flipped.
+	pub fn scale(&mut self, factor: f64) {
+		for var in self.variables.iter_mut() {
+			var.coefficient *= factor;
+		}
+		match self.relation {
+			Relation::Range(lower) if factor < 0. => {
+				self.relation = Relation::Range(self.constant * factor);
+				self.constant = lower * factor;
+			},
+			Relation::Range(lower) => {
+				self.relation = Relation::Range(lower * factor);
+				self.constant *= factor;
+			},
+			_ => {
+				if factor < 0. {
+					self.relation = self.relation.flip();
+				}
+				self.constant *= factor;
+			}
+		}
+	}
+
+	/// Scales the constraint so the coefficient largest in magnitude (the
+	/// first one, on ties) becomes 1, flipping the relation if it was
+	/// negative. A constraint without nonzero coefficients is unchanged.
+	///
+	/// # Examples
+	/// ```
+	/// use rulp::builder::{Constraint, Relation};
+	///
+	/// let terms = vec![("a".to_string(), 2.), ("b".to_string(), -4.)];
+	/// let mut constraint = Constraint::from_terms("c1", terms, Relation::LessThanOrEqual, 8.);
+	/// constraint.normalize();
+	///
+	/// let terms = vec![("a".to_string(), -0.5), ("b".to_string(), 1.)];
+	/// assert_eq!(constraint, Constraint::from_terms("c1", terms, Relation::GreaterThanOrEqual, -2.));
+	/// ```
+	pub fn normalize(&mut self) {
+		let leading = self.variables.iter()
+			.map(|var| var.coefficient)
+			.fold(0., |largest: f64, coeff| if coeff.abs() > largest.abs() { coeff } else { largest });
+		if leading != 0. {
+			self.scale(1. / leading);
+		}
+	}
+
+	/// Compares two constraints, allowing the coefficients and the constant
+	/// to differ by at most `epsilon`.
+	pub fn approx_eq(&self, other: &Constraint, epsilon: f64) -> bool {
+		self.name == other.name
+			&& self.relation == other.relation
+			&& (self.constant - other.constant).abs() <= epsilon
+			&& vars_approx_eq(&self.variables, &other.variables, epsilon)
+	}
+}
+
+impl Objective {
+	/// Compares two objectives, allowing the coefficients to differ by
+	/// at most `epsilon`.
+	pub fn approx_eq(&self, other: &Objective, epsilon: f64) -> bool {
+		self.name == other.name
+			&& self.maximize == other.maximize
+			&& vars_approx_eq(&self.variables, &other.variables, epsilon)
+	}
+}
+
+impl Relation {
+	/// The relation that holds after multiplying both sides by a negative
+	/// number, so `<=` and `>=` swap and `==` stays. A ranged relation is
+	/// returned unchanged, as its bounds change along with the constant;
+	/// `Constraint::scale` takes care of both.
+	pub fn flip(&self) -> Relation {
+		match *self {
+			Relation::LessThanOrEqual => Relation::GreaterThanOrEqual,
+			Relation::GreaterThanOrEqual => Relation::LessThanOrEqual,
+			Relation::Equal => Relation::Equal,
+			Relation::Range(lower) => Relation::Range(lower)
+		}
+	}
+}
+
+// The name itself when it isn't taken yet, otherwise the first free one
+// out of `name_2`, `name_3`, ...
+pub(crate) fn unique_name<F: Fn(&str) -> bool>(name: &str, taken: F) -> String {
+	if !taken(name) {
+		return name.to_string();
+	}
+	let mut suffix = 2;
+	while taken(&format!("{}_{}", name, suffix)) {
+		suffix += 1;
+	}
+	format!("{}_{}", name, suffix)
+}
+
+// Whether the terms of a constraint all cancel out and what's left,
+// `0 <relation> constant`, holds anyway
+fn is_droppable(constraint: &Constraint) -> bool {
+	let mut coefficients: HashMap<&str, f64> = HashMap::new();
+	for var in &constraint.variables {
+		*coefficients.entry(&var.name).or_insert(0.) += var.coefficient;
+	}
+	if coefficients.values().any(|&coeff| coeff != 0.) {
+		return false;
+	}
+
+	match constraint.relation {
+		Relation::Equal => constraint.constant == 0.,
+		Relation::LessThanOrEqual => 0. <= constraint.constant,
+		Relation::GreaterThanOrEqual => 0. >= constraint.constant,
+		Relation::Range(lower) => lower <= 0. && 0. <= constraint.constant
+	}
+}
+
+fn vars_approx_eq(a: &[Variable], b: &[Variable], epsilon: f64) -> bool {
+	a.len() == b.len() && a.iter().zip(b.iter()).all(|(x, y)| x.approx_eq(y, epsilon))
 }
 
 impl Builder {
+	/// Adds an objective minimizing the maximum of several expressions.
+	///
+	/// The objective is reformulated by introducing an auxiliary variable
+	/// `minimax` along with one constraint `minimax - expr_i >= 0` per
+	/// expression (named `minimax_0`, `minimax_1`, ...), then minimizing
+	/// `minimax`. The maximum may be negative, so `minimax` is free: it is
+	/// written as `minimax_pos - minimax_neg`, two nonnegative variables
+	/// whose names are reserved. All variables in the expressions must be
+	/// declared beforehand. Panics if `minimax_pos` or `minimax_neg` is
+	/// declared already.
+	pub fn add_minimax_objective(&mut self, exprs: &[Vec<Variable>]) {
+		let aux = ["minimax_pos".to_string(), "minimax_neg".to_string()];
+		for name in &aux {
+			if self.variables.contains(name) {
+				panic!("\"{}\" is reserved for minimax objectives", name);
+			}
+			self.add_variable(Variable {
+				name: name.clone(),
+				coefficient: 0.
+			});
+		}
+		let minimax = || vec![
+			Variable { name: aux[0].clone(), coefficient: 1. },
+			Variable { name: aux[1].clone(), coefficient: -1. }
+		];
+
+		for (i, expr) in exprs.iter().enumerate() {
+			let mut variables = minimax();
+			for var in expr {
+				variables.push(Variable {
+					name: var.name.clone(),
+					coefficient: -var.coefficient
+				});
+			}
+
+			self.add_constraint(Constraint {
+				name: format!("minimax_{}", i),
+				variables,
+				constant: 0.,
+				relation: Relation::GreaterThanOrEqual
+			});
+		}
+
+		self.add_objective(Objective {
+			name: "minimax".to_string(),
+			variables: minimax(),
+			maximize: false
+		});
+	}
+
+	/// Adds a constraint that may be violated at a cost of `penalty` per
+	/// unit of violation.
+	///
+	/// The constraint gets violation variables `{name}_over` (how far the
+	/// expression exceeds the upper side) and/or `{name}_under` (how far it
+	/// falls below the lower side), which are penalized in the objective
+	/// whichever direction it optimizes. Their values in a solution show
+	/// which soft constraints were relaxed and by how much.
+	pub fn add_soft_constraint(&mut self, constraint: Constraint, penalty: f64) {
+		if penalty < 0. {
+			panic!("Negative penalty for soft constraint \"{}\"", constraint.name);
+		}
+
+		let mut constraint = constraint;
+		let (over, under) = match constraint.relation {
+			Relation::LessThanOrEqual => (true, false),
+			Relation::GreaterThanOrEqual => (false, true),
+			Relation::Equal | Relation::Range(_) => (true, true)
+		};
+
+		for &(violated, suffix, coefficient) in &[(over, "over", -1.), (under, "under", 1.)] {
+			if violated {
+				let name = format!("{}_{}", constraint.name, suffix);
+				self.add_variable(Variable {
+					name: name.clone(),
+					coefficient: 0.
+				});
+				constraint.variables.push(Variable {
+					name: name.clone(),
+					coefficient
+				});
+				self.penalties.push((name, penalty));
+			}
+		}
+
+		self.add_constraint(constraint);
+	}
+
+	/// Changes the constant (right hand side) of an existing constraint.
+	///
+	/// For a ranged constraint this is its upper side. Panics if there is
+	/// no constraint with that name.
+	pub fn update_constraint(&mut self, name: &str, new_constant: f64) {
+		self.constraint_mut(name).constant = new_constant;
+	}
+
+	/// Sets the coefficient of a variable in an existing constraint,
+	/// adding the term if the constraint doesn't mention the variable yet.
+	///
+	/// Repeated terms on the variable are replaced by a single one. Panics
+	/// if the constraint or the variable is unknown.
+	pub fn set_constraint_coefficient(&mut self, name: &str, var: &str, value: f64) {
+		if !self.variables.contains(var) {
+			panic!("Unknown variable \"{}\"", var);
+		}
+
+		let constraint = self.constraint_mut(name);
+		let index = constraint.variables.iter()
+			.position(|variable| variable.name == var)
+			.unwrap_or(constraint.variables.len());
+		// Every removed term is at or after the first one, so the index still
+		// points at its place
+		constraint.variables.retain(|variable| variable.name != var);
+		constraint.variables.insert(index, Variable {
+			name: var.to_string(),
+			coefficient: value
+		});
+	}
+
+	/// Adds a variable along with its objective coefficient and its
+	/// coefficient in each of the named constraints, the way column
+	/// generation adds variables to a model.
+	///
+	/// The objective and the constraints must already be added, possibly
+	/// without any terms yet. Panics if the variable is already declared
+	/// or a constraint is unknown.
+	///
+	/// # Examples
+	/// ```
+	/// use rulp::builder::{Builder, BuilderBase, Constraint, Objective, Relation};
+	///
+	/// let mut builder = Builder::new();
+	/// builder.add_objective(Objective { name: "obj".to_string(), variables: vec![], maximize: true });
+	/// builder.add_constraint(Constraint::from_terms("cap", vec![], Relation::LessThanOrEqual, 4.));
+	/// builder.add_column("a", 3., &[("cap", 2.)]);
+	///
+	/// let lp = builder.build_lp();
+	/// assert_eq!(lp.c, vec![3., 0.]);
+	/// assert_eq!(lp.A.data(), &vec![2., 1.]);
+	/// ```
+	pub fn add_column(&mut self, name: &str, obj_coeff: f64, constraint_coeffs: &[(&str, f64)]) {
+		if self.variables.contains(name) {
+			panic!("Variable \"{}\" already declared", name);
+		}
+
+		self.add_variable(Variable { name: name.to_string(), coefficient: 0. });
+		match self.objective {
+			Some(ref mut objective) => objective.variables.push(Variable { name: name.to_string(), coefficient: obj_coeff }),
+			None => panic!("No objective function!")
+		}
+		for &(constraint, coefficient) in constraint_coeffs {
+			self.set_constraint_coefficient(constraint, name, coefficient);
+		}
+	}
+
+	/// Lists, in declaration order, the variables that the last call to
+	/// `build_lp` left with only the default `>= 0` bound.
+	///
+	/// A variable counts as explicitly bounded from below when it is
+	/// binary or some constraint bounds it on its own, e.g. `x >= 2` or
+	/// `-x <= 0`. Every other variable is assumed to be nonnegative, which
+	/// may not be what the model intended for a free variable.
+	pub fn default_nonnegative_variables(&self) -> &[String] {
+		&self.default_bounds
+	}
+
+	/// Limits the Lp to `n` variables, not counting the slack, excess and
+	/// range variables added when building it. Adding a variable past the limit
+	/// fails, so untrusted input can't grow the model without bound. The
+	/// parser expands ranges like `var x[1..1000]` before adding them, see
+	/// `ParserOptions::max_variables` to limit those.
+	pub fn max_variables(&mut self, n: usize) {
+		self.max_variables = Some(n);
+	}
+
+	/// Limits the Lp to `m` constraints, checked when adding a constraint
+	/// the same way as `max_variables`. Constraints whose terms all cancel
+	/// out and that hold anyway are dropped by `build_lp`, so they don't
+	/// count towards the limit.
+	pub fn max_constraints(&mut self, m: usize) {
+		self.max_constraints = Some(m);
+	}
+
 	fn generate_A(&self) -> Matrix<f64> {
 		let num_variables = self.variables.len();
 		let num_constraints = self.constraints.len();
@@ -155,9 +587,9 @@ impl Builder {
 
 		for row in 0 .. num_constraints {
 			let constraint = &self.constraints[row];
-			for ref var in &constraint.variables {
+			for var in &constraint.variables {
 				let index = row * num_variables + self.variable_indices[&var.name];
-				A[index] = var.coefficient;
+				A[index] += var.coefficient;
 			}
 		}
 
@@ -186,8 +618,11 @@ impl Builder {
 				panic!("No objective function!");
 			},
 			Some(ref obj) => {
-				for ref var in &obj.variables {
-					c[self.variable_indices[&var.name]] = var.coefficient;
+				for var in &obj.variables {
+					c[self.variable_indices[&var.name]] += var.coefficient;
+				}
+				for &(ref name, penalty) in &self.penalties {
+					c[self.variable_indices[name]] += if obj.maximize { -penalty } else { penalty };
 				}
 
 				if obj.maximize {
@@ -201,8 +636,62 @@ impl Builder {
 		(c, opt)
 	}
 
+	// Binary kinds and soft constraints add rows of their own, which
+	// `try_add_constraint` doesn't see
+	fn check_size_limits(&self) -> Result<(), String> {
+		if let Some(max) = self.max_variables {
+			if self.var_names.len() > max {
+				return Err(format!("Too many variables: {} exceeds the limit of {}", self.var_names.len(), max));
+			}
+		}
+		if let Some(max) = self.max_constraints {
+			if self.constraints.len() > max {
+				return Err(format!("Too many constraints: {} exceeds the limit of {}", self.constraints.len(), max));
+			}
+		}
+		Ok(())
+	}
+
+	// Declares a variable without checking `max_variables`, which doesn't
+	// count the slack, excess and range columns `build_lp` adds this way
+	fn insert_variable(&mut self, name: &str) {
+		let num_variables = self.variables.len();
+		self.variable_indices.insert(name.to_string(), num_variables);
+		self.variables.insert(name.to_string());
+		self.var_names.push(name.to_string());
+	}
+
+	fn has_constraint(&self, name: &str) -> bool {
+		self.constraints.iter().any(|constraint| constraint.name == name)
+	}
+
+	fn constraint_mut(&mut self, name: &str) -> &mut Constraint {
+		match self.constraints.iter_mut().find(|constraint| constraint.name == name) {
+			Some(constraint) => constraint,
+			None => panic!("Unknown constraint \"{}\"", name)
+		}
+	}
+
+	fn has_explicit_lower_bound(&self, name: &str) -> bool {
+		if self.kinds.get(name) == Some(&VariableKind::Binary) {
+			return true;
+		}
+
+		self.constraints.iter().any(|constraint| {
+			if constraint.variables.len() != 1 || constraint.variables[0].name != name {
+				return false;
+			}
+			let coefficient = constraint.variables[0].coefficient;
+			match constraint.relation {
+				Relation::Equal | Relation::Range(_) => coefficient != 0.,
+				Relation::GreaterThanOrEqual => coefficient > 0.,
+				Relation::LessThanOrEqual => coefficient < 0.
+			}
+		})
+	}
+
 	fn check_variables(&self, variables: &Vec<Variable>) -> Option<()> {
-		for ref var in variables {
+		for var in variables {
 			if !self.variables.contains(&var.name) {
 				return None
 			}
@@ -211,9 +700,17 @@ impl Builder {
 		Some(())
 	}
 
+	// Constraints whose terms all cancel out reduce to `0 <relation> constant`.
+	// Those that hold are dropped; those that don't are kept, so the Lp
+	// stays infeasible.
+	fn drop_satisfied_empty_constraints(&mut self) {
+		self.constraints.retain(|constraint| !is_droppable(constraint));
+	}
+
 	fn convert_to_standard_form(&mut self) -> usize {
 		let mut needs_slack = vec![];
 		let mut needs_excess = vec![];
+		let mut needs_range = vec![];
 
 		for i in 0 .. self.constraints.len() {
 			match self.constraints[i].relation {
@@ -223,27 +720,70 @@ impl Builder {
 				},
 				Relation::GreaterThanOrEqual => {
 					needs_excess.push(i);
+				},
+				Relation::Range(lower) => {
+					needs_slack.push(i);
+					needs_range.push((i, lower));
 				}
 			}
 		}
 
-		let num_artificial_vars = needs_slack.len() + needs_excess.len();
+		let num_artificial_vars = needs_slack.len() + needs_excess.len() + needs_range.len();
 
 		self.add_slack_variables(needs_slack);
 		self.add_excess_variables(needs_excess);
+		self.add_range_rows(needs_range);
 
 		num_artificial_vars
 	}
 
+	// Each ranged constraint already got a slack for its upper bound, the
+	// lower bound becomes an extra row capping that slack at upper - lower
+	fn add_range_rows(&mut self, constraints: Vec<(usize, f64)>) {
+		for (range_ct, (i, lower)) in constraints.into_iter().enumerate() {
+			let (name, slack, upper) = {
+				let constraint = &self.constraints[i];
+				let slack = constraint.variables.last().unwrap().name.clone();
+				(constraint.name.clone(), slack, constraint.constant)
+			};
+
+			let range = Variable {
+				name: format!("range_{}", range_ct),
+				coefficient: 1.,
+			};
+			self.insert_variable(&range.name);
+
+			self.ranges.push(RangedRow {
+				row: i,
+				bound_row: self.constraints.len(),
+				slack: self.variable_indices[&slack],
+				range: self.variable_indices[&range.name],
+				lower,
+				upper,
+			});
+
+			self.constraints.push(Constraint {
+				name: format!("{}_range", name),
+				variables: vec![
+					Variable {
+						name: slack,
+						coefficient: 1.,
+					},
+					range
+				],
+				constant: upper - lower,
+				relation: Relation::Equal
+			});
+		}
+	}
+
 	fn add_slack_variables(&mut self, constraints: Vec<usize>) {
-		let mut slack_ct = 0;
 		let mut vars_to_add = vec![];
 
-		for i in constraints {
-			let ref mut constraint = self.constraints[i];
+		for (slack_ct, i) in constraints.into_iter().enumerate() {
+			let constraint = &mut self.constraints[i];
 
 			let slack = format!("slack_{}", slack_ct);
-			slack_ct += 1;
 
 
 			let var = Variable {
@@ -258,19 +798,17 @@ impl Builder {
 		}
 
 		for v in vars_to_add {
-			self.add_variable(v);
+			self.insert_variable(&v.name);
 		}
 	}
 
 	fn add_excess_variables(&mut self, constraints: Vec<usize>) {
-		let mut excess_ct = 0;
 		let mut vars_to_add = vec![];
 
-		for i in constraints {
-			let ref mut constraint = self.constraints[i];
+		for (excess_ct, i) in constraints.into_iter().enumerate() {
+			let constraint = &mut self.constraints[i];
 
 			let excess = format!("excess_{}", excess_ct);
-			excess_ct += 1;
 
 			let var = Variable {
 				name: excess,
@@ -283,7 +821,7 @@ impl Builder {
 		}
 
 		for v in vars_to_add {
-			self.add_variable(v);
+			self.insert_variable(&v.name);
 		}
 	}
 }
\ No newline at end of file
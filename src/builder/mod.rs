@@ -5,9 +5,10 @@
 //! programming problem.
 
 pub use std::collections::{HashMap, HashSet};
-pub use lp::Lp;
+pub use lp::{Lp, RangedRow, VariableKind};
 
 mod impl_builder;
+pub(crate) use self::impl_builder::unique_name;
 
 #[derive(Debug, PartialEq, Clone)]
 pub struct Variable {
@@ -34,7 +35,9 @@ pub struct Objective {
 pub enum Relation {
 	Equal,
 	LessThanOrEqual,
-	GreaterThanOrEqual
+	GreaterThanOrEqual,
+	/// Ranged constraint `lower <= expr <= constant`, holding the lower bound
+	Range(f64)
 }
 
 pub trait BuilderBase {
@@ -42,7 +45,41 @@ pub trait BuilderBase {
 	fn add_variable(&mut self, variable: Variable);
 	fn add_constraint(&mut self, constraint: Constraint);
 	fn add_objective(&mut self, objective: Objective);
+	/// Marks a variable as integer or binary. Builders that only support
+	/// continuous variables can leave this out, and then panic on
+	/// anything but `VariableKind::Continuous`.
+	fn set_variable_kind(&mut self, name: &str, kind: VariableKind) {
+		if kind != VariableKind::Continuous {
+			panic!("This builder only supports continuous variables, \"{}\" is {:?}", name, kind);
+		}
+	}
 	fn build_lp(&mut self) -> Lp;
+
+	/// Like `add_variable`, but gives an error instead of panicking when
+	/// the variable can't be added. Defaults to `add_variable`.
+	fn try_add_variable(&mut self, variable: Variable) -> Result<(), String> {
+		self.add_variable(variable);
+		Ok(())
+	}
+	/// Like `add_constraint`, but gives an error instead of panicking.
+	fn try_add_constraint(&mut self, constraint: Constraint) -> Result<(), String> {
+		self.add_constraint(constraint);
+		Ok(())
+	}
+	/// Like `add_objective`, but gives an error instead of panicking.
+	fn try_add_objective(&mut self, objective: Objective) -> Result<(), String> {
+		self.add_objective(objective);
+		Ok(())
+	}
+	/// Like `set_variable_kind`, but gives an error instead of panicking.
+	fn try_set_variable_kind(&mut self, name: &str, kind: VariableKind) -> Result<(), String> {
+		self.set_variable_kind(name, kind);
+		Ok(())
+	}
+	/// Like `build_lp`, but gives an error instead of panicking.
+	fn try_build_lp(&mut self) -> Result<Lp, String> {
+		Ok(self.build_lp())
+	}
 }
 
 #[derive(Debug)]
@@ -51,5 +88,11 @@ pub struct Builder {
 	variable_indices: HashMap<String, usize>,
 	constraints: Vec<Constraint>,
 	objective: Option<Objective>,
-	var_names: Vec<String>
+	var_names: Vec<String>,
+	ranges: Vec<RangedRow>,
+	kinds: HashMap<String, VariableKind>,
+	penalties: Vec<(String, f64)>,
+	default_bounds: Vec<String>,
+	max_variables: Option<usize>,
+	max_constraints: Option<usize>
 }
\ No newline at end of file
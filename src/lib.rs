@@ -1,5 +1,6 @@
 #![allow(unused_imports)]		// Allowed because compiler complains about/doesnt recognize macro imports only used in tests
 #![allow(non_snake_case)]		// Allowed to follow LP standard naming procedure
+#![allow(clippy::tabs_in_doc_comments)]	// Doc examples are indented with tabs like the code
 
 #[macro_use]
 extern crate rulinalg;
@@ -16,3 +17,87 @@ pub mod solver;
 
 #[allow(dead_code)]	// print_matrix mainly for debugging
 mod utils;
+
+use std::fmt;
+use std::io::{self, BufRead, Write};
+use builder::{Builder, BuilderBase};
+use parser::{ParseError, Parser, ParserBase};
+use solver::{Solution, Solver};
+
+/// Errors raised by `solve_reader` and `run`.
+#[derive(Debug)]
+pub enum RulpError {
+	/// Reading the problem or writing the report failed
+	Io(io::Error),
+	/// The problem has invalid syntax or can't be built
+	Parse(ParseError)
+}
+
+impl fmt::Display for RulpError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match *self {
+			RulpError::Io(ref err) => write!(f, "Failed to read problem: {}", err),
+			RulpError::Parse(ref err) => write!(f, "Invalid problem: {}", err.message)
+		}
+	}
+}
+
+impl std::error::Error for RulpError {}
+
+impl From<io::Error> for RulpError {
+	fn from(err: io::Error) -> Self {
+		RulpError::Io(err)
+	}
+}
+
+/// Reads an LP in the text syntax from `reader`, builds it with a default
+/// Builder and solves it with a default Solver.
+///
+/// Invalid syntax, or a problem the Builder rejects, gives a
+/// `RulpError::Parse` instead of a panic.
+///
+/// # Examples
+/// ```
+/// use std::io::Cursor;
+///
+/// let mut input = Cursor::new("var a; maximize obj: a; subject to cap: a <= 4;");
+/// let solution = rulp::solve_reader(&mut input).unwrap();
+/// assert_eq!(solution.objective, Some(4.));
+/// ```
+pub fn solve_reader<R: BufRead>(reader: &mut R) -> Result<Solution, RulpError> {
+	let mut text = String::new();
+	reader.read_to_string(&mut text)?;
+
+	let lp = Parser::with_options(Default::default()).try_parse_lp(&text, Builder::new()).map_err(RulpError::Parse)?;
+	Ok(Solver::new().solve(&lp))
+}
+
+/// Parses an LP in the text syntax, builds it with a default Builder,
+/// solves it with a default Solver and writes a report to `output`: the
+/// model, then the solution and the dual value of each constraint.
+///
+/// As in `solve_reader`, invalid syntax gives an error instead of a
+/// panic.
+///
+/// # Examples
+/// ```
+/// let mut report = vec![];
+/// rulp::run("var a; maximize obj: a; subject to cap: a <= 4;", &mut report).unwrap();
+///
+/// let report = String::from_utf8(report).unwrap();
+/// assert!(report.contains("Optimal objective: 4"));
+/// assert!(report.contains("cap: 1"));
+/// ```
+pub fn run<W: Write>(input: &str, output: &mut W) -> Result<(), RulpError> {
+	let lp = Parser::with_options(Default::default()).try_parse_lp(input, Builder::new()).map_err(RulpError::Parse)?;
+	let solution = Solver::new().solve(&lp);
+
+	write!(output, "{}", lp)?;
+	write!(output, "{}", solution)?;
+	if solution.duals.is_some() {
+		writeln!(output, "\nDuals:")?;
+		solution.write_duals(&lp, output)?;
+	}
+
+	Ok(())
+}
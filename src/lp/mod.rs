@@ -1,5 +1,10 @@
 use rulinalg::matrix::{BaseMatrix, Matrix};
+use rulinalg::vector::Vector;
+use std::collections::HashMap;
 use std::fmt;
+use builder::{Builder, BuilderBase, Constraint, Objective, Relation, Variable};
+use solver::{SimplexSolver, Solution, SolverBase, Status};
+use utils::XorShiftRng;
 
 #[derive(Debug, PartialEq, Clone)]
 pub struct Lp {
@@ -9,6 +14,206 @@ pub struct Lp {
 	pub optimization: Optimization,
 	pub vars: Vec<String>,
 	pub num_artificial_vars: usize,
+	pub constraint_names: Vec<String>,
+	pub ranges: Vec<RangedRow>,
+	pub kinds: Vec<VariableKind>,
+	/// Set on Lps produced by `to_minimization`, so solvers report the
+	/// objective (and duals) in the original sense
+	pub objective_negated: bool,
+}
+
+/// The values a variable may take, on top of being non-negative.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum VariableKind {
+	Continuous,
+	Integer,
+	/// An integer variable bounded above by 1
+	Binary,
+}
+
+/// The class of an Lp by the kinds of its declared variables.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum ProblemType {
+	/// LP: every variable is continuous
+	Linear,
+	/// ILP: every variable is integer or binary, not all of them binary
+	Integer,
+	/// MILP: continuous variables mixed with integer or binary ones
+	MixedInteger,
+	/// BILP: every variable is binary
+	BinaryInteger,
+}
+
+/// Bookkeeping for a ranged constraint `lower <= expr <= upper`.
+///
+/// The constraint is stored as the row `expr + slack = upper` together
+/// with a bound row `slack + range = upper - lower`, so `slack` is the
+/// distance to the upper bound and `range` the distance to the lower one.
+#[derive(Debug, PartialEq, Clone)]
+pub struct RangedRow {
+	pub row: usize,
+	pub bound_row: usize,
+	pub slack: usize,
+	pub range: usize,
+	pub lower: f64,
+	pub upper: f64,
+}
+
+/// An inconsistency between the dimensions of an Lp's fields.
+#[derive(Debug, PartialEq, Clone)]
+pub enum ValidationError {
+	/// `A` has a different number of rows than `b` has entries
+	RowCountMismatch { rows: usize, b: usize },
+	/// `A` has a different number of columns than `c` has entries
+	ColumnCountMismatch { cols: usize, c: usize },
+	/// There is not exactly one variable name per column of `A`
+	VariableCountMismatch { vars: usize, cols: usize },
+	/// There is not exactly one constraint name per row of `A`
+	ConstraintCountMismatch { constraint_names: usize, rows: usize },
+	/// More artificial variables are recorded than there are variables
+	ArtificialCountMismatch { num_artificial_vars: usize, vars: usize },
+	/// A ranged constraint refers to a row or column outside of `A`
+	InvalidRange { row: usize },
+	/// There is not exactly one variable kind per column of `A`
+	KindCountMismatch { kinds: usize, cols: usize },
+}
+
+impl fmt::Display for ValidationError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match *self {
+			ValidationError::RowCountMismatch { rows, b } =>
+				write!(f, "A has {} rows but b has {} entries", rows, b),
+			ValidationError::ColumnCountMismatch { cols, c } =>
+				write!(f, "A has {} columns but c has {} entries", cols, c),
+			ValidationError::VariableCountMismatch { vars, cols } =>
+				write!(f, "{} variable names for {} columns", vars, cols),
+			ValidationError::ConstraintCountMismatch { constraint_names, rows } =>
+				write!(f, "{} constraint names for {} rows", constraint_names, rows),
+			ValidationError::ArtificialCountMismatch { num_artificial_vars, vars } =>
+				write!(f, "{} artificial variables out of only {} variables", num_artificial_vars, vars),
+			ValidationError::InvalidRange { row } =>
+				write!(f, "ranged constraint on row {} is out of bounds", row),
+			ValidationError::KindCountMismatch { kinds, cols } =>
+				write!(f, "{} variable kinds for {} columns", kinds, cols),
+		}
+	}
+}
+
+/// Why an Lp can't be written in a format that only covers some models.
+#[derive(Debug, PartialEq, Clone)]
+pub enum ExportError {
+	/// A constraint is neither a node's flow balance nor an arc's capacity
+	NotANetwork { constraint: String },
+	/// A variable doesn't leave exactly one node and enter exactly one other
+	InvalidArc { variable: String },
+	/// A variable or constraint name is empty or has whitespace, which
+	/// separates the fields of an MPS line
+	InvalidName { name: String },
+}
+
+impl fmt::Display for ExportError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match *self {
+			ExportError::NotANetwork { ref constraint } =>
+				write!(f, "constraint \"{}\" is neither a flow balance nor a capacity", constraint),
+			ExportError::InvalidArc { ref variable } =>
+				write!(f, "variable \"{}\" is not the flow on an arc between two nodes", variable),
+			ExportError::InvalidName { ref name } =>
+				write!(f, "name \"{}\" can't be written as an MPS field", name),
+		}
+	}
+}
+
+/// The differences between two Lps, matching variables and constraints
+/// by name. See `Lp::diff`.
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct LpDiff {
+	pub added_variables: Vec<String>,
+	pub removed_variables: Vec<String>,
+	pub added_constraints: Vec<String>,
+	pub removed_constraints: Vec<String>,
+	/// Constraints present in both whose coefficients or constant differ
+	pub changed_constraints: Vec<String>,
+	/// `(variable, new - old)` for every objective coefficient that changed
+	pub objective_deltas: Vec<(String, f64)>,
+	pub optimization_changed: bool,
+}
+
+impl LpDiff {
+	/// Returns true if both Lps describe the same model.
+	pub fn is_empty(&self) -> bool {
+		*self == LpDiff::default()
+	}
+}
+
+/// An Lp made smaller by `Lp::presolve`, along with what it takes to turn
+/// its solutions into solutions of the original Lp.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Presolved {
+	/// The original Lp
+	pub original: Lp,
+	/// The reduced Lp, to be solved instead of the original
+	pub lp: Lp,
+	/// `(eliminated, kept)` for every variable substituted away, which
+	/// equals the variable kept for it, in the original declaration order
+	pub substitutions: Vec<(String, String)>
+}
+
+impl Presolved {
+	/// Maps a solution of the reduced Lp back onto the original one: each
+	/// eliminated variable takes the value of the one kept for it, and the
+	/// slack and excess variables of the original rows are worked out from
+	/// the declared ones.
+	///
+	/// The objective, status and iterations are kept. The duals, basis and
+	/// pivots refer to the rows and columns of the reduced Lp, so they are
+	/// left out.
+	pub fn restore(&self, solution: &Solution) -> Solution {
+		let original = &self.original;
+		let values = solution.values.as_ref().map(|values| {
+			let value_of = |name: &str| {
+				let kept = self.substitutions.iter().find(|&(eliminated, _)| eliminated == name).map_or(name, |(_, kept)| kept.as_str());
+				values[self.lp.vars.iter().position(|var| var == kept).unwrap()]
+			};
+			let num_structural = original.vars.len() - original.num_artificial_vars;
+			let mut restored: Vec<Option<f64>> = original.vars.iter().enumerate()
+				.map(|(col, var)| if col < num_structural { Some(value_of(var)) } else { None })
+				.collect();
+
+			// A ranged row's bound row needs its slack, found from the row
+			// itself, so rows are gone over until none has a single unknown
+			let mut progress = true;
+			while progress {
+				progress = false;
+				for row in 0 .. original.A.rows() {
+					let unknown: Vec<usize> = (0 .. original.A.cols())
+						.filter(|&col| original.A[[row, col]] != 0. && restored[col].is_none())
+						.collect();
+					if unknown.len() == 1 {
+						let known: f64 = (0 .. original.A.cols())
+							.filter_map(|col| restored[col].map(|value| original.A[[row, col]] * value))
+							.sum();
+						restored[unknown[0]] = Some((original.b[row] - known) / original.A[[row, unknown[0]]]);
+						progress = true;
+					}
+				}
+			}
+			restored.into_iter().map(|value| value.unwrap_or(0.)).collect()
+		});
+
+		Solution {
+			lp: original.clone(),
+			values,
+			objective: solution.objective,
+			duals: None,
+			farkas: None,
+			iterations: solution.iterations,
+			residuals: None,
+			status: solution.status.clone(),
+			pivots: vec![],
+			basis: None
+		}
+	}
 }
 
 #[derive(Debug, PartialEq, Clone)]
@@ -17,6 +222,1673 @@ pub enum Optimization {
 	Max,
 }
 
+impl Lp {
+	/// Compares two Lps, allowing every entry of `A`, `b` and `c` to
+	/// differ by at most `epsilon`. All other fields must match exactly.
+	pub fn approx_eq(&self, other: &Lp, epsilon: f64) -> bool {
+		let close = |x: &[f64], y: &[f64]| {
+			x.len() == y.len() && x.iter().zip(y.iter()).all(|(a, b)| (a - b).abs() <= epsilon)
+		};
+
+		self.A.rows() == other.A.rows()
+			&& self.A.cols() == other.A.cols()
+			&& close(self.A.data(), other.A.data())
+			&& close(&self.b, &other.b)
+			&& close(&self.c, &other.c)
+			&& self.optimization == other.optimization
+			&& self.vars == other.vars
+			&& self.num_artificial_vars == other.num_artificial_vars
+			&& self.constraint_names == other.constraint_names
+			&& self.ranges == other.ranges
+			&& self.kinds == other.kinds
+			&& self.objective_negated == other.objective_negated
+	}
+
+	/// Checks that the dimensions of `A`, `b`, `c` and the variable and
+	/// constraint names are consistent with each other.
+	///
+	/// # Examples
+	/// ```
+	/// use rulp::lp::{Lp, ValidationError};
+	///
+	/// let mut lp = Lp::random(3, 2, 0);
+	/// assert_eq!(lp.validate(), Ok(()));
+	///
+	/// lp.b.pop();
+	/// assert_eq!(lp.validate(), Err(ValidationError::RowCountMismatch { rows: 2, b: 1 }));
+	/// ```
+	pub fn validate(&self) -> Result<(), ValidationError> {
+		let rows = self.A.rows();
+		let cols = self.A.cols();
+
+		if rows != self.b.len() {
+			return Err(ValidationError::RowCountMismatch { rows, b: self.b.len() });
+		}
+		if cols != self.c.len() {
+			return Err(ValidationError::ColumnCountMismatch { cols, c: self.c.len() });
+		}
+		if cols != self.vars.len() {
+			return Err(ValidationError::VariableCountMismatch { vars: self.vars.len(), cols });
+		}
+		if rows != self.constraint_names.len() {
+			return Err(ValidationError::ConstraintCountMismatch {
+				constraint_names: self.constraint_names.len(),
+				rows
+			});
+		}
+		if self.num_artificial_vars > self.vars.len() {
+			return Err(ValidationError::ArtificialCountMismatch {
+				num_artificial_vars: self.num_artificial_vars,
+				vars: self.vars.len()
+			});
+		}
+		if cols != self.kinds.len() {
+			return Err(ValidationError::KindCountMismatch { kinds: self.kinds.len(), cols });
+		}
+		for range in &self.ranges {
+			if range.row >= rows || range.bound_row >= rows || range.slack >= cols || range.range >= cols {
+				return Err(ValidationError::InvalidRange { row: range.row });
+			}
+		}
+
+		Ok(())
+	}
+
+	/// Compares this Lp against `other`, reporting what changed going from
+	/// this one to the other. Variables and constraints are matched by
+	/// name, so reordering them is not a change. Constraints are compared
+	/// in their original form, as recovered by `to_constraints`, so the
+	/// slack and excess variables, whose names depend on the order of the
+	/// rows, are left out.
+	///
+	/// # Examples
+	/// ```
+	/// use rulp::lp::Lp;
+	///
+	/// let lp = Lp::random(3, 2, 0);
+	/// let mut other = lp.clone();
+	/// other.c[0] += 1.;
+	///
+	/// let diff = lp.diff(&other);
+	/// assert_eq!(diff.objective_deltas, vec![("x_0".to_string(), 1.)]);
+	/// assert!(lp.diff(&lp).is_empty());
+	/// ```
+	pub fn diff(&self, other: &Lp) -> LpDiff {
+		let mut diff = LpDiff {
+			optimization_changed: self.optimization != other.optimization,
+			..LpDiff::default()
+		};
+
+		let old_vars = &self.vars[.. self.vars.len() - self.num_artificial_vars];
+		let new_vars = &other.vars[.. other.vars.len() - other.num_artificial_vars];
+		diff.added_variables = new_vars.iter().filter(|var| !old_vars.contains(var)).cloned().collect();
+		diff.removed_variables = old_vars.iter().filter(|var| !new_vars.contains(var)).cloned().collect();
+
+		let old_objective = self.objective_by_name();
+		let new_objective = other.objective_by_name();
+		for var in old_vars.iter().chain(diff.added_variables.iter()) {
+			let old = old_objective.get(var.as_str()).cloned().unwrap_or(0.);
+			let new = new_objective.get(var.as_str()).cloned().unwrap_or(0.);
+			if old != new {
+				diff.objective_deltas.push((var.clone(), new - old));
+			}
+		}
+
+		let old_constraints = self.to_constraints();
+		let new_constraints = other.to_constraints();
+		for old in &old_constraints {
+			match new_constraints.iter().find(|new| new.name == old.name) {
+				None => diff.removed_constraints.push(old.name.clone()),
+				Some(new) => {
+					if old.constant != new.constant || old.relation != new.relation || terms_by_name(old) != terms_by_name(new) {
+						diff.changed_constraints.push(old.name.clone());
+					}
+				}
+			}
+		}
+		diff.added_constraints = new_constraints.iter()
+			.filter(|new| !old_constraints.iter().any(|old| old.name == new.name))
+			.map(|new| new.name.clone())
+			.collect();
+
+		diff
+	}
+
+	/// Shrinks the Lp before solving it by substituting variables forced to
+	/// be equal. Constraints `c*a - c*b == 0` merge their two variables into
+	/// one class, so chains like `a == b` and `b == c` end up in the same
+	/// one. Only the first declared variable of each class is kept: the
+	/// others are replaced by it in every constraint and the objective, and
+	/// the equalities themselves are dropped. The variable kept takes the
+	/// strictest kind of its class.
+	///
+	/// Solve `lp` of the result and `Presolved::restore` the solution to
+	/// get values for every variable of this Lp:
+	///
+	/// ```
+	/// use rulp::builder::{Builder, BuilderBase};
+	/// use rulp::parser::{Parser, ParserBase};
+	/// use rulp::solver::{SimplexSolver, SolverBase};
+	///
+	/// let lp = Parser::lp_from_text("
+	/// 	var a;
+	/// 	var b;
+	/// 	maximize obj: a + 2*b;
+	/// 	subject to same: a + -1*b == 0;
+	/// 	subject to cap: a + b <= 4;
+	/// ", Builder::new());
+	///
+	/// let presolved = lp.presolve();
+	/// assert_eq!(presolved.substitutions, vec![("b".to_string(), "a".to_string())]);
+	/// let solution = presolved.restore(&SimplexSolver::new(presolved.lp.clone()).solve());
+	/// assert_eq!(&solution.values.unwrap()[.. 2], &[2., 2.]);
+	/// ```
+	///
+	/// The objective is named `objective`, as its name isn't stored in
+	/// the Lp.
+	pub fn presolve(&self) -> Presolved {
+		let num_structural = self.vars.len() - self.num_artificial_vars;
+		let constraints = self.to_constraints();
+
+		// Union-find over the structural columns, each class rooted at its
+		// first declared column
+		let mut parent: Vec<usize> = (0 .. num_structural).collect();
+		for constraint in &constraints {
+			if let Some((first, second)) = equated_columns(self, constraint) {
+				let (first, second) = (find_root(&mut parent, first), find_root(&mut parent, second));
+				parent[first.max(second)] = first.min(second);
+			}
+		}
+
+		let mut substitutions: Vec<(String, String)> = vec![];
+		let mut kinds: Vec<VariableKind> = self.kinds[.. num_structural].to_vec();
+		for col in 0 .. num_structural {
+			let root = find_root(&mut parent, col);
+			if root != col {
+				kinds[root] = stricter_kind(kinds[root], kinds[col]);
+				substitutions.push((self.vars[col].clone(), self.vars[root].clone()));
+			}
+		}
+		let is_eliminated = |name: &str| substitutions.iter().any(|(eliminated, _)| eliminated == name);
+
+		let kept = |name: &str| -> String {
+			substitutions.iter().find(|&(eliminated, _)| eliminated == name).map_or(name, |(_, kept)| kept.as_str()).to_string()
+		};
+		let substitute = |terms: &[Variable]| -> Vec<Variable> {
+			let mut merged: Vec<Variable> = vec![];
+			for term in terms {
+				let name = kept(&term.name);
+				match merged.iter_mut().find(|other| other.name == name) {
+					Some(other) => other.coefficient += term.coefficient,
+					None => merged.push(Variable { name, coefficient: term.coefficient })
+				}
+			}
+			merged.retain(|term| term.coefficient != 0.);
+			merged
+		};
+
+		let mut builder = Builder::new();
+		for (var, &kind) in self.vars.iter().zip(kinds.iter()) {
+			if !is_eliminated(var) {
+				builder.add_variable(Variable { name: var.clone(), coefficient: 0. });
+				builder.set_variable_kind(var, kind);
+			}
+		}
+		for constraint in constraints {
+			let variables = substitute(&constraint.variables);
+			// The equalities used end up as `0 == 0`
+			if variables.is_empty() && constraint.constant == 0. && constraint.relation == Relation::Equal {
+				continue;
+			}
+			builder.add_constraint(Constraint { variables, ..constraint });
+		}
+		let objective: Vec<Variable> = self.vars[.. num_structural].iter().zip(self.c.iter())
+			.map(|(var, &coeff)| Variable { name: var.clone(), coefficient: coeff })
+			.collect();
+		builder.add_objective(Objective {
+			name: "objective".to_string(),
+			variables: substitute(&objective),
+			maximize: self.optimization == Optimization::Max
+		});
+
+		Presolved {
+			original: self.clone(),
+			lp: builder.build_lp(),
+			substitutions
+		}
+	}
+
+	/// Classifies the Lp by the kinds of its declared variables, which can
+	/// be used to pick between `Solver::solve` and `Solver::solve_mip`.
+	///
+	/// Slack, excess and range variables are left out, so an Lp without
+	/// declared variables counts as `Linear`.
+	pub fn problem_type(&self) -> ProblemType {
+		let kinds = &self.kinds[.. self.vars.len() - self.num_artificial_vars];
+		let integral = kinds.iter().filter(|&&kind| kind != VariableKind::Continuous).count();
+		let binary = kinds.iter().filter(|&&kind| kind == VariableKind::Binary).count();
+
+		if integral == 0 {
+			ProblemType::Linear
+		} else if integral < kinds.len() {
+			ProblemType::MixedInteger
+		} else if binary == kinds.len() {
+			ProblemType::BinaryInteger
+		} else {
+			ProblemType::Integer
+		}
+	}
+
+	/// Copies the Lp with every integer and binary variable made continuous.
+	///
+	/// Binary variables keep their `<var>_binary` rows, so they stay
+	/// between 0 and 1.
+	pub fn relax_integrality(&self) -> Lp {
+		let mut lp = self.clone();
+		lp.kinds = vec![VariableKind::Continuous; lp.kinds.len()];
+		lp
+	}
+
+	/// Solves the continuous relaxation, ignoring which variables are
+	/// integer or binary, and returns its objective.
+	///
+	/// This is the bound at the root of a `Solver::solve_mip` search: no
+	/// integer solution does better. Returns None if the relaxation is
+	/// infeasible or unbounded.
+	pub fn relaxation_bound(&self) -> Option<f64> {
+		let solution = SimplexSolver::new(self.clone()).solve();
+		match solution.status {
+			Status::Optimal => solution.objective,
+			_ => None
+		}
+	}
+
+	/// Rounds the relaxation bound to the nearest integer no integer
+	/// solution can beat: down when maximizing, up when minimizing.
+	///
+	/// This is only valid when the objective takes integer values at every
+	/// integer solution, so it is None unless every nonzero objective
+	/// coefficient is an integer on an integer or binary variable, as well
+	/// as when `relaxation_bound` is None.
+	pub fn integer_objective_bound(&self) -> Option<f64> {
+		// Keeps a bound within round-off of an integer from being rounded
+		// past it
+		const TOLERANCE: f64 = 1e-9;
+
+		let integer_valued = self.c.iter().zip(self.kinds.iter())
+			.all(|(&coeff, &kind)| coeff == 0. || (kind != VariableKind::Continuous && coeff == coeff.round()));
+		if !integer_valued {
+			return None;
+		}
+
+		// The bound is reported in the sense of the original objective
+		let maximizing = (self.optimization == Optimization::Max) != self.objective_negated;
+		self.relaxation_bound().map(|bound| if maximizing {
+			(bound + TOLERANCE).floor()
+		} else {
+			(bound - TOLERANCE).ceil()
+		})
+	}
+
+	/// Solves the Lp and tells whether its optimum may not be unique: some
+	/// nonbasic column, structural or slack, has a zero reduced cost, so
+	/// it could enter the basis without changing the objective. This is
+	/// what happens when the objective is parallel to a binding constraint.
+	///
+	/// At a degenerate optimum the entering column may not be able to move
+	/// away from zero, so this is an indicator rather than proof. False
+	/// when there is no optimal solution.
+	pub fn has_alternative_optima(&self) -> bool {
+		const TOLERANCE: f64 = 1e-9;
+
+		let solution = SimplexSolver::new(self.clone()).solve();
+		let (basis, duals) = match (solution.basis, solution.duals) {
+			(Some(basis), Some(duals)) if solution.status == Status::Optimal => (basis, duals),
+			_ => return false
+		};
+
+		// The duals are reported with the sign of the original objective
+		let y: Vec<f64> = duals.iter().map(|dual| dual * self.reported_sign()).collect();
+		(0 .. self.A.cols())
+			.filter(|col| !basis.contains(col))
+			.any(|col| {
+				let reduced_cost = self.c[col] - (0 .. self.A.rows()).map(|row| self.A[[row, col]] * y[row]).sum::<f64>();
+				reduced_cost.abs() <= TOLERANCE
+			})
+	}
+
+	/// Re-solves the Lp with the constant of the named constraint increased
+	/// by `delta` and returns how much the optimal objective changes.
+	///
+	/// For small changes this is the constraint's dual value times `delta`,
+	/// but the re-solve stays exact past the point where the basis changes.
+	/// For a ranged constraint its upper side moves. Panics if there is no
+	/// constraint with that name or either Lp has no optimal solution.
+	pub fn relax_constraint_effect(&self, name: &str, delta: f64) -> f64 {
+		let row = match self.constraint_names.iter().position(|constraint| constraint == name) {
+			Some(row) => row,
+			None => panic!("Unknown constraint \"{}\"", name)
+		};
+
+		let mut relaxed = self.clone();
+		relaxed.b[row] += delta;
+		for range in relaxed.ranges.iter_mut().filter(|range| range.row == row) {
+			range.upper += delta;
+			relaxed.b[range.bound_row] += delta;
+		}
+
+		match (self.relaxation_bound(), relaxed.relaxation_bound()) {
+			(Some(original), Some(changed)) => changed - original,
+			_ => panic!("No optimal solution with constraint \"{}\" relaxed by {}", name, delta)
+		}
+	}
+
+	/// Re-solves the continuous relaxation of the Lp with the objective
+	/// coefficient of the named variable increased by `delta` and returns
+	/// how much the optimal objective changes. Integer and binary kinds are
+	/// ignored, as in `relaxation_bound`.
+	///
+	/// While the optimal basis stays the same this is the variable's
+	/// optimal value times `delta`; past that point the re-solve follows the
+	/// new basis. Panics if there is no variable with that name or either
+	/// relaxation has no optimal solution.
+	pub fn objective_coefficient_effect(&self, name: &str, delta: f64) -> f64 {
+		let col = match self.vars.iter().position(|var| var == name) {
+			Some(col) => col,
+			None => panic!("Unknown variable \"{}\"", name)
+		};
+
+		let mut perturbed = self.clone();
+		perturbed.c[col] += delta;
+
+		match (self.relaxation_bound(), perturbed.relaxation_bound()) {
+			(Some(original), Some(changed)) => changed - original,
+			_ => panic!("No optimal solution with the coefficient of \"{}\" changed by {}", name, delta)
+		}
+	}
+
+	/// Finds the range `(min, max)` each declared variable takes over the
+	/// feasible region, by minimizing and maximizing it subject to the
+	/// constraints. This is tighter than any bound the constraints state
+	/// one by one, e.g. `a + b <= 4` and `b >= 1` give `a` a maximum of 3.
+	///
+	/// A variable the region doesn't bound from above gets a maximum of
+	/// `f64::INFINITY`. Integrality is ignored, and the map is empty if the Lp
+	/// is infeasible.
+	///
+	/// # Examples
+	/// ```
+	/// use rulp::builder::{Builder, BuilderBase};
+	/// use rulp::parser::{Parser, ParserBase};
+	///
+	/// let lp = Parser::lp_from_text("
+	/// 	var a;
+	/// 	var b;
+	/// 	maximize obj: a;
+	/// 	subject to total: a + b <= 4;
+	/// 	subject to floor: b >= 1;
+	/// ", Builder::new());
+	/// assert_eq!(lp.implied_variable_bounds()["a"], (0., 3.));
+	/// ```
+	pub fn implied_variable_bounds(&self) -> HashMap<String, (f64, f64)> {
+		let num_structural = self.vars.len() - self.num_artificial_vars;
+		let mut bounds = HashMap::new();
+		for col in 0 .. num_structural {
+			let extreme = |optimization: Optimization| {
+				let mut lp = self.clone();
+				lp.c = vec![0.; self.c.len()];
+				lp.c[col] = 1.;
+				lp.optimization = optimization;
+				lp.objective_negated = false;
+				SimplexSolver::new(lp).solve()
+			};
+
+			let min = extreme(Optimization::Min);
+			if min.status == Status::Infeasible {
+				return HashMap::new();
+			}
+			let max = extreme(Optimization::Max);
+			let upper = if max.status == Status::Unbounded { f64::INFINITY } else { max.objective.unwrap() };
+			bounds.insert(self.vars[col].clone(), (min.objective.unwrap(), upper));
+		}
+
+		bounds
+	}
+
+	/// Finds an irreducible infeasible subset of the constraints: the
+	/// constraints (original ones, as in `to_constraints`) left after
+	/// dropping each in turn that the rest stay infeasible without, so
+	/// the subset can't be satisfied together but any smaller part of it
+	/// can. Variables keep their bounds throughout. Returns None if the Lp
+	/// is feasible, ignoring integrality.
+	pub fn infeasible_subset(&self) -> Option<Vec<String>> {
+		let mut subset = self.to_constraints();
+		if self.is_feasible_with(&subset) {
+			return None;
+		}
+
+		let mut i = 0;
+		while i < subset.len() {
+			let dropped = subset.remove(i);
+			if self.is_feasible_with(&subset) {
+				subset.insert(i, dropped);
+				i += 1;
+			}
+		}
+
+		Some(subset.into_iter().map(|constraint| constraint.name).collect())
+	}
+
+	/// Explains in a sentence why the Lp is infeasible, by listing the
+	/// constraints of `infeasible_subset` as written, e.g. "Constraints c1
+	/// (a + b <= 5) and c2 (a + b >= 10) cannot both hold." Returns None
+	/// if the Lp is feasible, ignoring integrality.
+	pub fn explain_infeasibility(&self) -> Option<String> {
+		let names = self.infeasible_subset()?;
+		let constraints = self.to_constraints();
+		let described: Vec<String> = names.iter()
+			.map(|name| {
+				let constraint = constraints.iter().find(|constraint| constraint.name == *name).unwrap();
+				format!("{} ({})", name, constraint_text(constraint))
+			})
+			.collect();
+
+		Some(match described.len() {
+			0 => "The variables cannot all be non-negative.".to_string(),
+			1 => format!("Constraint {} cannot hold with non-negative variables.", described[0]),
+			2 => format!("Constraints {} and {} cannot both hold.", described[0], described[1]),
+			n => format!("Constraints {} and {} cannot all hold.", described[.. n - 1].join(", "), described[n - 1])
+		})
+	}
+
+	// Whether some point satisfies the given constraints on this Lp's
+	// variables, which are non-negative and binary ones at most 1
+	fn is_feasible_with(&self, constraints: &[Constraint]) -> bool {
+		let num_structural = self.vars.len() - self.num_artificial_vars;
+		let mut builder = Builder::new();
+		for name in &self.vars[.. num_structural] {
+			builder.add_variable(Variable { name: name.clone(), coefficient: 0. });
+		}
+		for (name, kind) in self.vars[.. num_structural].iter().zip(self.kinds.iter()) {
+			if *kind == VariableKind::Binary {
+				builder.set_variable_kind(name, VariableKind::Binary);
+			}
+		}
+		for constraint in constraints {
+			builder.add_constraint(constraint.clone());
+		}
+		builder.add_objective(Objective { name: "feasibility".to_string(), variables: vec![], maximize: false });
+
+		let lp = builder.build_lp();
+		SimplexSolver::new(lp).solve().status != Status::Infeasible
+	}
+
+	/// Returns the names of the constraints in which the variable has a
+	/// nonzero coefficient, in row order. The list is empty for an
+	/// unknown variable.
+	pub fn constraints_with_variable(&self, name: &str) -> Vec<String> {
+		let col = match self.vars.iter().position(|var| var == name) {
+			Some(col) => col,
+			None => return vec![]
+		};
+
+		(0 .. self.A.rows())
+			.filter(|&row| self.A[[row, col]] != 0.)
+			.map(|row| self.constraint_names[row].clone())
+			.collect()
+	}
+
+	/// Returns how far the named variable can increase from `point`, with
+	/// the other variables fixed, before some constraint becomes binding.
+	///
+	/// `point` holds a value per variable in declaration order and should
+	/// be feasible. This is the ratio test over the constraints the
+	/// variable appears in, taking both sides of ranged constraints and the
+	/// upper bound of binary variables into account. The headroom is
+	/// infinite when no constraint limits the increase. Panics for an
+	/// unknown variable.
+	pub fn variable_increase_headroom(&self, name: &str, point: &[f64]) -> f64 {
+		let col = match self.vars.iter().position(|var| var == name) {
+			Some(col) => col,
+			None => panic!("Unknown variable \"{}\"", name)
+		};
+
+		let mut headroom = match self.kinds[col] {
+			VariableKind::Binary => 1. - point[col],
+			_ => f64::INFINITY
+		};
+		for constraint in self.to_constraints() {
+			let coeff = match constraint.variables.iter().find(|var| var.name == name) {
+				Some(var) => var.coefficient,
+				None => continue
+			};
+			let activity: f64 = constraint.variables.iter()
+				.map(|var| var.coefficient * point[self.vars.iter().position(|v| *v == var.name).unwrap()])
+				.sum();
+
+			let limit = match constraint.relation {
+				Relation::Equal => 0.,
+				Relation::LessThanOrEqual if coeff > 0. => (constraint.constant - activity) / coeff,
+				Relation::GreaterThanOrEqual if coeff < 0. => (constraint.constant - activity) / coeff,
+				Relation::Range(_) if coeff > 0. => (constraint.constant - activity) / coeff,
+				Relation::Range(lower) => (lower - activity) / coeff,
+				_ => continue
+			};
+			headroom = headroom.min(limit.max(0.));
+		}
+
+		headroom
+	}
+
+	/// Sums how far `point` is from satisfying each constraint, which is
+	/// zero exactly when the point is feasible.
+	///
+	/// `point` holds a value per variable in declaration order. A ranged
+	/// constraint counts by how far it is outside its range, and the
+	/// non-negativity of every variable (and the upper bound of binary
+	/// ones) counts like a constraint of its own.
+	pub fn total_violation(&self, point: &[f64]) -> f64 {
+		let bounds: f64 = point.iter().zip(self.kinds.iter())
+			.map(|(&value, &kind)| match kind {
+				VariableKind::Binary => (-value).max(value - 1.).max(0.),
+				_ => (-value).max(0.)
+			})
+			.sum();
+
+		let constraints: f64 = self.to_constraints().iter()
+			.map(|constraint| {
+				let activity: f64 = constraint.variables.iter()
+					.map(|var| var.coefficient * point[self.vars.iter().position(|v| *v == var.name).unwrap()])
+					.sum();
+				match constraint.relation {
+					Relation::Equal => (activity - constraint.constant).abs(),
+					Relation::LessThanOrEqual => (activity - constraint.constant).max(0.),
+					Relation::GreaterThanOrEqual => (constraint.constant - activity).max(0.),
+					Relation::Range(lower) => (activity - constraint.constant).max(lower - activity).max(0.)
+				}
+			})
+			.sum();
+
+		bounds + constraints
+	}
+
+	/// Tells whether the feasible region is a single point: the equality
+	/// constraints pin every variable to one value, and that point also
+	/// satisfies the rest of the constraints. Any objective is then
+	/// optimal at that point, which `Solver::solve` returns without
+	/// pivoting if the `singleton_check` option is set.
+	///
+	/// # Examples
+	/// ```
+	/// use rulp::builder::{Builder, BuilderBase};
+	/// use rulp::parser::{Parser, ParserBase};
+	///
+	/// let lp = Parser::lp_from_text("
+	/// 	var a;
+	/// 	var b;
+	/// 	maximize objective: a + b;
+	/// 	subject to sum: a + b == 3;
+	/// 	subject to difference: a - b == 1;
+	/// ", Builder::new());
+	/// assert!(lp.is_singleton_feasible());
+	/// ```
+	pub fn is_singleton_feasible(&self) -> bool {
+		self.singleton_point().is_some()
+	}
+
+	// The value of every column, slacks included, at the only feasible
+	// point, or None if the feasible region is empty or not a point
+	pub(crate) fn singleton_point(&self) -> Option<Vec<f64>> {
+		const TOLERANCE: f64 = 1e-7;
+
+		let num_structural = self.vars.len() - self.num_artificial_vars;
+		let structural: Vec<usize> = (0 .. num_structural).collect();
+		let artificial: Vec<usize> = (num_structural .. self.A.cols()).collect();
+		let rows: Vec<usize> = (0 .. self.A.rows()).collect();
+		let equalities: Vec<usize> = rows.iter().cloned()
+			.filter(|&row| artificial.iter().all(|&col| self.A[[row, col]] == 0.))
+			.collect();
+		if num_structural == 0 || equalities.len() < num_structural {
+			return None;
+		}
+
+		let b: Vec<f64> = equalities.iter().map(|&row| self.b[row]).collect();
+		let mut point = solve_subsystem(&self.A, &equalities, &structural, &b, TOLERANCE)?;
+		let integral = point.iter().zip(self.kinds.iter())
+			.all(|(&value, &kind)| kind == VariableKind::Continuous || (value - value.round()).abs() <= TOLERANCE);
+		if !integral || self.total_violation(&point) > TOLERANCE {
+			return None;
+		}
+
+		// The slack, excess and range columns follow from the structural
+		// values, one per inequality row
+		if !artificial.is_empty() {
+			let rest: Vec<f64> = rows.iter()
+				.map(|&row| self.b[row] - structural.iter().map(|&col| self.A[[row, col]] * point[col]).sum::<f64>())
+				.collect();
+			match solve_subsystem(&self.A, &rows, &artificial, &rest, TOLERANCE) {
+				Some(values) => point.extend(values),
+				None => return None
+			}
+		}
+
+		Some(point.into_iter().map(|value| value.max(0.)).collect())
+	}
+
+	// Nonzero objective coefficients keyed by variable name
+	fn objective_by_name(&self) -> HashMap<&str, f64> {
+		self.vars.iter().zip(self.c.iter())
+			.filter(|&(_, &coeff)| coeff != 0.)
+			.map(|(var, &coeff)| (var.as_str(), coeff))
+			.collect()
+	}
+
+	/// Returns an equivalent minimization problem: a maximization's
+	/// objective is negated and its sense flipped, a minimization is
+	/// returned unchanged.
+	///
+	/// Solutions of the returned Lp still report the objective value of
+	/// the original problem.
+	///
+	/// # Examples
+	/// ```
+	/// use rulp::lp::{Lp, Optimization};
+	/// use rulp::solver::Solver;
+	///
+	/// let lp = Lp::random(3, 2, 4);
+	/// let min = lp.to_minimization();
+	/// assert_eq!(min.optimization, Optimization::Min);
+	///
+	/// let max_objective = Solver::new().solve(&lp).objective.unwrap();
+	/// let min_objective = Solver::new().solve(&min).objective.unwrap();
+	/// assert!((max_objective - min_objective).abs() < 1e-9);
+	/// ```
+	pub fn to_minimization(&self) -> Lp {
+		let mut lp = self.clone();
+		if lp.optimization == Optimization::Max {
+			lp.optimization = Optimization::Min;
+			lp.c = lp.c.iter().map(|coeff| -coeff).collect();
+			lp.objective_negated = !lp.objective_negated;
+		}
+
+		lp
+	}
+
+	/// Builds the elastic version of the Lp, which minimizes the total
+	/// violation of its constraints and is always feasible.
+	///
+	/// Every row gets two new variables after the declared ones:
+	/// `<constraint>_shortfall`, how far the activity may fall below the
+	/// constant, and `<constraint>_overflow`, how far it may go above it.
+	/// Both cost 1, so the optimum is zero exactly when the Lp is feasible,
+	/// and the nonzero ones point at the constraints to relax. Variable
+	/// kinds are kept.
+	pub fn to_feasibility_restoration(&self) -> Lp {
+		let rows = self.A.rows();
+		let cols = self.A.cols();
+		let num_structural = self.vars.len() - self.num_artificial_vars;
+		let new_cols = cols + 2 * rows;
+		let column = |col: usize| if col < num_structural { col } else { col + 2 * rows };	// Artificials move past the elastics
+
+		let mut A = vec![0.; rows * new_cols];
+		for row in 0 .. rows {
+			for col in 0 .. cols {
+				A[row * new_cols + column(col)] = self.A[[row, col]];
+			}
+			A[row * new_cols + num_structural + 2 * row] = 1.;
+			A[row * new_cols + num_structural + 2 * row + 1] = -1.;
+		}
+
+		let mut vars = self.vars[.. num_structural].to_vec();
+		let mut kinds = self.kinds[.. num_structural].to_vec();
+		for name in &self.constraint_names {
+			vars.push(format!("{}_shortfall", name));
+			vars.push(format!("{}_overflow", name));
+			kinds.push(VariableKind::Continuous);
+			kinds.push(VariableKind::Continuous);
+		}
+		vars.extend_from_slice(&self.vars[num_structural ..]);
+		kinds.extend_from_slice(&self.kinds[num_structural ..]);
+
+		let mut c = vec![0.; new_cols];
+		for coeff in &mut c[num_structural .. num_structural + 2 * rows] {
+			*coeff = 1.;
+		}
+
+		Lp {
+			A: Matrix::new(rows, new_cols, A),
+			b: self.b.clone(),
+			c,
+			optimization: Optimization::Min,
+			vars,
+			num_artificial_vars: self.num_artificial_vars,
+			constraint_names: self.constraint_names.clone(),
+			ranges: self.ranges.iter().map(|range| RangedRow {
+				slack: column(range.slack),
+				range: column(range.range),
+				..range.clone()
+			}).collect(),
+			kinds,
+			objective_negated: false
+		}
+	}
+
+	/// Checks whether `primal` (one value per column, slacks included) and
+	/// `dual` (one value per row) are optimal for this Lp, i.e. satisfy the
+	/// KKT conditions:
+	///
+	/// * primal feasibility: `Ax = b` and `x >= 0`
+	/// * dual feasibility: every reduced cost `c_j - A_j^T y` is `<= 0` when
+	///   maximizing (`>= 0` when minimizing)
+	/// * complementary slackness: each `x_j` or its reduced cost is zero
+	///
+	/// Each condition may be off by `epsilon` times the magnitude of the
+	/// data it involves.
+	pub fn check_complementary_slackness(&self, primal: &[f64], dual: &[f64], epsilon: f64) -> bool {
+		let m = self.A.rows();
+		let n = self.A.cols();
+		if primal.len() != n || dual.len() != m {
+			return false;
+		}
+
+		for i in 0 .. m {
+			let lhs: f64 = (0 .. n).map(|j| self.A[[i, j]] * primal[j]).sum();
+			if (lhs - self.b[i]).abs() > epsilon * (1. + self.b[i].abs()) {
+				return false;
+			}
+		}
+
+		let sign = if self.optimization == Optimization::Max { 1. } else { -1. };
+		for (j, &value) in primal.iter().enumerate() {
+			let reduced_cost = self.c[j] - (0 .. m).map(|i| self.A[[i, j]] * dual[i]).sum::<f64>();
+			let scale = epsilon * (1. + self.c[j].abs());
+			if value < -epsilon || sign * reduced_cost > scale || (value * reduced_cost).abs() > scale * (1. + value.abs()) {
+				return false;
+			}
+		}
+
+		true
+	}
+
+	/// Builds the dual of the Lp.
+	///
+	/// With the problem as `min c'x s.t. Ax = b, x >= 0` (see
+	/// `to_equality_form`), the dual is `max b'y s.t. A'y <= c` with `y`
+	/// free. Each `y_i` is written as the difference of two nonnegative
+	/// variables `y_<i>_pos` and `y_<i>_neg`, and column `j` of the Lp
+	/// gives the constraint `dual_<j>`.
+	pub fn dual(&self) -> Lp {
+		let (A, b, c) = self.to_equality_form();
+
+		let mut builder = Builder::new();
+		let mut objective = vec![];
+		for (i, &constant) in b.iter().enumerate() {
+			for &(sign, suffix) in &[(1., "pos"), (-1., "neg")] {
+				let name = format!("y_{}_{}", i, suffix);
+				builder.add_variable(Variable { name: name.clone(), coefficient: 0. });
+				objective.push(Variable { name, coefficient: sign * constant });
+			}
+		}
+		for j in 0 .. A.cols() {
+			let mut variables = vec![];
+			for i in 0 .. A.rows() {
+				variables.push(Variable { name: format!("y_{}_pos", i), coefficient: A[[i, j]] });
+				variables.push(Variable { name: format!("y_{}_neg", i), coefficient: -A[[i, j]] });
+			}
+			builder.add_constraint(Constraint {
+				name: format!("dual_{}", j),
+				variables,
+				constant: c[j],
+				relation: Relation::LessThanOrEqual
+			});
+		}
+		builder.add_objective(Objective {
+			name: "dual".to_string(),
+			variables: objective,
+			maximize: true
+		});
+
+		builder.build_lp()
+	}
+
+	/// Solves both this Lp and its dual (see `dual`) and checks that their
+	/// optimal objectives agree within `epsilon` times the objective's
+	/// magnitude, the objective of this Lp being taken in the equality
+	/// form's `min c'x`.
+	///
+	/// Returns false if either problem has no optimum.
+	pub fn verify_strong_duality(&self, epsilon: f64) -> bool {
+		let (_, _, c) = self.to_equality_form();
+
+		let primal = match SimplexSolver::new(self.clone()).solve().values {
+			Some(values) => c.iter().zip(values.iter()).map(|(coeff, value)| coeff * value).sum::<f64>(),
+			None => return false
+		};
+
+		match SimplexSolver::new(self.dual()).solve().objective {
+			Some(dual) => (primal - dual).abs() <= epsilon * (1. + primal.abs()),
+			None => false
+		}
+	}
+
+	/// Finds the feasible point closest to `point` in L1 distance, which
+	/// unlike the Euclidean projection can be computed as an Lp.
+	///
+	/// `point` holds one value per declared variable and so does the
+	/// result. Every variable `x` gets a distance `d >= |x - point|`, and
+	/// the distances are minimized subject to this Lp's constraints.
+	/// Returns None if the Lp is infeasible. Panics if `point` has the
+	/// wrong length.
+	pub fn project_point(&self, point: &[f64]) -> Option<Vec<f64>> {
+		let num_structural = self.vars.len() - self.num_artificial_vars;
+		if point.len() != num_structural {
+			panic!("Point has {} values for {} variables", point.len(), num_structural);
+		}
+
+		// Columns are renamed, as the slack columns' names would clash with
+		// the builder's own slack variables
+		let column = |col: usize| format!("x_{}", col);
+		let mut builder = Builder::new();
+		for col in 0 .. self.A.cols() {
+			builder.add_variable(Variable { name: column(col), coefficient: 0. });
+		}
+		for row in 0 .. self.A.rows() {
+			builder.add_constraint(Constraint {
+				name: self.constraint_names[row].clone(),
+				variables: (0 .. self.A.cols())
+					.filter(|&col| self.A[[row, col]] != 0.)
+					.map(|col| Variable { name: column(col), coefficient: self.A[[row, col]] })
+					.collect(),
+				constant: self.b[row],
+				relation: Relation::Equal
+			});
+		}
+
+		let mut distances = vec![];
+		for (col, &value) in point.iter().enumerate() {
+			let distance = format!("d_{}", col);
+			builder.add_variable(Variable { name: distance.clone(), coefficient: 0. });
+			for &(sign, side) in &[(1., "above"), (-1., "below")] {			// d >= x - point and d >= point - x
+				builder.add_constraint(Constraint {
+					name: format!("{}_{}", distance, side),
+					variables: vec![
+						Variable { name: column(col), coefficient: sign },
+						Variable { name: distance.clone(), coefficient: -1. }
+					],
+					constant: sign * value,
+					relation: Relation::LessThanOrEqual
+				});
+			}
+			distances.push(Variable { name: distance, coefficient: 1. });
+		}
+		builder.add_objective(Objective {
+			name: "distance".to_string(),
+			variables: distances,
+			maximize: false
+		});
+
+		SimplexSolver::new(builder.build_lp()).solve().values.map(|mut values| {
+			values.truncate(num_structural);
+			values
+		})
+	}
+
+	/// Computes the analytic center of the feasible region: the point of
+	/// `Ax = b, x >= 0` maximizing `sum(ln x_j)`, so every variable
+	/// (including slacks) is as far from its bound as possible.
+	///
+	/// Found by Newton's method started from all ones. Returns None if the
+	/// region is unbounded, has no interior (some variable is forced to
+	/// zero) or is empty.
+	///
+	/// # Examples
+	/// ```
+	/// use rulp::lp::Lp;
+	///
+	/// let lp = Lp::random(3, 4, 2);
+	/// let center = lp.analytic_center().unwrap();
+	/// assert!(center.iter().all(|&x| x > 0.));
+	/// ```
+	pub fn analytic_center(&self) -> Option<Vec<f64>> {
+		const MAX_ITERATIONS: usize = 200;
+		const TOLERANCE: f64 = 1e-10;
+		const DIVERGENCE: f64 = 1e10;
+
+		let m = self.A.rows();
+		let n = self.A.cols();
+		let mut x = vec![1.; n];
+
+		for _ in 0 .. MAX_ITERATIONS {
+			let residual: Vec<f64> = (0 .. m)
+				.map(|i| self.b[i] - (0 .. n).map(|j| self.A[[i, j]] * x[j]).sum::<f64>())
+				.collect();
+
+			// Newton step for max sum(ln x) s.t. Ax = b from the (possibly
+			// infeasible) x: (A X^2 A^T) w = Ax - r, dx = x - X^2 A^T w
+			let mut normal = vec![0.; m * m];
+			for i in 0 .. m {
+				for k in 0 .. m {
+					normal[i * m + k] = (0 .. n).map(|j| self.A[[i, j]] * x[j] * x[j] * self.A[[k, j]]).sum();
+				}
+				normal[i * m + i] += 1e-12;
+			}
+			let rhs: Vec<f64> = (0 .. m)
+				.map(|i| (0 .. n).map(|j| self.A[[i, j]] * x[j]).sum::<f64>() - residual[i])
+				.collect();
+			let w = if m == 0 {
+				vec![]
+			} else {
+				match Matrix::new(m, m, normal).solve(Vector::new(rhs)) {
+					Ok(w) => w.into_vec(),
+					Err(_) => return None
+				}
+			};
+			let dx: Vec<f64> = (0 .. n)
+				.map(|j| x[j] - x[j] * x[j] * (0 .. m).map(|i| self.A[[i, j]] * w[i]).sum::<f64>())
+				.collect();
+
+			let decrement = (0 .. n).map(|j| (dx[j] / x[j]).powi(2)).sum::<f64>().sqrt();
+			let infeasibility = residual.iter().map(|r| r.abs()).fold(0., f64::max);
+			if decrement < TOLERANCE && infeasibility < TOLERANCE * (1. + self.b.iter().map(|b| b.abs()).fold(0., f64::max)) {
+				return Some(x);
+			}
+
+			let mut step = 1. / (1. + decrement);								// Damped Newton step, also kept
+			for j in 0 .. n {													// strictly inside x > 0
+				if dx[j] < 0. {
+					step = step.min(-0.99 * x[j] / dx[j]);
+				}
+			}
+			for j in 0 .. n {
+				x[j] += step * dx[j];
+			}
+
+			if x.iter().any(|&value| value > DIVERGENCE || !value.is_finite()) {
+				return None;
+			}
+		}
+
+		None
+	}
+
+	/// Computes the Chebyshev center of the feasible region: the center and
+	/// radius of the largest ball, over the declared variables, that fits
+	/// inside it.
+	///
+	/// Solved as the LP maximizing `r` subject to `a'x + ||a|| r <= b` for
+	/// every inequality among the constraints recovered by `to_constraints`
+	/// (ranged constraints count as two, non-negativity bounds and the
+	/// upper bounds of binary variables as one each), while equality
+	/// constraints stay as they are and the ball lies within them. Returns
+	/// None if the region is empty or holds arbitrarily large balls.
+	pub fn chebyshev_center(&self) -> Option<(Vec<f64>, f64)> {
+		let num_structural = self.vars.len() - self.num_artificial_vars;
+		let column = |col: usize| format!("x_{}", col);						// Renamed as in project_point
+		let mut builder = Builder::new();
+		for col in 0 .. num_structural {
+			builder.add_variable(Variable { name: column(col), coefficient: 0. });
+		}
+		builder.add_variable(Variable { name: "radius".to_string(), coefficient: 0. });
+
+		let mut add_row = |name: String, coefficients: &[f64], constant: f64, relation: Relation| {
+			let mut variables: Vec<Variable> = coefficients.iter().enumerate()
+				.filter(|&(_, &coeff)| coeff != 0.)
+				.map(|(col, &coeff)| Variable { name: column(col), coefficient: coeff })
+				.collect();
+			if variables.is_empty() {
+				return;
+			}
+			if relation == Relation::LessThanOrEqual {
+				let norm = coefficients.iter().map(|coeff| coeff * coeff).sum::<f64>().sqrt();
+				variables.push(Variable { name: "radius".to_string(), coefficient: norm });
+			}
+			builder.add_constraint(Constraint { name, variables, constant, relation });
+		};
+
+		for constraint in self.to_constraints() {
+			let name = constraint.name.clone();
+			let coefficients = self.dense_coefficients(&constraint);
+			let negated: Vec<f64> = coefficients.iter().map(|coeff| -coeff).collect();
+			match constraint.relation {
+				Relation::Range(lower) => {
+					add_row(format!("{}_upper", name), &coefficients, constraint.constant, Relation::LessThanOrEqual);
+					add_row(format!("{}_lower", name), &negated, -lower, Relation::LessThanOrEqual);
+				},
+				Relation::LessThanOrEqual => add_row(name, &coefficients, constraint.constant, Relation::LessThanOrEqual),
+				Relation::GreaterThanOrEqual => add_row(name, &negated, -constraint.constant, Relation::LessThanOrEqual),
+				Relation::Equal => add_row(name, &coefficients, constraint.constant, Relation::Equal)
+			}
+		}
+		for col in 0 .. num_structural {										// -x <= 0
+			let mut coefficients = vec![0.; num_structural];
+			coefficients[col] = -1.;
+			add_row(format!("{}_nonnegative", column(col)), &coefficients, 0., Relation::LessThanOrEqual);
+			if self.kinds[col] == VariableKind::Binary {						// x <= 1
+				coefficients[col] = 1.;
+				add_row(format!("{}_binary", column(col)), &coefficients, 1., Relation::LessThanOrEqual);
+			}
+		}
+
+		builder.add_objective(Objective {
+			name: "radius".to_string(),
+			variables: vec![Variable { name: "radius".to_string(), coefficient: 1. }],
+			maximize: true
+		});
+
+		let solution = SimplexSolver::new(builder.build_lp()).solve();
+		match (solution.values, solution.objective) {
+			(Some(mut values), Some(radius)) => {
+				values.truncate(num_structural);
+				Some((values, radius))
+			},
+			_ => None
+		}
+	}
+
+	/// Renders the Lp as a LaTeX `align*` block, one line for the objective
+	/// and one per constraint, aligned at the relations.
+	///
+	/// Constraints are written in their original form, as recovered by
+	/// `to_constraints`: slack and excess variables are left out and turned
+	/// back into `\leq` and `\geq`, and ranged constraints are written as
+	/// `lower \leq expr \leq upper`. Binary variables get a line of their
+	/// own instead of their `<var>_binary` rows. The block starts by
+	/// providing the `\maximize`/`\minimize` commands used for the
+	/// objective.
+	pub fn to_latex(&self) -> String {
+		let num_structural = self.vars.len() - self.num_artificial_vars;
+		let sense = if self.optimization == Optimization::Max { "maximize" } else { "minimize" };
+
+		let mut latex = String::new();
+		latex.push_str(&format!("\\providecommand{{\\{0}}}{{\\operatorname{{{0}}}}}\n", sense));
+		latex.push_str("\\begin{align*}\n");
+		latex.push_str(&format!("\\{} \\quad {} & \\\\\n", sense, self.latex_expression(&self.c[.. num_structural])));
+		latex.push_str("\\text{subject to} & \\\\\n");
+
+		for constraint in self.to_constraints() {
+			let expression = self.latex_expression(&self.dense_coefficients(&constraint));
+			match constraint.relation {
+				Relation::Range(lower) => {
+					latex.push_str(&format!("{} \\leq {} &\\leq {} \\\\\n", lower, expression, constraint.constant));
+				},
+				ref relation => {
+					let relation = match *relation {
+						Relation::LessThanOrEqual => "\\leq",
+						Relation::GreaterThanOrEqual => "\\geq",
+						_ => "="
+					};
+					latex.push_str(&format!("{} &{} {} \\\\\n", expression, relation, constraint.constant));
+				}
+			}
+		}
+
+		let names: Vec<String> = self.vars[.. num_structural].iter().map(|var| latex_name(var)).collect();
+		let binary: Vec<String> = self.vars.iter().zip(self.kinds.iter())
+			.filter(|&(_, kind)| *kind == VariableKind::Binary)
+			.map(|(var, _)| latex_name(var))
+			.collect();
+		if binary.is_empty() {
+			latex.push_str(&format!("{} &\\geq 0\n", names.join(", ")));
+		} else {
+			latex.push_str(&format!("{} &\\geq 0 \\\\\n", names.join(", ")));
+			latex.push_str(&format!("{} &\\in \\{{0, 1\\}}\n", binary.join(", ")));
+		}
+		latex.push_str("\\end{align*}\n");
+		latex
+	}
+
+	/// Recovers the constraints the Lp was built from, in their original
+	/// form: slack, excess and range columns are left out and turned back
+	/// into relations, and the bound rows of ranged constraints and the
+	/// `<var>_binary` rows added for binary variables are skipped.
+	pub fn to_constraints(&self) -> Vec<Constraint> {
+		let num_structural = self.vars.len() - self.num_artificial_vars;
+		let mut constraints = vec![];
+
+		for row in 0 .. self.A.rows() {
+			let name = &self.constraint_names[row];
+			let implied_by_kind = (0 .. num_structural).any(|col| self.is_binary_row(row, col));
+			if implied_by_kind || self.ranges.iter().any(|range| range.bound_row == row) {
+				continue;
+			}
+
+			let (constant, relation) = match self.ranges.iter().find(|range| range.row == row) {
+				Some(range) => (range.upper, Relation::Range(range.lower)),
+				None => {
+					let artificial = (num_structural .. self.A.cols()).map(|col| self.A[[row, col]]).find(|&coeff| coeff != 0.);
+					let relation = match artificial {
+						Some(coeff) if coeff > 0. => Relation::LessThanOrEqual,
+						Some(_) => Relation::GreaterThanOrEqual,
+						None => Relation::Equal
+					};
+					(self.b[row], relation)
+				}
+			};
+
+			constraints.push(Constraint {
+				name: name.clone(),
+				variables: (0 .. num_structural)
+					.filter(|&col| self.A[[row, col]] != 0.)
+					.map(|col| Variable { name: self.vars[col].clone(), coefficient: self.A[[row, col]] })
+					.collect(),
+				constant,
+				relation
+			});
+		}
+
+		constraints
+	}
+
+	// Whether the row is the `<var>_binary` row the builder adds for a
+	// binary column, possibly renamed to `<var>_binary_2`, ...: named
+	// after the column and reading `var + slack = 1` with nothing else
+	fn is_binary_row(&self, row: usize, col: usize) -> bool {
+		let num_structural = self.vars.len() - self.num_artificial_vars;
+		let base = format!("{}_binary", self.vars[col]);
+		let name = &self.constraint_names[row];
+		let named_after = *name == base || name.starts_with(&format!("{}_", base))
+			&& name[base.len() + 1 ..].parse::<usize>().is_ok();
+		let others = (0 .. num_structural).filter(|&other| other != col && self.A[[row, other]] != 0.).count();
+		let slack = (num_structural .. self.A.cols()).filter(|&other| self.A[[row, other]] != 0.).collect::<Vec<_>>();
+		self.kinds[col] == VariableKind::Binary && named_after && others == 0 && self.A[[row, col]] == 1.
+			&& self.b[row] == 1. && slack.len() == 1 && self.A[[row, slack[0]]] > 0.
+	}
+
+	/// Generates Rust source for the `Builder` calls that reconstruct this
+	/// model: one `add_variable` per declared variable, `set_variable_kind`
+	/// for integer and binary ones, one `add_constraint` per constraint and
+	/// the `add_objective`, ending in `let lp = builder.build_lp();`.
+	///
+	/// Constraints are recovered in their original form by `to_constraints`.
+	/// The objective is named `objective`, since its name isn't stored in
+	/// the Lp. The code expects `rulp::builder::*` to be in scope.
+	pub fn to_rust_builder_code(&self) -> String {
+		let num_structural = self.vars.len() - self.num_artificial_vars;
+		let variables = |coefficients: &[f64]| -> String {
+			coefficients.iter().enumerate()
+				.filter(|&(_, &coeff)| coeff != 0.)
+				.map(|(col, coeff)| format!("\t\tVariable {{ name: {:?}.to_string(), coefficient: {:?} }},\n", self.vars[col], coeff))
+				.collect()
+		};
+
+		let mut code = String::from("let mut builder = Builder::new();\n");
+		for var in &self.vars[.. num_structural] {
+			code.push_str(&format!("builder.add_variable(Variable {{ name: {:?}.to_string(), coefficient: 0. }});\n", var));
+		}
+		for (var, kind) in self.vars.iter().zip(self.kinds.iter()).take(num_structural) {
+			if *kind != VariableKind::Continuous {
+				code.push_str(&format!("builder.set_variable_kind({:?}, VariableKind::{:?});\n", var, kind));
+			}
+		}
+
+		for constraint in self.to_constraints() {
+			let relation = match constraint.relation {
+				Relation::Range(lower) => format!("Relation::Range({:?})", lower),
+				relation => format!("Relation::{:?}", relation)
+			};
+			let variables: String = constraint.variables.iter()
+				.map(|var| format!("\t\tVariable {{ name: {:?}.to_string(), coefficient: {:?} }},\n", var.name, var.coefficient))
+				.collect();
+			code.push_str(&format!(
+				"builder.add_constraint(Constraint {{\n\tname: {:?}.to_string(),\n\tvariables: vec![\n{}\t],\n\tconstant: {:?},\n\trelation: {}\n}});\n",
+				constraint.name, variables, constraint.constant, relation
+			));
+		}
+
+		code.push_str(&format!(
+			"builder.add_objective(Objective {{\n\tname: \"objective\".to_string(),\n\tvariables: vec![\n{}\t],\n\tmaximize: {}\n}});\n",
+			variables(&self.c[.. num_structural]), self.optimization == Optimization::Max
+		));
+		code.push_str("let lp = builder.build_lp();\n");
+		code
+	}
+
+	/// Writes the Lp in the DIMACS min-cost flow format, if it is a network
+	/// flow model like those built by `NetworkParser`.
+	///
+	/// Each equality constraint is a node, with its constant as the supply,
+	/// and each variable an arc, leaving the node where it has coefficient
+	/// 1 and entering the one where it has -1. Constraints on a single
+	/// variable bounding it above give the arcs' capacities; arcs without
+	/// one are capped at the total supply, which no flow needs to exceed.
+	/// Costs are taken in minimization form. Names are kept in `c` comment
+	/// lines. Any other constraint, or a variable not linking two nodes,
+	/// gives an ExportError.
+	pub fn to_dimacs(&self) -> Result<String, ExportError> {
+		let num_structural = self.vars.len() - self.num_artificial_vars;
+		let (_, _, c) = self.to_equality_form();
+		let mut nodes = vec![];
+		let mut capacities = vec![None; num_structural];
+
+		for constraint in self.to_constraints() {
+			let not_a_network = || ExportError::NotANetwork { constraint: constraint.name.clone() };
+			match constraint.relation {
+				Relation::Equal => nodes.push(constraint.name.clone()),
+				Relation::LessThanOrEqual if constraint.variables.len() == 1 && constraint.variables[0].coefficient > 0. => {
+					let col = self.vars.iter().position(|var| *var == constraint.variables[0].name).unwrap();
+					let capacity = constraint.constant / constraint.variables[0].coefficient;
+					capacities[col] = Some(capacities[col].map_or(capacity, |current: f64| current.min(capacity)));
+				},
+				_ => return Err(not_a_network())
+			}
+		}
+
+		let rows: Vec<usize> = nodes.iter()
+			.map(|node| self.constraint_names.iter().position(|name| name == node).unwrap())
+			.collect();
+		let mut arcs = vec![];
+		for col in 0 .. num_structural {
+			let endpoint = |coefficient: f64| {
+				let matches: Vec<usize> = (0 .. rows.len()).filter(|&node| self.A[[rows[node], col]] == coefficient).collect();
+				if matches.len() == 1 { Some(matches[0]) } else { None }
+			};
+			let linked = rows.iter().filter(|&&row| self.A[[row, col]] != 0.).count();
+			match (endpoint(1.), endpoint(-1.)) {
+				(Some(from), Some(to)) if linked == 2 => arcs.push((from, to)),
+				_ => return Err(ExportError::InvalidArc { variable: self.vars[col].clone() })
+			}
+		}
+
+		let total_supply: f64 = rows.iter().map(|&row| self.b[row].max(0.)).sum();
+		let mut dimacs = String::new();
+		dimacs.push_str(&format!("p min {} {}\n", nodes.len(), arcs.len()));
+		for (node, &row) in rows.iter().enumerate() {
+			dimacs.push_str(&format!("c node {} {}\n", node + 1, nodes[node]));
+			if self.b[row] != 0. {
+				dimacs.push_str(&format!("n {} {}\n", node + 1, self.b[row]));
+			}
+		}
+		for (col, &(from, to)) in arcs.iter().enumerate() {
+			dimacs.push_str(&format!("c arc {} {}\n", col + 1, self.vars[col]));
+			dimacs.push_str(&format!("a {} {} 0 {} {}\n", from + 1, to + 1, capacities[col].unwrap_or(total_supply), c[col]));
+		}
+		Ok(dimacs)
+	}
+
+	/// Writes the Lp in the free MPS format read by `Parser::lp_from_mps`.
+	///
+	/// Constraints are written in their original form, as recovered by
+	/// `to_constraints`, with the objective as the `N` row `objective` (its
+	/// name isn't stored in the Lp). A ranged constraint is an `L` row
+	/// at its upper bound with its width in `RANGES`. Constraints on a
+	/// single variable are the variables' bounds in this model, so they go
+	/// to `BOUNDS` instead, as `LO`, `UP` or `FX` bounds: reading the model
+	/// back names them `<col>_lower`, `<col>_upper` and `<col>_fixed`. Integer
+	/// columns are written between `'INTORG'` and `'INTEND'` markers, binary
+	/// ones with a `BV` bound.
+	///
+	/// A name that is empty or contains whitespace gives an ExportError.
+	pub fn to_mps(&self) -> Result<String, ExportError> {
+		let num_structural = self.vars.len() - self.num_artificial_vars;
+		let constraints = self.to_constraints();
+		for name in self.vars[.. num_structural].iter().chain(constraints.iter().map(|constraint| &constraint.name)) {
+			if name.is_empty() || name.contains(char::is_whitespace) {
+				return Err(ExportError::InvalidName { name: name.clone() });
+			}
+		}
+
+		let rows: Vec<Constraint> = constraints.into_iter().filter(|constraint| !is_bound(constraint)).collect();
+		let (lower, upper) = self.variable_bounds();
+
+		let mut mps = String::from("NAME\n");
+		if self.optimization == Optimization::Max {
+			mps.push_str("OBJSENSE\n    MAX\n");
+		}
+		mps.push_str("ROWS\n N  objective\n");
+		for row in &rows {
+			let kind = match row.relation {
+				Relation::Equal => "E",
+				Relation::GreaterThanOrEqual => "G",
+				Relation::LessThanOrEqual | Relation::Range(_) => "L"
+			};
+			mps.push_str(&format!(" {}  {}\n", kind, row.name));
+		}
+
+		mps.push_str("COLUMNS\n");
+		let mut integer_block = false;
+		for col in 0 .. num_structural {
+			let integer = self.kinds[col] == VariableKind::Integer;
+			if integer != integer_block {
+				mps.push_str(&format!("    MARKER    'MARKER'    '{}'\n", if integer { "INTORG" } else { "INTEND" }));
+				integer_block = integer;
+			}
+			let var = &self.vars[col];
+			if self.c[col] != 0. {
+				mps.push_str(&format!("    {}    objective    {}\n", var, self.c[col]));
+			}
+			for row in &rows {
+				if let Some(term) = row.variables.iter().find(|term| term.name == *var) {
+					mps.push_str(&format!("    {}    {}    {}\n", var, row.name, term.coefficient));
+				}
+			}
+		}
+		if integer_block {
+			mps.push_str("    MARKER    'MARKER'    'INTEND'\n");
+		}
+
+		mps.push_str("RHS\n");
+		for row in rows.iter().filter(|row| row.constant != 0.) {
+			mps.push_str(&format!("    RHS    {}    {}\n", row.name, row.constant));
+		}
+
+		if rows.iter().any(|row| matches!(row.relation, Relation::Range(_))) {
+			mps.push_str("RANGES\n");
+			for row in &rows {
+				if let Relation::Range(range_lower) = row.relation {
+					mps.push_str(&format!("    RNG    {}    {}\n", row.name, row.constant - range_lower));
+				}
+			}
+		}
+
+		let mut bound_lines = String::new();
+		for col in 0 .. num_structural {
+			let var = &self.vars[col];
+			if self.kinds[col] == VariableKind::Binary {
+				bound_lines.push_str(&format!(" BV BND    {}\n", var));
+			}
+			// A BV bound already gives the upper bound of one
+			let default_upper = if self.kinds[col] == VariableKind::Binary { 1. } else { f64::INFINITY };
+			if lower[col] == upper[col] {
+				bound_lines.push_str(&format!(" FX BND    {}    {}\n", var, lower[col]));
+				continue;
+			}
+			if lower[col] > 0. {
+				bound_lines.push_str(&format!(" LO BND    {}    {}\n", var, lower[col]));
+			}
+			if upper[col] < default_upper {
+				bound_lines.push_str(&format!(" UP BND    {}    {}\n", var, upper[col]));
+			}
+		}
+		if !bound_lines.is_empty() {
+			mps.push_str("BOUNDS\n");
+			mps.push_str(&bound_lines);
+		}
+
+		mps.push_str("ENDATA\n");
+		Ok(mps)
+	}
+
+	// The lower and upper bound of each declared variable: zero below, one
+	// above for binary variables, tightened by every constraint on the
+	// variable alone
+	pub(crate) fn variable_bounds(&self) -> (Vec<f64>, Vec<f64>) {
+		let num_structural = self.vars.len() - self.num_artificial_vars;
+		let mut lower: Vec<f64> = vec![0.; num_structural];
+		let mut upper: Vec<f64> = self.kinds[.. num_structural].iter()
+			.map(|&kind| if kind == VariableKind::Binary { 1. } else { f64::INFINITY })
+			.collect();
+		for constraint in self.to_constraints().into_iter().filter(is_bound) {
+			let col = self.vars.iter().position(|var| *var == constraint.variables[0].name).unwrap();
+			let coefficient = constraint.variables[0].coefficient;
+			let upper_side = constraint.constant / coefficient;
+			let (low, high) = match constraint.relation {
+				Relation::LessThanOrEqual => (None, Some(upper_side)),
+				Relation::GreaterThanOrEqual => (Some(upper_side), None),
+				Relation::Equal => (Some(upper_side), Some(upper_side)),
+				Relation::Range(range_lower) => (Some(range_lower / coefficient), Some(upper_side))
+			};
+			let (low, high) = if coefficient < 0. { (high, low) } else { (low, high) };		// Dividing by a negative flips the relation
+			if let Some(low) = low {
+				lower[col] = lower[col].max(low);
+			}
+			if let Some(high) = high {
+				upper[col] = upper[col].min(high);
+			}
+		}
+
+		(lower, upper)
+	}
+
+	// Coefficients of a constraint from `to_constraints` over the declared
+	// variables, in column order
+	fn dense_coefficients(&self, constraint: &Constraint) -> Vec<f64> {
+		let mut coefficients = vec![0.; self.vars.len() - self.num_artificial_vars];
+		for var in &constraint.variables {
+			coefficients[self.vars.iter().position(|name| *name == var.name).unwrap()] = var.coefficient;
+		}
+		coefficients
+	}
+
+	// Writes `3 \mathit{a} - \mathit{b} + ...` over the nonzero coefficients
+	fn latex_expression(&self, coefficients: &[f64]) -> String {
+		let mut expression = String::new();
+		for (col, &coeff) in coefficients.iter().enumerate() {
+			if coeff == 0. {
+				continue;
+			}
+
+			if expression.is_empty() {
+				if coeff < 0. {
+					expression.push('-');
+				}
+			} else {
+				expression.push_str(if coeff < 0. { " - " } else { " + " });
+			}
+			if coeff.abs() != 1. {
+				expression.push_str(&format!("{} ", coeff.abs()));
+			}
+			expression.push_str(&latex_name(&self.vars[col]));
+		}
+
+		if expression.is_empty() {
+			expression.push('0');
+		}
+		expression
+	}
+
+	// -1 when the objective being optimized is the negation of the one
+	// solutions should report
+	pub(crate) fn reported_sign(&self) -> f64 {
+		if self.objective_negated { -1. } else { 1. }
+	}
+
+	/// Returns `(A, b, c)` describing the problem as `min c'x s.t. Ax = b, x >= 0`.
+	///
+	/// `A` and `b` are already in equality form and are returned unchanged.
+	/// The columns of `A` (and entries of `c`) are laid out as:
+	///
+	/// 1. the declared variables, in declaration order;
+	/// 2. one `slack_#` column per `<=` or ranged constraint, in constraint order;
+	/// 3. one `excess_#` column per `>=` constraint, in constraint order;
+	/// 4. one `range_#` column per ranged constraint, in constraint order.
+	///
+	/// The rows follow constraint order, with the bound row of each ranged
+	/// constraint appended after all other rows. For maximization problems
+	/// `c` is negated, so minimizing `c'x` maximizes the original objective.
+	pub fn to_equality_form(&self) -> (Matrix<f64>, Vec<f64>, Vec<f64>) {
+		let c = match self.optimization {
+			Optimization::Min => self.c.clone(),
+			Optimization::Max => self.c.iter().map(|coeff| -coeff).collect(),
+		};
+
+		(self.A.clone(), self.b.clone(), c)
+	}
+
+	/// Generates a random maximization problem that is feasible and bounded
+	/// by construction.
+	///
+	/// All constraints are `<=` with strictly positive coefficients, and their
+	/// right hand sides are derived from a random non-negative point, so that
+	/// point is always feasible. The same seed always yields the same problem.
+	///
+	/// # Examples
+	/// ```
+	/// use rulp::lp::Lp;
+	///
+	/// let lp = Lp::random(3, 2, 42);
+	/// assert_eq!(lp.b.len(), 2);
+	/// assert_eq!(lp.c.len(), 5);
+	/// assert_eq!(lp, Lp::random(3, 2, 42));
+	/// ```
+	pub fn random(num_vars: usize, num_constraints: usize, seed: u64) -> Lp {
+		let mut rng = XorShiftRng::new(seed);
+		let mut builder = Builder::new();
+
+		let names: Vec<String> = (0 .. num_vars).map(|i| format!("x_{}", i)).collect();
+		for name in &names {
+			builder.add_variable(Variable { name: name.clone(), coefficient: 0. });
+		}
+
+		let point: Vec<f64> = names.iter().map(|_| rng.next_f64(0., 10.)).collect();
+
+		for i in 0 .. num_constraints {
+			let mut variables = vec![];
+			let mut lhs = 0.;
+			for j in 0 .. num_vars {
+				let coefficient = rng.next_f64(1., 10.);
+				lhs += coefficient * point[j];
+				variables.push(Variable { name: names[j].clone(), coefficient });
+			}
+
+			builder.add_constraint(Constraint {
+				name: format!("c_{}", i),
+				variables,
+				constant: lhs + rng.next_f64(0., 5.),
+				relation: Relation::LessThanOrEqual
+			});
+		}
+
+		let objective = names.iter()
+			.map(|name| Variable { name: name.clone(), coefficient: rng.next_f64(1., 10.) })
+			.collect();
+
+		builder.add_objective(Objective {
+			name: "random".to_string(),
+			variables: objective,
+			maximize: true
+		});
+
+		builder.build_lp()
+	}
+}
+
+fn latex_name(name: &str) -> String {
+	format!("\\mathit{{{}}}", name.replace("_", "\\_"))
+}
+
+// The columns `(earlier, later)` a constraint `c*a - c*b == 0` forces to be
+// equal
+fn equated_columns(lp: &Lp, constraint: &Constraint) -> Option<(usize, usize)> {
+	if constraint.relation != Relation::Equal || constraint.constant != 0. || constraint.variables.len() != 2 {
+		return None;
+	}
+	let (a, b) = (&constraint.variables[0], &constraint.variables[1]);
+	if a.coefficient == 0. || a.coefficient != -b.coefficient {
+		return None;
+	}
+	let first = lp.vars.iter().position(|var| *var == a.name).unwrap();
+	let second = lp.vars.iter().position(|var| *var == b.name).unwrap();
+	Some((first.min(second), first.max(second)))
+}
+
+// The root of a column's class in `Lp::presolve`, pointing the columns on
+// the way straight at it
+fn find_root(parent: &mut [usize], col: usize) -> usize {
+	let mut root = col;
+	while parent[root] != root {
+		root = parent[root];
+	}
+	let mut col = col;
+	while parent[col] != root {
+		let next = parent[col];
+		parent[col] = root;
+		col = next;
+	}
+	root
+}
+
+// Binary is stricter than integer, which is stricter than continuous
+fn stricter_kind(a: VariableKind, b: VariableKind) -> VariableKind {
+	match (a, b) {
+		(VariableKind::Binary, _) | (_, VariableKind::Binary) => VariableKind::Binary,
+		(VariableKind::Integer, _) | (_, VariableKind::Integer) => VariableKind::Integer,
+		_ => VariableKind::Continuous
+	}
+}
+
+// Whether a constraint is on a single variable, which makes it one of the
+// variable's bounds
+fn is_bound(constraint: &Constraint) -> bool {
+	constraint.variables.len() == 1 && constraint.variables[0].coefficient != 0.
+}
+
+// Coefficients of a constraint keyed by variable name
+fn terms_by_name(constraint: &Constraint) -> HashMap<&str, f64> {
+	constraint.variables.iter().map(|var| (var.name.as_str(), var.coefficient)).collect()
+}
+
+// Writes a constraint the way it would be written in the LP syntax, like
+// `a - 2*b <= 5`
+fn constraint_text(constraint: &Constraint) -> String {
+	let mut expression = String::new();
+	for (i, var) in constraint.variables.iter().enumerate() {
+		let coefficient = if i == 0 {
+			var.coefficient
+		} else {
+			expression.push_str(if var.coefficient < 0. { " - " } else { " + " });
+			var.coefficient.abs()
+		};
+		if coefficient == 1. {
+			expression.push_str(&var.name);
+		} else if coefficient == -1. {
+			expression.push_str(&format!("-{}", var.name));
+		} else {
+			expression.push_str(&format!("{}*{}", coefficient, var.name));
+		}
+	}
+	if expression.is_empty() {
+		expression.push('0');
+	}
+
+	match constraint.relation {
+		Relation::Equal => format!("{} == {}", expression, constraint.constant),
+		Relation::LessThanOrEqual => format!("{} <= {}", expression, constraint.constant),
+		Relation::GreaterThanOrEqual => format!("{} >= {}", expression, constraint.constant),
+		Relation::Range(lower) => format!("{} <= {} <= {}", lower, expression, constraint.constant)
+	}
+}
+
+// Solves the rows and columns of `A` picked by `rows` and `cols` for
+// `rhs` by Gaussian elimination. Returns None unless the columns are
+// linearly independent, so the solution is unique, and it satisfies every
+// picked row within `tolerance`
+fn solve_subsystem(A: &Matrix<f64>, rows: &[usize], cols: &[usize], rhs: &[f64], tolerance: f64) -> Option<Vec<f64>> {
+	let n = cols.len();
+	let mut system: Vec<Vec<f64>> = rows.iter().zip(rhs.iter())
+		.map(|(&row, &value)| cols.iter().map(|&col| A[[row, col]]).chain(Some(value)).collect())
+		.collect();
+
+	for pivot in 0 .. n {
+		let best = (pivot .. system.len())
+			.max_by(|&i, &j| system[i][pivot].abs().partial_cmp(&system[j][pivot].abs()).unwrap());
+		match best {
+			Some(best) if system[best][pivot].abs() > tolerance => system.swap(pivot, best),
+			_ => return None
+		}
+
+		let (above, below) = system.split_at_mut(pivot + 1);
+		let pivot_row = &above[pivot];
+		for row in below {
+			let factor = row[pivot] / pivot_row[pivot];
+			for (entry, &value) in row[pivot ..].iter_mut().zip(pivot_row[pivot ..].iter()) {
+				*entry -= factor * value;
+			}
+		}
+	}
+
+	let mut solution = vec![0.; n];
+	for pivot in (0 .. n).rev() {
+		let known: f64 = (pivot + 1 .. n).map(|j| system[pivot][j] * solution[j]).sum();
+		solution[pivot] = (system[pivot][n] - known) / system[pivot][pivot];
+	}
+
+	let consistent = rows.iter().zip(rhs.iter()).all(|(&row, &value)| {
+		let activity: f64 = cols.iter().zip(solution.iter()).map(|(&col, &x)| A[[row, col]] * x).sum();
+		(activity - value).abs() <= tolerance * (1. + value.abs())
+	});
+	if consistent { Some(solution) } else { None }
+}
+
 impl fmt::Display for Lp {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
     	unsafe {
@@ -26,12 +1898,12 @@ impl fmt::Display for Lp {
     			write!(f, "{:>5.1}  ", self.c[c]).unwrap();
     		}
 
-    		writeln!(f, "").unwrap();
+    		writeln!(f).unwrap();
 
     		for _ in 0 .. self.A.cols() {
     			write!(f, " - - - ").unwrap();
     		}
-    		writeln!(f, "").unwrap();
+    		writeln!(f).unwrap();
     		
 
 			for r in 0 .. self.A.rows() {
@@ -46,8 +46,8 @@ fn main() {
 	let solver = SimplexSolver::new(lp);
 	let solution = solver.solve();
 
-	let mut output_file = File::create(&output_path).unwrap();
-	output_file.write(format!("{:}", &solution).as_bytes()).expect("Failed to write to destination file");
+	let mut output_file = File::create(output_path).unwrap();
+	output_file.write_all(format!("{:}", &solution).as_bytes()).expect("Failed to write to destination file");
 
 	if matches.is_present("display") {
 		println!("{:}", &solution);
@@ -0,0 +1,344 @@
+use super::*;
+use super::impl_parser::build_components;
+use builder::{Builder, Relation};
+
+impl Parser {
+	/// Builds an Lp from a model in the (free) MPS format, with fields
+	/// separated by whitespace and `*` starting a comment line.
+	///
+	/// The `ROWS`, `COLUMNS`, `RHS`, `RANGES` and `BOUNDS` sections are
+	/// read, along with an `OBJSENSE` of `MAX` or `MIN` (the default). The
+	/// first `N` row is the objective. Columns between `'INTORG'` and
+	/// `'INTEND'` markers are integer, as are those with an `LI` or `UI`
+	/// bound, and those with a `BV` bound binary.
+	///
+	/// Since every variable is non-negative, bounds become constraints on a
+	/// single variable: `<col>_lower`, `<col>_upper`, or `<col>_fixed` for
+	/// an `FX` bound. A column that may go negative (`FR`, `MI` or a
+	/// negative lower bound) is split into `<col>_pos - <col>_neg`, both
+	/// non-negative.
+	///
+	/// ```
+	/// use rulp::parser::Parser;
+	///
+	/// let lp = Parser::lp_from_mps("
+	/// NAME          bakery
+	/// OBJSENSE
+	///     MAX
+	/// ROWS
+	///  N  profits
+	///  L  flour
+	/// COLUMNS
+	///     bagels    profits   3          flour     12
+	///     doughnuts profits   1.25       flour     6.5
+	/// RHS
+	///     RHS       flour     400
+	/// ENDATA
+	/// ").unwrap();
+	/// assert_eq!(lp.b, vec![400.]);
+	/// ```
+	///
+	/// A malformed line, an unknown section, row or column, or a model the
+	/// Builder rejects gives a ParseError with index 0.
+	pub fn lp_from_mps(text: &str) -> Result<Lp, ParseError> {
+		components_from_mps(text)
+			.and_then(|components| build_components(components, &mut Builder::new()))
+			.map_err(|message| ParseError { index: 0, message })
+	}
+}
+
+// The bounds and kind given to a column, non-negative and continuous
+// unless the BOUNDS section says otherwise
+struct MpsColumn {
+	name: String,
+	kind: VariableKind,
+	lower: f64,
+	upper: f64
+}
+
+fn components_from_mps(text: &str) -> Result<Components, String> {
+	let mut section = "";
+	let mut maximize = false;
+	let mut objective: Option<Objective> = None;
+	let mut rows: Vec<(Constraint, char)> = vec![];
+	let mut other_objectives: Vec<String> = vec![];
+	let mut columns: Vec<MpsColumn> = vec![];
+	let mut integer_block = false;
+
+	for line in text.lines() {
+		if line.trim().is_empty() || line.starts_with('*') {
+			continue;
+		}
+		let fields: Vec<&str> = line.split_whitespace().collect();
+		if !line.starts_with(char::is_whitespace) {
+			section = fields[0];
+			match (section, fields.get(1)) {
+				("OBJSENSE", Some(sense)) => maximize = read_sense(sense)?,
+				("NAME", _) | ("OBJSENSE", None) | ("ROWS", _) | ("COLUMNS", _) | ("RHS", _) | ("RANGES", _) | ("BOUNDS", _) => {},
+				("ENDATA", _) => break,
+				_ => return Err(format!("Unknown MPS section \"{}\"", section))
+			}
+			continue;
+		}
+
+		match section {
+			"OBJSENSE" => maximize = read_sense(fields[0])?,
+			"ROWS" => {
+				if fields.len() != 2 {
+					return Err(format!("Invalid MPS row \"{}\"", line.trim()));
+				}
+				let (kind, name) = (fields[0], fields[1].to_string());
+				let relation = match kind {
+					// Only the first N row is the objective, the others are left out
+					"N" if objective.is_none() => {
+						objective = Some(Objective { name, variables: vec![], maximize: false });
+						continue;
+					},
+					"N" => {
+						other_objectives.push(name);
+						continue;
+					},
+					"L" => Relation::LessThanOrEqual,
+					"G" => Relation::GreaterThanOrEqual,
+					"E" => Relation::Equal,
+					_ => return Err(format!("Unknown MPS row type \"{}\"", kind))
+				};
+				rows.push((Constraint { name, variables: vec![], constant: 0., relation }, kind.chars().next().unwrap()));
+			},
+			"COLUMNS" => {
+				if fields.len() == 3 && fields[1] == "'MARKER'" {
+					integer_block = match fields[2] {
+						"'INTORG'" => true,
+						"'INTEND'" => false,
+						marker => return Err(format!("Unknown MPS marker {}", marker))
+					};
+					continue;
+				}
+				let name = fields[0];
+				if !columns.iter().any(|column| column.name == name) {
+					columns.push(MpsColumn {
+						name: name.to_string(),
+						kind: if integer_block { VariableKind::Integer } else { VariableKind::Continuous },
+						lower: 0.,
+						upper: f64::INFINITY
+					});
+				}
+				for (row, value) in pairs(&fields[1 ..], line)? {
+					let term = Variable { name: name.to_string(), coefficient: value };
+					match objective {
+						Some(ref mut objective) if objective.name == row => objective.variables.push(term),
+						_ if other_objectives.iter().any(|name| name == row) => {},
+						_ => find_row(&mut rows, row)?.0.variables.push(term)
+					}
+				}
+			},
+			"RHS" => {
+				for (row, value) in pairs(&fields[1 ..], line)? {
+					if objective.as_ref().is_some_and(|objective| objective.name == row) || other_objectives.iter().any(|name| name == row) {
+						return Err(format!("Constant {} of objective \"{}\" isn't supported", value, row));
+					}
+					find_row(&mut rows, row)?.0.constant = value;
+				}
+			},
+			"RANGES" => {
+				for (row, value) in pairs(&fields[1 ..], line)? {
+					let &mut (ref mut constraint, kind) = find_row(&mut rows, row)?;
+					constraint.relation = Relation::Range(match kind {
+						'G' => constraint.constant,
+						'E' if value > 0. => constraint.constant,
+						_ => constraint.constant - value.abs()
+					});
+					if kind == 'G' || (kind == 'E' && value > 0.) {
+						constraint.constant += value.abs();
+					}
+				}
+			},
+			"BOUNDS" => {
+				if fields.len() < 3 {
+					return Err(format!("Invalid MPS bound \"{}\"", line.trim()));
+				}
+				let column = match columns.iter_mut().find(|column| column.name == fields[2]) {
+					Some(column) => column,
+					None => return Err(format!("Bound on unknown column \"{}\"", fields[2]))
+				};
+				let value = match fields.get(3) {
+					Some(value) => Some(value.parse::<f64>().map_err(|_| format!("Invalid MPS number \"{}\"", value))?),
+					None => None
+				};
+				let needs_value = || value.ok_or_else(|| format!("Missing the value of the bound \"{}\"", line.trim()));
+				match fields[0] {
+					"UP" => column.upper = needs_value()?,
+					"LO" => column.lower = needs_value()?,
+					"FX" => {
+						column.lower = needs_value()?;
+						column.upper = column.lower;
+					},
+					"FR" => {
+						column.lower = -f64::INFINITY;
+						column.upper = f64::INFINITY;
+					},
+					"MI" => column.lower = -f64::INFINITY,
+					"PL" => column.upper = f64::INFINITY,
+					"BV" => column.kind = VariableKind::Binary,
+					"LI" => {
+						column.kind = VariableKind::Integer;
+						column.lower = needs_value()?;
+					},
+					"UI" => {
+						column.kind = VariableKind::Integer;
+						column.upper = needs_value()?;
+					},
+					kind => return Err(format!("Unknown MPS bound type \"{}\"", kind))
+				}
+			},
+			_ => return Err(format!("MPS line \"{}\" outside of a section", line.trim()))
+		}
+	}
+
+	let mut objective = objective.ok_or("Missing the objective, an N row")?;
+	objective.maximize = maximize;
+	let mut constraints: Vec<Constraint> = rows.into_iter().map(|(constraint, _)| constraint).collect();
+	let mut variables = vec![];
+	let mut kinds = vec![];
+
+	for column in &columns {
+		// A column that may go negative is the difference of two that can't
+		let terms = if column.lower < 0. {
+			let (pos, neg) = (format!("{}_pos", column.name), format!("{}_neg", column.name));
+			for terms in constraints.iter_mut().map(|constraint| &mut constraint.variables).chain(Some(&mut objective.variables)) {
+				split_column(terms, &column.name, &pos, &neg);
+			}
+			vec![pos, neg]
+		} else {
+			vec![column.name.clone()]
+		};
+		for name in &terms {
+			variables.push(Variable { name: name.clone(), coefficient: 0. });
+			if column.kind != VariableKind::Continuous {
+				kinds.push((name.clone(), column.kind));
+			}
+		}
+
+		let expression = || {
+			let mut expression = vec![Variable { name: terms[0].clone(), coefficient: 1. }];
+			if terms.len() == 2 {
+				expression.push(Variable { name: terms[1].clone(), coefficient: -1. });
+			}
+			expression
+		};
+		let bound = |suffix: &str, constant: f64, relation: Relation| Constraint {
+			name: format!("{}_{}", column.name, suffix),
+			variables: expression(),
+			constant,
+			relation
+		};
+		if column.lower == column.upper {
+			constraints.push(bound("fixed", column.lower, Relation::Equal));
+			continue;
+		}
+		if column.lower != 0. && column.lower.is_finite() {
+			constraints.push(bound("lower", column.lower, Relation::GreaterThanOrEqual));
+		}
+		if column.upper.is_finite() {
+			constraints.push(bound("upper", column.upper, Relation::LessThanOrEqual));
+		}
+	}
+
+	Ok(Components {
+		variables,
+		kinds,
+		constraints,
+		objective
+	})
+}
+
+fn read_sense(sense: &str) -> Result<bool, String> {
+	match sense {
+		"MAX" | "MAXIMIZE" => Ok(true),
+		"MIN" | "MINIMIZE" => Ok(false),
+		_ => Err(format!("Unknown MPS objective sense \"{}\"", sense))
+	}
+}
+
+// The `row value` pairs after the first field of a COLUMNS, RHS or RANGES line
+fn pairs<'a>(fields: &[&'a str], line: &str) -> Result<Vec<(&'a str, f64)>, String> {
+	if fields.is_empty() || !fields.len().is_multiple_of(2) {
+		return Err(format!("Invalid MPS line \"{}\"", line.trim()));
+	}
+	fields.chunks(2)
+		.map(|pair| match pair[1].parse::<f64>() {
+			Ok(value) => Ok((pair[0], value)),
+			Err(_) => Err(format!("Invalid MPS number \"{}\"", pair[1]))
+		})
+		.collect()
+}
+
+fn find_row<'a>(rows: &'a mut [(Constraint, char)], name: &str) -> Result<&'a mut (Constraint, char), String> {
+	rows.iter_mut().find(|&&mut (ref constraint, _)| constraint.name == name).ok_or_else(|| format!("Unknown MPS row \"{}\"", name))
+}
+
+// Replaces the terms on `name` by the same terms on `pos` and their
+// negation on `neg`
+fn split_column(terms: &mut Vec<Variable>, name: &str, pos: &str, neg: &str) {
+	let mut split = vec![];
+	for var in terms.drain(..) {
+		if var.name == name {
+			split.push(Variable { name: pos.to_string(), coefficient: var.coefficient });
+			split.push(Variable { name: neg.to_string(), coefficient: -var.coefficient });
+		} else {
+			split.push(var);
+		}
+	}
+	*terms = split;
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn mps_ranges_test() {
+		let components = components_from_mps("
+NAME ranges
+ROWS
+ N obj
+ L less
+ G greater
+ E up
+ E down
+COLUMNS
+    x obj 1 less 1
+    x greater 1 up 1
+    x down 1
+RHS
+    RHS less 10 greater 2
+    RHS up 4 down 4
+RANGES
+    RNG less 3 greater 3
+    RNG up 3 down -3
+ENDATA
+").unwrap();
+
+		let ranges: Vec<(f64, Relation)> = components.constraints.iter()
+			.map(|constraint| (constraint.constant, constraint.relation.clone()))
+			.collect();
+		assert_eq!(ranges, vec![
+			(10., Relation::Range(7.)),
+			(5., Relation::Range(2.)),
+			(7., Relation::Range(4.)),
+			(4., Relation::Range(1.))
+		]);
+	}
+
+	#[test]
+	fn mps_error_test() {
+		let err = Parser::lp_from_mps("ROWS\n N obj\nCOLUMNS\n    x obj 1 nowhere 2\n").unwrap_err();
+		assert_eq!(err.message, "Unknown MPS row \"nowhere\"");
+
+		let err = Parser::lp_from_mps("ROWS\n N obj\nCOLUMNS\n    x obj 1\nBOUNDS\n UP BND y 4\n").unwrap_err();
+		assert_eq!(err.message, "Bound on unknown column \"y\"");
+
+		let err = Parser::lp_from_mps("ROWS\n N obj\nCOLUMNS\n    x obj one\n").unwrap_err();
+		assert_eq!(err.message, "Invalid MPS number \"one\"");
+	}
+}
@@ -0,0 +1,159 @@
+use super::*;
+use super::impl_parser::build_components;
+use builder::Relation;
+use utils::read_file_contents;
+
+// Name of the objective of every network model
+const OBJECTIVE_NAME: &str = "total_cost";
+
+impl ParserBase for NetworkParser {
+	/// Constructor for Components struct.
+	///
+	/// Each arc becomes a variable of its name with a `<arc>_cap`
+	/// constraint if it has a capacity, each node a `<node>_balance`
+	/// constraint (flow out minus flow in equals its supply), in order of
+	/// first appearance, and the objective `total_cost` minimizes the
+	/// cost of the flow.
+	fn parse_components_from_text(text: &str) -> Components {
+		NetworkParser::new().parse_components(text)
+	}
+
+	fn parse_components_from_file(file: &mut File) -> Components {
+		Self::parse_components_from_text(&read_file_contents(file))
+	}
+
+	/// Constructor for Lp struct.
+	///
+	/// # Examples
+	/// ```
+	/// use rulp::builder::{Builder, BuilderBase};
+	/// use rulp::parser::{NetworkParser, ParserBase};
+	/// use rulp::solver::{SimplexSolver, SolverBase};
+	///
+	/// let lp = NetworkParser::lp_from_text("
+	/// 	node plant supply 4;
+	/// 	node store demand 4;
+	/// 	arc road from plant to store cap 3 cost 1;
+	/// 	arc rail from plant to store cost 5;
+	/// ", Builder::new());
+	///
+	/// let solution = SimplexSolver::new(lp).solve();
+	/// assert!((solution.objective.unwrap() - 8.).abs() < 1e-9);
+	/// ```
+	fn lp_from_text<B: BuilderBase>(text: &str, mut builder: B) -> Lp {
+		build_components(Self::parse_components_from_text(text), &mut builder).unwrap_or_else(|message| panic!("{}", message))
+	}
+
+	fn lp_from_file<B: BuilderBase>(file: &mut File, builder: B) -> Lp {
+		Self::lp_from_text(&read_file_contents(file), builder)
+	}
+}
+
+// An arc as declared: name, endpoints, capacity and cost
+struct NetworkArc {
+	name: String,
+	from: String,
+	to: String,
+	capacity: Option<f64>,
+	cost: f64
+}
+
+impl Default for NetworkParser {
+	fn default() -> Self {
+		NetworkParser::new()
+	}
+}
+
+impl NetworkParser {
+	pub fn new() -> Self {
+		NetworkParser {
+			arc_regex: Regex::new(r"^arc\s+(?P<name>\w+)\s+from\s+(?P<from>\w+)\s+to\s+(?P<to>\w+)(?:\s+cap\s+(?P<cap>\d+\.?\d*))?(?:\s+cost\s+(?P<cost>-?\d+\.?\d*))?$").unwrap(),
+			node_regex: Regex::new(r"^node\s+(?P<name>\w+)\s+(?P<kind>supply|demand)\s+(?P<amount>-?\d+\.?\d*)$").unwrap(),
+		}
+	}
+
+	/// Expands the network shorthand into Components, as described for
+	/// `parse_components_from_text`. Panics on a statement that is neither
+	/// an arc, a node nor a comment, or on an arc or node given twice.
+	pub fn parse_components(&self, text: &str) -> Components {
+		let mut arcs: Vec<NetworkArc> = vec![];
+		let mut supplies: Vec<(String, f64)> = vec![];
+		let mut nodes: Vec<String> = vec![];
+
+		for statement in text.split(';').map(|statement| statement.trim()).filter(|statement| !statement.is_empty()) {
+			if statement.starts_with('#') {
+				continue;
+			}
+
+			if let Some(caps) = self.arc_regex.captures(statement) {
+				let arc = NetworkArc {
+					name: caps["name"].to_string(),
+					from: caps["from"].to_string(),
+					to: caps["to"].to_string(),
+					capacity: caps.name("cap").map(|cap| cap.as_str().parse::<f64>().unwrap()),
+					cost: caps.name("cost").map_or(0., |cost| cost.as_str().parse::<f64>().unwrap())
+				};
+				if arcs.iter().any(|known| known.name == arc.name) {
+					panic!("Arc \"{}\" given twice", arc.name);
+				}
+				add_node(&mut nodes, &arc.from);
+				add_node(&mut nodes, &arc.to);
+				arcs.push(arc);
+			} else if let Some(caps) = self.node_regex.captures(statement) {
+				let amount = caps["amount"].parse::<f64>().unwrap();
+				let supply = if &caps["kind"] == "supply" { amount } else { -amount };
+				if supplies.iter().any(|(node, _)| *node == caps["name"]) {
+					panic!("Node \"{}\" given twice", &caps["name"]);
+				}
+				add_node(&mut nodes, &caps["name"]);
+				supplies.push((caps["name"].to_string(), supply));
+			} else {
+				panic!("Invalid network statement \"{}\"", statement);
+			}
+		}
+
+		let mut constraints = vec![];
+		for arc in arcs.iter().filter(|arc| arc.capacity.is_some()) {
+			constraints.push(Constraint {
+				name: format!("{}_cap", arc.name),
+				variables: vec![Variable { name: arc.name.clone(), coefficient: 1. }],
+				constant: arc.capacity.unwrap(),
+				relation: Relation::LessThanOrEqual
+			});
+		}
+		for node in &nodes {
+			let mut variables = vec![];
+			for arc in &arcs {
+				if arc.from == *node {
+					variables.push(Variable { name: arc.name.clone(), coefficient: 1. });
+				}
+				if arc.to == *node {
+					variables.push(Variable { name: arc.name.clone(), coefficient: -1. });
+				}
+			}
+			constraints.push(Constraint {
+				name: format!("{}_balance", node),
+				variables,
+				constant: supplies.iter().find(|&(name, _)| name == node).map_or(0., |&(_, supply)| supply),
+				relation: Relation::Equal
+			});
+		}
+
+		Components {
+			variables: arcs.iter().map(|arc| Variable { name: arc.name.clone(), coefficient: 0. }).collect(),
+			kinds: vec![],
+			constraints,
+			objective: Objective {
+				name: OBJECTIVE_NAME.to_string(),
+				variables: arcs.iter().map(|arc| Variable { name: arc.name.clone(), coefficient: arc.cost }).collect(),
+				maximize: false
+			}
+		}
+	}
+}
+
+fn add_node(nodes: &mut Vec<String>, node: &str) {
+	if !nodes.iter().any(|known| known == node) {
+		nodes.push(node.to_string());
+	}
+}
@@ -1,20 +1,41 @@
 use super::*;
-use builder::Relation;
+use builder::{unique_name, Builder, Relation};
 use utils::read_file_contents;
+use std::collections::HashMap;
+
+// Name given to objectives declared without one
+pub(super) const DEFAULT_OBJECTIVE_NAME: &str = "objective";
 
 #[derive(Debug, PartialEq)]
 enum LineType {
 	Variable,
 	Constraint,
 	Objective,
+	Definition,
 	Comment
 }
 
+/// A piece of a constraint's body, as split by `tokenize_constraint`
+#[derive(Debug, PartialEq)]
+enum ConstraintToken<'a> {
+	/// The trimmed text between relations: the terms or a constant side
+	Expression(&'a str),
+	Relation(Relation)
+}
+
 #[derive(Debug, PartialEq)]
 enum Component {
-	Variable(Variable),
+	Variable(Variable, VariableKind),
+	VariableRange(Vec<Variable>, VariableKind),
 	Constraint(Constraint),
+	/// `expr1 <= expr2 <= expr3` with variables outside of the middle,
+	/// read as one constraint per relation
+	ConstraintChain(Vec<Constraint>),
 	Objective(Objective),
+	/// `objective maximize: [...]`, resolved once all variables are declared
+	VectorObjective(bool, Vec<f64>),
+	/// `name: expr`, the expression of an objective declared as `maximize name`
+	Definition(String, Vec<Variable>),
 	Comment
 }
 
@@ -25,7 +46,7 @@ impl ParserBase for Parser {
 	/// Takes a string input to be parsed.
 	fn parse_components_from_text(text: &str) -> Components {
 		let p = Parser::new();
-		p.get_components(text)
+		p.parse_components(text)
 	}
 
 	/// Constructor for Components struct.
@@ -81,20 +102,8 @@ impl ParserBase for Parser {
 	/// 	assert_eq!(lp.optimization, Optimization::Max);
 	/// # }
 	/// ```
-	fn lp_from_text<B: BuilderBase>(text: &str, mut builder: B) -> Lp {
-		let components = Self::parse_components_from_text(text);
-
-		for v in components.variables {
-			builder.add_variable(v);
-		}
-
-		for c in components.constraints {
-			builder.add_constraint(c);
-		}
-
-		builder.add_objective(components.objective);
-
-		builder.build_lp()
+	fn lp_from_text<B: BuilderBase>(text: &str, builder: B) -> Lp {
+		Parser::new().parse_lp(text, builder)
 	}
 
 	/// Constructor for Lp struct.
@@ -107,126 +116,953 @@ impl ParserBase for Parser {
 
 impl Parser {
 	fn new() -> Self {
+		Self::with_options(ParserOptions::default())
+	}
+
+	/// Constructor for Parser struct with opt-in syntax extensions.
+	///
+	/// # Examples
+	/// ```
+	/// use rulp::parser::{Parser, ParserOptions};
+	///
+	/// let parser = Parser::with_options(ParserOptions {
+	/// 	coefficient_after_variable: true,
+	/// 	..ParserOptions::default()
+	/// });
+	/// let components = parser.parse_components("var a; maximize obj: a*3.;");
+	/// assert_eq!(components.objective.variables[0].coefficient, 3.);
+	/// ```
+	pub fn with_options(options: ParserOptions) -> Self {
 		Parser {
-			variable_declaration_regex: Regex::new(r"var\s+(?P<name>\w+)\s*").unwrap(),
-			variable_regex: Regex::new(r"((?:\s*(?P<sign>-)?\s*)(?P<coeff>\d+\.?\d*)\s*\*\s*)?(?P<name>\w+)").unwrap(),
-			objective_regex: Regex::new(r"(?P<type>minimize|maximize)\s+(?P<name>\w+)\s*:\s*(?P<equation>[^;]*)").unwrap(),
-			equation_component_regex: Regex::new(r"^(?P<vars>[\w\s\*\.\+-]*)\s*((?P<type>==|<=|>=)\s*(?P<constant>\d+\.?\d*)\s*)?$").unwrap(),
-			constraint_regex: Regex::new(r"subject to (?P<name>\w*):\s*(?P<terms>[^=><]+?)\s*(?P<type>==|<=|>=)\s*?(?P<constant>\d+\.?\d*)\s*?").unwrap()
+			options,
+			variable_declaration_regex: Regex::new(r"var\s+(?P<name>\w+(?:\[\d+\])?)(?:\s+(?:cost|value)\s+(?P<cost>-?\s*\d+\.?\d*))?\s*").unwrap(),
+			variable_range_regex: Regex::new(r"var\s+(?P<name>\w+)\[(?P<start>\d+)\s*\.\.\s*(?P<end>\d+)\]").unwrap(),
+			variable_kind_regex: Regex::new(r"\s(?P<kind>integer|binary)$").unwrap(),
+			parameter_regex: Regex::new(r"^(?:param|const)\s+(?P<name>\w+)\s*=\s*(?P<value>[^=]+)$").unwrap(),
+			expression_token_regex: Regex::new(r"\s*(\d+\.?\d*|[-+*/])\s*").unwrap(),
+			group_regex: Regex::new(r"(?:(?P<sign>-)\s*)?(?:(?P<coeff>\d+\.?\d*)\s*\*\s*)?\((?P<group>[^()]*)\)").unwrap(),
+			constant_product_regex: Regex::new(r"(?P<prefix>^|[^\w.\]])(?P<left>\d+\.?\d*)\s*(?P<op>[*/])\s*(?P<right>-?\d+\.?\d*)").unwrap(),
+			parameter_reference_regex: Regex::new(r"[A-Za-z_]\w*").unwrap(),
+			expression_name_regex: Regex::new(r"\d+\.?\d*|[A-Za-z_]\w*(?:\[\d+\])?").unwrap(),
+			coefficient_vector_regex: Regex::new(r"^coeffs\s+(?P<name>\w+)\s*=\s*\[(?P<values>[^\]]*)\]$").unwrap(),
+			dot_product_regex: Regex::new(r"(?P<name>\w+)\s*\.\s*\[(?P<vars>(?:[^\[\]]|\[\d+\])*)\]").unwrap(),
+			positional_reference_regex: Regex::new(r"\$(?P<index>\d+)").unwrap(),
+			constant_term_regex: Regex::new(r"^(?P<sign>-)?\s*(?P<value>\d+\.?\d*)$").unwrap(),
+			interval_constraint_regex: Regex::new(r#"^(?P<terms>[^=><]+?)\s+in\s*\[\s*(?P<lower>\d+\.?\d*)\s*,\s*(?P<upper>\d+\.?\d*)\s*\]\s*$"#).unwrap(),
+			si_suffix_regex: Regex::new(r"(?P<prefix>^|[^\w.\[])(?P<number>\d+\.?\d*)(?P<suffix>[kMG])\b").unwrap(),
+			digit_separator_regex: Regex::new(r"(?P<prefix>^|[^\w.\[])(?P<number>\d[\d_]*(?:\.[\d_]*)?)").unwrap(),
+			vector_objective_regex: Regex::new(r"^objective\s+(?P<type>minimi[sz]e|maximi[sz]e)\s*:\s*\[(?P<coeffs>[^\]]*)\]$").unwrap(),
+			variable_regex: Regex::new(r"((?:\s*(?P<sign>-)?\s*)(?P<coeff>\d+\.?\d*)\s*\*\s*)?(?P<name>\w+(?:\[\d+\])?)").unwrap(),
+			definition_regex: Regex::new(r"(?s)^(?P<name>\w+)\s*:\s*(?P<expr>.*)$").unwrap(),
+			objective_regex: Regex::new(r#"(?P<type>minimi[sz]e|maximi[sz]e)(?:\s+(?:(?P<name>\w+|"[\w-]+")\s*:\s*)?(?P<equation>[^;]*))?"#).unwrap(),
+			constraint_header_regex: Regex::new(r#"(?s)subject\s+to\s+(?P<name>\w*|"[\w-]+")\s*:\s*(?P<body>.*)$"#).unwrap(),
+			constraint_block_regex: Regex::new(r"subject\s+to\s*\{(?P<body>[^}]*)\}\s*;?").unwrap(),
+			constraint_keyword_regex: Regex::new(r"^subject\s+to\b").unwrap(),
+			reversed_variable_regex: Regex::new(r"^(?P<name>\w+(?:\[\d+\])?)\s*\*\s*(?P<sign>-)?\s*(?P<coeff>\d+\.?\d*)$").unwrap()
 		}
 	}
 
-	fn get_components(&self, text: &str) -> Components {
-		let components: Vec<Component> = text
-			.split(';')
-			.map(|line| line.trim())
-			.filter(|line| line.len() > 0)
-			.map(|line| self.component_from_line(line))
-			.filter(|component| *component != Component::Comment)
+	/// Parses several problems kept in one text, separated by
+	/// `PROBLEM_SEPARATOR` (`%%%`), each built with a default Builder.
+	///
+	/// Every problem is parsed on its own, so one with invalid syntax
+	/// yields an error without affecting the others. Blank segments, e.g.
+	/// after a trailing separator, are skipped.
+	///
+	/// # Examples
+	/// ```
+	/// use rulp::parser::Parser;
+	///
+	/// let lps = Parser::lp_list_from_text("
+	/// 	var a; maximize obj: a; subject to cap: a <= 1;
+	/// 	%%%
+	/// 	var b; minimize obj: b;
+	/// ");
+	/// assert_eq!(lps.len(), 2);
+	/// assert!(lps.iter().all(|lp| lp.is_ok()));
+	/// ```
+	pub fn lp_list_from_text(text: &str) -> Vec<Result<Lp, ParseError>> {
+		let parser = Parser::new();
+		text.split(PROBLEM_SEPARATOR)
+			.filter(|segment| !segment.trim().is_empty())
+			.enumerate()
+			.map(|(index, segment)| {
+				parser.try_parse_lp(segment, Builder::new())
+					.map_err(|err| ParseError { index, message: err.message })
+			})
+			.collect()
+	}
+
+	/// Constructor for Components struct using this parser's options.
+	///
+	/// Panics on invalid syntax, see `try_parse_components`.
+	pub fn parse_components(&self, text: &str) -> Components {
+		self.try_parse_components(text).unwrap_or_else(|err| panic!("{}", err.message))
+	}
+
+	/// Like `parse_components`, but gives an error instead of panicking on
+	/// invalid syntax.
+	///
+	/// # Examples
+	/// ```
+	/// use rulp::parser::Parser;
+	///
+	/// let parser = Parser::with_options(Default::default());
+	/// let err = parser.try_parse_components("var a; maximize obj: a; subject to c1: a = 1;").unwrap_err();
+	/// assert!(err.message.contains("unknown relation"));
+	/// ```
+	pub fn try_parse_components(&self, text: &str) -> Result<Components, ParseError> {
+		self.get_components(text).map_err(|message| ParseError { index: 0, message })
+	}
+
+	/// Constructor for Lp struct using this parser's options.
+	///
+	/// Takes a string input to be parsed and a Builder struct. Panics on
+	/// invalid syntax or when the builder rejects the problem, see
+	/// `try_parse_lp`.
+	pub fn parse_lp<B: BuilderBase>(&self, text: &str, builder: B) -> Lp {
+		self.try_parse_lp(text, builder).unwrap_or_else(|err| panic!("{}", err.message))
+	}
+
+	/// Like `parse_lp`, but gives an error instead of panicking, using the
+	/// builder's `try_` methods.
+	pub fn try_parse_lp<B: BuilderBase>(&self, text: &str, mut builder: B) -> Result<Lp, ParseError> {
+		let components = self.try_parse_components(text)?;
+		build_components(components, &mut builder).map_err(|message| ParseError { index: 0, message })
+	}
+
+	/// Parses a model for editing it statement by statement with
+	/// `reparse_statement`.
+	pub fn parse_model(&self, text: &str) -> Result<ParsedModel, ParseError> {
+		self.get_model(text).map_err(|message| ParseError { index: 0, message })
+	}
+
+	fn get_components(&self, text: &str) -> Result<Components, String> {
+		self.get_model(text).map(|model| model.components)
+	}
+
+	fn get_model(&self, text: &str) -> Result<ParsedModel, String> {
+		let text = self.expand_numbers(&self.strip_comments(&self.expand_constraint_blocks(text)));
+		let (parameters, text) = self.collect_parameters(&text)?;
+		let (vectors, text) = self.collect_coefficient_vectors(&text)?;
+		let names = if self.positional_reference_regex.is_match(&text) { self.declared_names(&text)? } else { vec![] };
+		let text = self.expand_statement(&text, &parameters, &vectors, &names)?;
+		let mut components = vec![];
+		for line in text.split(';').map(|line| line.trim()).filter(|line| !line.is_empty()) {
+			match self.component_from_line(line)? {
+				Component::Comment => {},
+				component => components.push(component)
+			}
+		}
+		let mut definitions: Vec<(String, Vec<Variable>)> = components.iter()
+			.filter_map(|component| match *component {
+				Component::Definition(ref name, ref terms) => Some((name.clone(), terms.clone())),
+				_ => None
+			})
 			.collect();
 
 		let mut variables = vec![];
-		let mut constraints = vec![];
+		let mut kinds = vec![];
+		let mut constraints: Vec<Constraint> = vec![];
 		let mut objective = None;
+		let mut statements = vec![];
 
 		for c in components {
 			match c {
-				Component::Variable(var) => {
+				Component::Variable(var, kind) => {
+					if kind != VariableKind::Continuous {
+						kinds.push((var.name.clone(), kind));
+					}
+					statements.push(Statement::Variables(variables.len(), variables.len() + 1));
 					variables.push(var);
+					self.check_variable_count(variables.len())?;
+				},
+				Component::VariableRange(vars, kind) => {
+					if kind != VariableKind::Continuous {
+						kinds.extend(vars.iter().map(|var| (var.name.clone(), kind)));
+					}
+					statements.push(Statement::Variables(variables.len(), variables.len() + vars.len()));
+					variables.extend(vars);
+					self.check_variable_count(variables.len())?;
 				},
 				Component::Constraint(con) => {
+					if self.options.auto_declare {
+						declare_new_variables(&mut variables, &con.variables);
+					}
+					statements.push(Statement::Constraint(constraints.len()));
 					constraints.push(con);
 				},
+				Component::ConstraintChain(chain) => {
+					if self.options.auto_declare {
+						for con in &chain {
+							declare_new_variables(&mut variables, &con.variables);
+						}
+					}
+					statements.push(Statement::Constraints(constraints.len(), constraints.len() + chain.len()));
+					let chain = name_chain(chain, &constraints);
+					constraints.extend(chain);
+				},
 				Component::Objective(obj) => {
+					let obj = link_definition(obj, &mut definitions, &variables);
+					if self.options.auto_declare {
+						declare_new_variables(&mut variables, &obj.variables);
+					}
+					statements.push(Statement::Objective);
 					objective = Some(obj);
 				},
-				Component::Comment => {}
+				Component::VectorObjective(maximize, coefficients) => {
+					statements.push(Statement::Objective);
+					objective = Some(self.resolve_vector_objective(maximize, coefficients, &variables)?);
+				},
+				Component::Definition(..) | Component::Comment => {}
+			}
+		}
+		if let Some((name, _)) = definitions.first() {
+			return Err(format!("\"{}\" is defined but no objective is declared as it", name));
+		}
+		let mut objective = objective.ok_or("No objective function provided!")?;
+		add_declared_costs(&mut objective, &variables)?;
+
+		Ok(ParsedModel {
+			components: Components {
+				variables,
+				kinds,
+				constraints,
+				objective
+			},
+			statements,
+			parameters,
+			vectors
+		})
+	}
+
+	/// Replaces the component parsed from one statement with the one parsed
+	/// from `new_text`, leaving the rest of the model untouched.
+	///
+	/// `index` counts the statements as listed in `model.statements`, and
+	/// the new statement must be of the same sort (a declaration, one
+	/// constraint or chain of constraints, or the objective). It is read
+	/// like a statement of the original text: it may use the `param`,
+	/// `const` and `coeffs` values declared there and `$k` references to
+	/// the declared variables, but can't declare new ones. A declaration
+	/// may change the number of variables and a chain the number of
+	/// constraints, shifting the later ones. Gives an error on a bad index,
+	/// invalid syntax or a statement of another sort, leaving the model as
+	/// it was.
+	pub fn reparse_statement(&self, model: &mut ParsedModel, index: usize, new_text: &str) -> Result<(), ParseError> {
+		self.replace_statement(model, index, new_text).map_err(|message| ParseError { index: 0, message })
+	}
+
+	fn replace_statement(&self, model: &mut ParsedModel, index: usize, new_text: &str) -> Result<(), String> {
+		let slot = match model.statements.get(index) {
+			Some(slot) => slot.clone(),
+			None => return Err(format!("No statement {} among {} statements", index, model.statements.len()))
+		};
+		let mismatch = || format!("Statement {} is {:?} and can't be replaced by \"{}\"", index, slot, new_text);
+
+		let text = self.expand_numbers(&self.strip_comments(new_text.trim().trim_end_matches(';')));
+		if text.contains(';') || self.parameter_regex.is_match(text.trim()) || self.coefficient_vector_regex.is_match(text.trim()) {
+			return Err(mismatch());
+		}
+		let names = if self.positional_reference_regex.is_match(&text) { declared_names(model) } else { vec![] };
+		let text = self.expand_statement(&text, &model.parameters, &model.vectors, &names)?;
+		let component = match text.trim() {
+			"" => return Err(mismatch()),
+			line => self.component_from_line(line)?
+		};
+
+		let mut edited = model.clone();
+		match (slot.clone(), component) {
+			(Statement::Variables(start, end), Component::Variable(var, kind)) => {
+				replace_variables(&mut edited, index, start, end, vec![var], kind)?;
+			},
+			(Statement::Variables(start, end), Component::VariableRange(vars, kind)) => {
+				self.check_variable_count(model.components.variables.len() - (end - start) + vars.len())?;
+				replace_variables(&mut edited, index, start, end, vars, kind)?;
+			},
+			(Statement::Constraint(i), Component::Constraint(con)) => {
+				self.replace_constraints(&mut edited, index, i, i + 1, vec![con]);
+			},
+			(Statement::Constraint(i), Component::ConstraintChain(chain)) => {
+				self.replace_constraints(&mut edited, index, i, i + 1, chain);
+			},
+			(Statement::Constraints(start, end), Component::Constraint(con)) => {
+				self.replace_constraints(&mut edited, index, start, end, vec![con]);
+			},
+			(Statement::Constraints(start, end), Component::ConstraintChain(chain)) => {
+				self.replace_constraints(&mut edited, index, start, end, chain);
+			},
+			(Statement::Objective, Component::Objective(mut obj)) => {
+				if self.options.auto_declare {
+					declare_new_variables(&mut edited.components.variables, &obj.variables);
+				}
+				add_declared_costs(&mut obj, &edited.components.variables)?;
+				edited.components.objective = obj;
+			},
+			(Statement::Objective, Component::VectorObjective(maximize, coefficients)) => {
+				let mut obj = self.resolve_vector_objective(maximize, coefficients, &edited.components.variables)?;
+				add_declared_costs(&mut obj, &edited.components.variables)?;
+				edited.components.objective = obj;
+			},
+			_ => return Err(mismatch())
+		}
+
+		*model = edited;
+		Ok(())
+	}
+
+	// Swaps the constraints of one statement, one or a chain of them, and
+	// moves the later statements' indices along
+	fn replace_constraints(&self, model: &mut ParsedModel, index: usize, start: usize, end: usize, new: Vec<Constraint>) {
+		if self.options.auto_declare {
+			for con in &new {
+				declare_new_variables(&mut model.components.variables, &con.variables);
+			}
+		}
+
+		let count = new.len();
+		let new = if count == 1 { new } else { name_chain(new, &model.components.constraints[.. start]) };
+		model.components.constraints.splice(start .. end, new);
+		model.statements[index] = if count == 1 { Statement::Constraint(start) } else { Statement::Constraints(start, start + count) };
+		for statement in model.statements[index + 1 ..].iter_mut() {
+			match *statement {
+				Statement::Constraint(ref mut i) => *i = *i - (end - start) + count,
+				Statement::Constraints(ref mut later_start, ref mut later_end) => {
+					*later_start = *later_start - (end - start) + count;
+					*later_end = *later_end - (end - start) + count;
+				},
+				_ => {}
 			}
 		}
+	}
 
-		Components {
-			variables: variables,
-			constraints: constraints,
-			objective: objective.expect("No objective function provided!")
+	// Applies the digit separator and SI suffix options
+	fn expand_numbers(&self, text: &str) -> String {
+		let mut text = text.to_string();
+		if self.options.digit_separators {
+			text = self.strip_digit_separators(&text);
 		}
+		if self.options.si_suffixes {
+			text = self.expand_si_suffixes(&text);
+		}
+		text
+	}
+
+	// Rewrites statements into the plain syntax `component_from_line`
+	// reads, with parameters, dot products and `$k` references replaced,
+	// parenthesized sums distributed and products of numbers folded
+	fn expand_statement(&self, text: &str, parameters: &HashMap<String, f64>, vectors: &HashMap<String, Vec<f64>>, names: &[String]) -> Result<String, String> {
+		let text = self.substitute_parameters(text, parameters);
+		let text = self.substitute_dot_products(&text, vectors)?;
+		let text = self.substitute_positional_references(&text, names)?;
+		let text = self.expand_groups(&text);
+		Ok(self.fold_constant_products(&text))
+	}
+
+	// Rewrites every `subject to { c1: ...; c2: ...; }` block into the
+	// equivalent flat `subject to c1: ...; subject to c2: ...;` statements
+	fn expand_constraint_blocks(&self, text: &str) -> String {
+		self.constraint_block_regex.replace_all(text, |caps: &regex::Captures| {
+			caps["body"]
+				.split(';')
+				.map(|line| line.trim())
+				.filter(|line| !line.is_empty())
+				.map(|line| format!("subject to {};", line))
+				.collect::<Vec<String>>()
+				.join("\n")
+		}).into_owned()
+	}
+
+	// Drops every statement holding a `#`, so what follows is never read
+	// from a comment, e.g. a `$k` that no declared variable matches
+	fn strip_comments(&self, text: &str) -> String {
+		text.split(';')
+			.filter(|statement| !statement.contains('#'))
+			.collect::<Vec<&str>>()
+			.join(";")
+	}
+
+	// Rewrites numbers like `100k` and `1.5M` into `100000` and `1500000`
+	fn expand_si_suffixes(&self, text: &str) -> String {
+		self.si_suffix_regex.replace_all(text, |caps: &regex::Captures| {
+			let multiplier = match &caps["suffix"] {
+				"k" => 1e3,
+				"M" => 1e6,
+				_ => 1e9
+			};
+			let value = caps["number"].parse::<f64>().unwrap() * multiplier;
+			format!("{}{}", &caps["prefix"], value)
+		}).into_owned()
+	}
+
+	// Rewrites numbers like `100_000` and `1_000.5` into `100000` and `1000.5`
+	fn strip_digit_separators(&self, text: &str) -> String {
+		self.digit_separator_regex.replace_all(text, |caps: &regex::Captures| {
+			format!("{}{}", &caps["prefix"], caps["number"].replace('_', ""))
+		}).into_owned()
+	}
+
+	// Removes every `param name = value;` (or `const name = value;`)
+	// declaration, returning the values along with the remaining statements
+	fn collect_parameters(&self, text: &str) -> Result<(HashMap<String, f64>, String), String> {
+		let mut parameters = HashMap::new();
+		let mut statements = vec![];
+
+		for statement in text.split(';') {
+			match self.parameter_regex.captures(statement.trim()) {
+				Some(caps) => {
+					let value = self.evaluate_parameter(&caps["value"], &parameters)?;
+					if parameters.insert(caps["name"].to_string(), value).is_some() {
+						return Err(format!("Parameter \"{}\" declared twice", &caps["name"]));
+					}
+				},
+				None => statements.push(statement)
+			}
+		}
+
+		Ok((parameters, statements.join(";")))
+	}
+
+	// Replaces each reference to a parameter in an expression by its value,
+	// in parentheses so a negative value keeps its sign. Names, keywords and
+	// comments are left alone
+	fn substitute_parameters(&self, text: &str, parameters: &HashMap<String, f64>) -> String {
+		if parameters.is_empty() {
+			return text.to_string();
+		}
+
+		text.split(';')
+			.map(|statement| match self.expression_start(statement) {
+				Some(start) => {
+					let body = self.expression_name_regex.replace_all(&statement[start ..], |caps: &regex::Captures| {
+						match parameters.get(&caps[0]) {
+							Some(value) => format!("({})", value),
+							None => caps[0].to_string()
+						}
+					});
+					format!("{}{}", &statement[.. start], body)
+				},
+				None => statement.to_string()
+			})
+			.collect::<Vec<String>>()
+			.join(";")
+	}
+
+	// Where the expressions of a statement start: after the `:` of a
+	// constraint, named objective or definition, or after the keyword of an
+	// unnamed objective. None for declarations and comments
+	fn expression_start(&self, statement: &str) -> Option<usize> {
+		let trimmed = statement.trim_start();
+		let offset = statement.len() - trimmed.len();
+		if trimmed.contains('#') || trimmed.starts_with("var") && trimmed[3 ..].starts_with(char::is_whitespace) {
+			return None;
+		}
+
+		match trimmed.find(':') {
+			Some(colon) => Some(offset + colon + 1),
+			None => ["minimize", "maximize", "minimise", "maximise"].iter()
+				.filter_map(|keyword| trimmed.find(keyword).map(|i| offset + i + keyword.len()))
+				.min()
+		}
+	}
+
+	// Removes every `coeffs name = [...];` declaration, returning the
+	// vectors along with the remaining statements
+	fn collect_coefficient_vectors(&self, text: &str) -> Result<(HashMap<String, Vec<f64>>, String), String> {
+		let mut vectors = HashMap::new();
+		let mut statements = vec![];
+
+		for statement in text.split(';') {
+			match self.coefficient_vector_regex.captures(statement.trim()) {
+				Some(caps) => {
+					let coefficients = self.parse_coefficient_vector(&caps["values"])?;
+					if vectors.insert(caps["name"].to_string(), coefficients).is_some() {
+						return Err(format!("Coefficient vector \"{}\" declared twice", &caps["name"]));
+					}
+				},
+				None => statements.push(statement)
+			}
+		}
+
+		Ok((vectors, statements.join(";")))
+	}
+
+	// Expands each dot product `name . [a, b, ...]` with a declared vector
+	// into `c_1*a + c_2*b + ...`
+	fn substitute_dot_products(&self, text: &str, vectors: &HashMap<String, Vec<f64>>) -> Result<String, String> {
+		if vectors.is_empty() {
+			return Ok(text.to_string());
+		}
+
+		// The closure can't return an error, so the first one is kept aside
+		let mut error = None;
+		let text = self.dot_product_regex.replace_all(text, |caps: &regex::Captures| {
+			let coefficients = match vectors.get(&caps["name"]) {
+				Some(coefficients) => coefficients,
+				None => return caps[0].to_string()
+			};
+			let vars: Vec<&str> = caps["vars"].split(',').map(|var| var.trim()).filter(|var| !var.is_empty()).collect();
+			if vars.len() != coefficients.len() && error.is_none() {
+				error = Some(format!("Coefficient vector \"{}\" has {} entries for {} variables in \"{}\"",
+					&caps["name"], coefficients.len(), vars.len(), &caps[0]));
+			}
+			coefficients.iter().zip(vars.iter())
+				.map(|(coeff, var)| format!("{}*{}", coeff, var))
+				.collect::<Vec<String>>()
+				.join(" + ")
+		}).into_owned();
+		match error {
+			Some(message) => Err(message),
+			None => Ok(text)
+		}
+	}
+
+	// Evaluates a parameter's value, an expression combining numbers and
+	// earlier parameters with `+`, `-`, `*` and `/` at the usual precedence
+	fn evaluate_parameter(&self, expr: &str, parameters: &HashMap<String, f64>) -> Result<f64, String> {
+		if let Some(name) = self.parameter_reference_regex.find_iter(expr).find(|name| !parameters.contains_key(name.as_str())) {
+			return Err(format!("Unknown parameter \"{}\" in \"{}\"", name.as_str(), expr));
+		}
+		let expr = self.parameter_reference_regex.replace_all(expr.trim(), |caps: &regex::Captures| {
+			format!("{}", parameters[&caps[0]])
+		}).into_owned();
+
+		self.evaluate_expression(&expr).ok_or_else(|| format!("Invalid parameter expression \"{}\"", expr))
 	}
 
-	fn component_from_line(&self, line: &str) -> Component {
-		match self.get_line_type(line) {
+	// Evaluates numbers combined with `+`, `-`, `*` and `/` at the usual
+	// precedence, or returns None if the expression is anything else
+	fn evaluate_expression(&self, expr: &str) -> Option<f64> {
+		let tokens: Vec<&str> = self.expression_token_regex.captures_iter(expr)
+			.map(|caps| caps.get(1).unwrap().as_str())
+			.collect();
+		let consumed: usize = self.expression_token_regex.find_iter(expr).map(|m| m.end() - m.start()).sum();
+		if consumed != expr.len() {
+			return None;
+		}
+
+		let mut total = 0.;
+		let mut product = 1.;
+		let mut sign = 1.;
+		let mut divide = false;
+		let mut expect_operand = true;
+		for token in tokens {
+			match (expect_operand, token) {
+				(true, "-") => sign = -sign,
+				(true, "+") => {},
+				(true, "*") | (true, "/") => return None,
+				(true, number) => {
+					let value = sign * number.parse::<f64>().unwrap();
+					product = if divide { product / value } else { product * value };
+					expect_operand = false;
+				},
+				(false, "*") | (false, "/") => {
+					divide = token == "/";
+					sign = 1.;
+					expect_operand = true;
+				},
+				(false, _) => {
+					total += product;
+					product = 1.;
+					divide = false;
+					sign = if token == "-" { -1. } else { 1. };
+					expect_operand = true;
+				}
+			}
+		}
+
+		if expect_operand {
+			return None;
+		}
+		Some(total + product)
+	}
+
+	// Distributes the multiplier of every parenthesized sum over its terms,
+	// innermost groups first, so `2 * (a + 3*b)` reads as `2*a + 2*3*b`
+	// (folded into `6*b` afterwards) and `-(a + b)` as `-1*a + -1*b`
+	fn expand_groups(&self, text: &str) -> String {
+		let mut text = text.to_string();
+		while self.group_regex.is_match(&text) {
+			text = self.group_regex.replace_all(&text, |caps: &regex::Captures| {
+				let coeff = caps.name("coeff").map_or(1., |coeff| coeff.as_str().parse::<f64>().unwrap());
+				let multiplier = if caps.name("sign").is_some() { -coeff } else { coeff };
+
+				let mut terms = vec![];
+				for term in caps["group"].split('+').map(|term| term.trim()) {
+					if self.options.whitespace_addition {
+						terms.extend(split_on_whitespace(term));
+					} else {
+						terms.push(term.to_string());
+					}
+				}
+				terms.iter()
+					.filter(|term| !term.is_empty())
+					.map(|term| match term.strip_prefix('-') {
+						Some(negated) => format!("{}*{}", -multiplier, negated.trim()),
+						None => format!("{}*{}", multiplier, term)
+					})
+					.collect::<Vec<String>>()
+					.join(" + ")
+			}).into_owned();
+		}
+		text
+	}
+
+	// Multiplies out products and quotients of numbers, so a coefficient
+	// written as `5 * 2 * a` (e.g. after substituting a parameter) reads
+	// as `10 * a`
+	fn fold_constant_products(&self, text: &str) -> String {
+		let mut text = text.to_string();
+		while self.constant_product_regex.is_match(&text) {
+			text = self.constant_product_regex.replace_all(&text, |caps: &regex::Captures| {
+				let left = caps["left"].parse::<f64>().unwrap();
+				let right = caps["right"].parse::<f64>().unwrap();
+				let value = if &caps["op"] == "*" { left * right } else { left / right };
+				format!("{}{}", &caps["prefix"], value)
+			}).into_owned();
+		}
+		text
+	}
+
+	// The names of the declared variables in declaration order, with
+	// ranges like `var x[1..3]` expanded
+	fn declared_names(&self, text: &str) -> Result<Vec<String>, String> {
+		let mut names = vec![];
+		for statement in text.split(';').map(|statement| statement.trim()) {
+			if statement.is_empty() || self.get_line_type(statement)? != LineType::Variable {
+				continue;
+			}
+			match self.parse_variable_range_declaration(statement)? {
+				Some(vars) => names.extend(vars.into_iter().map(|var| var.name)),
+				None => names.push(self.parse_variable_declaration(statement)?.name)
+			}
+		}
+		Ok(names)
+	}
+
+	// Replaces every `$k` by the k-th of the declared `names`, counting
+	// from 1
+	fn substitute_positional_references(&self, text: &str, names: &[String]) -> Result<String, String> {
+		if !self.positional_reference_regex.is_match(text) {
+			return Ok(text.to_string());
+		}
+
+		for caps in self.positional_reference_regex.captures_iter(text) {
+			match caps["index"].parse::<usize>() {
+				Ok(index) if index > 0 && index <= names.len() => {},
+				_ => return Err(format!("Positional reference ${} does not match any of the {} declared variables", &caps["index"], names.len()))
+			}
+		}
+		Ok(self.positional_reference_regex.replace_all(text, |caps: &regex::Captures| {
+			names[caps["index"].parse::<usize>().unwrap() - 1].clone()
+		}).into_owned())
+	}
+
+	fn component_from_line(&self, line: &str) -> Result<Component, String> {
+		Ok(match self.get_line_type(line)? {
 			LineType::Variable => {
-				Component::Variable(self.parse_variable_declaration(line))
+				let kind = self.parse_variable_kind(line);
+				match self.parse_variable_range_declaration(line)? {
+					Some(vars) => Component::VariableRange(vars, kind),
+					None => Component::Variable(self.parse_variable_declaration(line)?, kind)
+				}
 			},
 			LineType::Constraint => {
-				Component::Constraint(self.parse_constraint(line))
+				match self.parse_constraint_chain(line)? {
+					Some(chain) => Component::ConstraintChain(chain),
+					None => Component::Constraint(self.parse_constraint(line)?)
+				}
 			},
 			LineType::Objective => {
-				Component::Objective(self.parse_objective(line))
+				match self.vector_objective_regex.captures(line) {
+					Some(caps) => Component::VectorObjective(
+						caps["type"].starts_with("max"),
+						self.parse_coefficient_vector(&caps["coeffs"])?
+					),
+					None => Component::Objective(self.parse_objective(line)?)
+				}
+			},
+			LineType::Definition => {
+				let caps = self.definition_regex.captures(line).unwrap();
+				Component::Definition(caps["name"].to_string(), self.parse_objective_vars(&caps["expr"])?)
 			},
 			LineType::Comment => Component::Comment,
-		}
+		})
 	}
 
-	fn get_line_type(&self, line: &str) -> LineType {
+	fn get_line_type(&self, line: &str) -> Result<LineType, String> {
+		// Declarations and constraints are told apart by their leading
+		// keyword, so names like `covariance` or `maximize_x` don't count
 		if line.contains("#") {
-			return LineType::Comment;
-		} else if line.contains("var") {
-			return LineType::Variable;
-		} else if line.contains("minimize") || line.contains("maximize") {
-			return LineType::Objective;
-		} else if line.contains("subject to") {
-			return LineType::Constraint;
-		} 
+			return Ok(LineType::Comment);
+		} else if line.starts_with("var") && line[3 ..].starts_with(char::is_whitespace) {
+			return Ok(LineType::Variable);
+		} else if self.constraint_keyword_regex.is_match(line) {
+			return Ok(LineType::Constraint);
+		} else if self.definition_regex.is_match(line) {
+			return Ok(LineType::Definition);
+		} else if ["minimize", "maximize", "minimise", "maximise"].iter().any(|keyword| line.contains(keyword)) {
+			return Ok(LineType::Objective);
+		}
 
-		panic!("Unknown line type for \"{:?}\"", line);
+		Err(format!("Unknown line type for \"{:?}\"", line))
 	}
 
-	fn parse_variable_declaration(&self, data: &str) -> Variable {
-		let caps = self.variable_declaration_regex.captures(data).unwrap();
-		return Variable {
+	// Reads `var name`, with the objective coefficient of an optional
+	// `cost 3` (or `value 3`) as the variable's coefficient
+	fn parse_variable_declaration(&self, data: &str) -> Result<Variable, String> {
+		let caps = self.variable_declaration_regex.captures(data).ok_or_else(|| format!("Invalid declaration \"{}\"", data))?;
+		Ok(Variable {
 			name: caps["name"].to_string(),
+			coefficient: caps.name("cost").map_or(0., |cost| cost.as_str().replace(' ', "").parse::<f64>().unwrap()),
+		})
+	}
+
+	// Reads the optional `integer` or `binary` suffix of a declaration
+	fn parse_variable_kind(&self, data: &str) -> VariableKind {
+		match self.variable_kind_regex.captures(data) {
+			Some(ref caps) if &caps["kind"] == "integer" => VariableKind::Integer,
+			Some(_) => VariableKind::Binary,
+			None => VariableKind::Continuous
+		}
+	}
+
+	// Expands `var x[1..3]` into the variables `x[1]`, `x[2]` and `x[3]`
+	fn parse_variable_range_declaration(&self, data: &str) -> Result<Option<Vec<Variable>>, String> {
+		let caps = match self.variable_range_regex.captures(data) {
+			Some(caps) => caps,
+			None => return Ok(None)
+		};
+		let (start, end) = match (caps["start"].parse::<usize>(), caps["end"].parse::<usize>()) {
+			(Ok(start), Ok(end)) if end < usize::MAX => (start, end),
+			_ => return Err(format!("Variable range too large in \"{}\"", data))
+		};
+		if start > end {
+			return Err(format!("Empty variable range in \"{}\"", data));
+		}
+		self.check_variable_count(end - start + 1)?;
+
+		Ok(Some((start .. end + 1).map(|i| Variable {
+			name: format!("{}[{}]", &caps["name"], i),
 			coefficient: 0.,
-		}	
+		}).collect()))
 	}
 
-	fn parse_constraint(&self, data: &str) -> Constraint {
-		let caps = self.constraint_regex.captures(data).unwrap();
-		let name = caps["name"].to_string();
-		let relation = if caps["type"].contains("<") {
-			Relation::LessThanOrEqual
-		} else if caps["type"].contains(">") {
-			Relation::GreaterThanOrEqual
-		} else {
-			Relation::Equal
+	fn check_variable_count(&self, count: usize) -> Result<(), String> {
+		if let Some(max) = self.options.max_variables {
+			if count > max {
+				return Err(format!("Too many variables: {} exceeds the limit of {}", count, max));
+			}
+		}
+		Ok(())
+	}
+
+	fn parse_constraint(&self, data: &str) -> Result<Constraint, String> {
+		let caps = match self.constraint_header_regex.captures(data) {
+			Some(caps) => caps,
+			None => return Err(format!("Invalid constraint \"{}\"", data))
 		};
-			
-		let constant = caps["constant"].parse::<f64>().unwrap();
-		let variables = self.parse_objective_vars(&caps["terms"]);
+		let name = unquote_name(&caps["name"]);
+		let body = caps.name("body").unwrap().as_str().trim();
 
-		Constraint {
-			name: name,
-			variables: variables,
-			constant: constant,
-			relation: relation
+		if let Some(interval) = self.interval_constraint_regex.captures(body) {
+			return self.parse_interval_constraint(name, &interval);
 		}
 
+		match *tokenize_constraint(body, data)?.as_slice() {
+			[ConstraintToken::Expression(terms), ConstraintToken::Relation(ref relation), ConstraintToken::Expression(constant)] => {
+				let (variables, offset) = self.parse_terms(terms)?;
+				Ok(Constraint {
+					name,
+					variables,
+					constant: self.parse_constant(constant, data)? - offset,
+					relation: relation.clone()
+				})
+			},
+			[ConstraintToken::Expression(left), ConstraintToken::Relation(ref ltype), ConstraintToken::Expression(terms), ConstraintToken::Relation(ref rtype), ConstraintToken::Expression(right)] => {
+				self.parse_range_constraint(name, self.parse_constant(left, data)?, ltype, terms, rtype, self.parse_constant(right, data)?)
+			},
+			_ => Err(format!("Invalid constraint \"{}\": expected `terms <relation> constant` or `lower <= terms <= upper`", data))
+		}
 	}
 
-	fn parse_objective(&self, data: &str) -> Objective {
-		let caps = self.objective_regex.captures(data).expect("Invalid objective!");
+	// Splits a chain `expr1 <= expr2 <= expr3` where an outer expression
+	// has variables into `expr1 - expr2 <= 0` and `expr2 - expr3 <= 0`,
+	// both named `name` until they go into the model. Each part keeps its
+	// own relation. None for any other constraint, including ranges
+	// between two constants.
+	fn parse_constraint_chain(&self, data: &str) -> Result<Option<Vec<Constraint>>, String> {
+		let caps = match self.constraint_header_regex.captures(data) {
+			Some(caps) => caps,
+			None => return Ok(None)
+		};
+		let name = unquote_name(&caps["name"]);
+		let body = caps.name("body").unwrap().as_str().trim();
+		if self.interval_constraint_regex.is_match(body) {
+			return Ok(None);
+		}
 
-		Objective {
-			name: caps["name"].to_string(),
-			variables: self.parse_objective_vars(&caps["equation"]),
-			maximize: caps["type"].contains("maximize")
+		match tokenize_constraint(body, data)?.as_slice() {
+			&[ConstraintToken::Expression(left), ConstraintToken::Relation(ref ltype), ConstraintToken::Expression(middle), ConstraintToken::Relation(ref rtype), ConstraintToken::Expression(right)]
+				if self.evaluate_expression(left).is_none() || self.evaluate_expression(right).is_none() => Ok(Some(vec![
+					self.parse_difference_constraint(name.clone(), left, ltype, middle)?,
+					self.parse_difference_constraint(name, middle, rtype, right)?
+				])),
+			_ => Ok(None)
 		}
 	}
 
-	fn parse_objective_vars(&self, data: &str) -> Vec<Variable> {
-		data.split('+').map(|s| s.trim()).map(|var| self.parse_variable(var)).collect()
+	// Reads `left <relation> right` as `left - right <relation> 0`, with
+	// the constants of both sides moved to the right
+	fn parse_difference_constraint(&self, name: String, left: &str, relation: &Relation, right: &str) -> Result<Constraint, String> {
+		let (mut variables, left_offset) = self.parse_terms(left)?;
+		let (right_variables, right_offset) = self.parse_terms(right)?;
+		for var in right_variables {
+			match variables.iter().position(|term| term.name == var.name) {
+				Some(i) => variables[i].coefficient -= var.coefficient,
+				None => variables.push(Variable { name: var.name, coefficient: -var.coefficient })
+			}
+		}
+
+		Ok(Constraint {
+			name,
+			variables,
+			constant: right_offset - left_offset,
+			relation: relation.clone()
+		})
+	}
+
+	// Reads a constant side of a constraint, a number or arithmetic on
+	// numbers like `180 + 2` once parameters are substituted
+	fn parse_constant(&self, constant: &str, data: &str) -> Result<f64, String> {
+		self.evaluate_expression(constant)
+			.ok_or_else(|| format!("Invalid constraint \"{}\": the right hand side must be a number or a declared param or const", data))
 	}
 
-	fn parse_variable(&self, data: &str) -> Variable {
-		let caps = self.variable_regex.captures(data).unwrap();
+	// Handles both `lower <= expr <= upper` and `upper >= expr >= lower`
+	fn parse_range_constraint(&self, name: String, left: f64, ltype: &Relation, terms: &str, rtype: &Relation, right: f64) -> Result<Constraint, String> {
+		if *ltype == Relation::Equal || *rtype == Relation::Equal {
+			return Err(format!("Ranged constraint \"{}\" can't use ==", name));
+		} else if ltype != rtype {
+			return Err(format!("Ranged constraint \"{}\" mixes <= and >=", name));
+		}
+
+		let less = *ltype == Relation::LessThanOrEqual;
+
+		let (lower, upper) = if less { (left, right) } else { (right, left) };
+		let (variables, offset) = self.parse_terms(terms)?;
+
+		Ok(Constraint {
+			name,
+			variables,
+			constant: upper - offset,
+			relation: Relation::Range(lower - offset)
+		})
+	}
+
+	// Handles `expr in [lower, upper]`, the same as `lower <= expr <= upper`
+	fn parse_interval_constraint(&self, name: String, caps: &regex::Captures) -> Result<Constraint, String> {
+		let lower = caps["lower"].parse::<f64>().unwrap();
+		let upper = caps["upper"].parse::<f64>().unwrap();
+		let (variables, offset) = self.parse_terms(&caps["terms"])?;
+
+		Ok(Constraint {
+			name,
+			variables,
+			constant: upper - offset,
+			relation: Relation::Range(lower - offset)
+		})
+	}
+
+	fn parse_objective(&self, data: &str) -> Result<Objective, String> {
+		let caps = self.objective_regex.captures(data).ok_or("Invalid objective!")?;
+
+		Ok(Objective {
+			name: caps.name("name").map_or_else(|| DEFAULT_OBJECTIVE_NAME.to_string(), |name| unquote_name(name.as_str())),
+			variables: match caps.name("equation") {
+				Some(equation) => self.parse_objective_vars(equation.as_str())?,
+				None => vec![]
+			},
+			maximize: caps["type"].starts_with("max")
+		})
+	}
+
+	// Reads the `100000, 40000, -18000` inside a vector objective
+	fn parse_coefficient_vector(&self, data: &str) -> Result<Vec<f64>, String> {
+		data.split(',')
+			.map(|coeff| coeff.trim())
+			.filter(|coeff| !coeff.is_empty())
+			.map(|coeff| {
+				let caps = self.constant_term_regex.captures(coeff).ok_or("Invalid coefficient in vector objective!")?;
+				let value = caps["value"].parse::<f64>().unwrap();
+				Ok(if caps.name("sign").is_some() { -value } else { value })
+			})
+			.collect()
+	}
+
+	// Pairs each coefficient with the variable declared at the same position
+	fn resolve_vector_objective(&self, maximize: bool, coefficients: Vec<f64>, variables: &[Variable]) -> Result<Objective, String> {
+		if coefficients.len() != variables.len() {
+			return Err(format!("Vector objective has {} coefficients for {} declared variables", coefficients.len(), variables.len()));
+		}
+
+		Ok(Objective {
+			name: DEFAULT_OBJECTIVE_NAME.to_string(),
+			variables: variables.iter().zip(coefficients).map(|(var, coeff)| Variable {
+				name: var.name.clone(),
+				coefficient: coeff
+			}).collect(),
+			maximize
+		})
+	}
+
+	fn parse_objective_vars(&self, data: &str) -> Result<Vec<Variable>, String> {
+		// Left empty when the declarations carry the coefficients
+		if data.trim().is_empty() {
+			return Ok(vec![]);
+		}
+
+		let (variables, offset) = self.parse_terms(data)?;
+		if offset != 0. {
+			return Err(format!("Constant term in \"{}\" is only supported in constraints", data));
+		}
+
+		Ok(variables)
+	}
+
+	// Splits `2*a + 5 + 3*b` into its variable terms and the sum of its bare
+	// constant terms, which constraints move over to the right hand side
+	fn parse_terms(&self, data: &str) -> Result<(Vec<Variable>, f64), String> {
+		let mut variables = vec![];
+		let mut offset = 0.;
+
+		let mut terms: Vec<String> = vec![];
+		for term in data.split('+').map(|s| s.trim()) {
+			if self.options.whitespace_addition {
+				terms.extend(split_on_whitespace(term));
+			} else {
+				terms.push(term.to_string());
+			}
+		}
+
+		for term in &terms {
+			match self.constant_term_regex.captures(term) {
+				Some(caps) => {
+					let value = caps["value"].parse::<f64>().unwrap();
+					offset += if caps.name("sign").is_some() { -value } else { value };
+				},
+				None => variables.push(self.parse_variable(term)?)
+			}
+		}
+
+		Ok((variables, offset))
+	}
+
+	fn parse_variable(&self, data: &str) -> Result<Variable, String> {
+		let caps = match self.reversed_variable_regex.captures(data) {
+			Some(caps) if self.options.coefficient_after_variable => caps,
+			_ => self.variable_regex.captures(data).ok_or_else(|| format!("Invalid term \"{}\"", data))?
+		};
 		let name = caps["name"].to_string();
 		let sign = match caps.name("sign") {
 			None => {
@@ -246,13 +1082,194 @@ impl Parser {
 			}
 		};
 
-		Variable {
-				name: name,
+		Ok(Variable {
+				name,
 				coefficient: coefficient * sign,
+		})
+	}
+}
+
+// Feeds the components to the builder in the order it needs them
+pub(super) fn build_components<B: BuilderBase>(components: Components, builder: &mut B) -> Result<Lp, String> {
+	for v in components.variables {
+		builder.try_add_variable(v)?;
+	}
+
+	for (name, kind) in components.kinds {
+		builder.try_set_variable_kind(&name, kind)?;
+	}
+
+	for c in components.constraints {
+		builder.try_add_constraint(c)?;
+	}
+
+	builder.try_add_objective(components.objective)?;
+
+	builder.try_build_lp()
+}
+
+// Swaps the variables declared by one statement, keeping kinds in
+// declaration order and moving the later declarations' indices along.
+// The objective terms added for declared costs are added again, like
+// `get_model` does, so removed declarations take their costs along
+fn replace_variables(model: &mut ParsedModel, index: usize, start: usize, end: usize, vars: Vec<Variable>, kind: VariableKind) -> Result<(), String> {
+	let existing = &mut model.components;
+	let removed: Vec<Variable> = existing.variables[start .. end].to_vec();
+	let declared: Vec<String> = existing.variables.iter()
+		.filter(|var| var.coefficient != 0.)
+		.map(|var| var.name.clone())
+		.collect();
+	existing.objective.variables.retain(|term| !declared.contains(&term.name));
+
+	let kinds_before = existing.kinds.iter()
+		.filter(|&(name, _)| existing.variables[.. start].iter().any(|var| var.name == *name))
+		.count();
+	existing.kinds.retain(|(name, _)| !removed.iter().any(|var| var.name == *name));
+	if kind != VariableKind::Continuous {
+		for (offset, var) in vars.iter().enumerate() {
+			existing.kinds.insert(kinds_before + offset, (var.name.clone(), kind));
 		}
 	}
+
+	let count = vars.len();
+	existing.variables.splice(start .. end, vars);
+	model.statements[index] = Statement::Variables(start, start + count);
+	for statement in model.statements[index + 1 ..].iter_mut() {
+		if let Statement::Variables(ref mut later_start, ref mut later_end) = *statement {
+			*later_start = *later_start - (end - start) + count;
+			*later_end = *later_end - (end - start) + count;
+		}
+	}
+	add_declared_costs(&mut model.components.objective, &model.components.variables)
+}
+
+// The names of the variables declared by the statements of a model, in
+// declaration order
+fn declared_names(model: &ParsedModel) -> Vec<String> {
+	model.statements.iter()
+		.filter_map(|statement| match *statement {
+			Statement::Variables(start, end) => Some(&model.components.variables[start .. end]),
+			_ => None
+		})
+		.flat_map(|vars| vars.iter().map(|var| var.name.clone()))
+		.collect()
 }
 
+// Gives the parts of a chain its name, made unique among the constraints
+// before it with the suffixes the Builder uses for repeated names, so
+// `order` splits into `order` and `order_2`
+fn name_chain(chain: Vec<Constraint>, before: &[Constraint]) -> Vec<Constraint> {
+	let mut named: Vec<Constraint> = vec![];
+	for mut con in chain {
+		con.name = unique_name(&con.name, |name| before.iter().chain(named.iter()).any(|other| other.name == name));
+		named.push(con);
+	}
+	named
+}
+
+// Appends the variables used in `terms` that aren't declared yet, in order
+// of first use
+fn declare_new_variables(variables: &mut Vec<Variable>, terms: &[Variable]) {
+	for term in terms {
+		if !variables.iter().any(|var| var.name == term.name) {
+			variables.push(Variable {
+				name: term.name.clone(),
+				coefficient: 0.
+			});
+		}
+	}
+}
+
+// Splits `3*a 2 * b - c` into the terms `3*a`, `2*b` and `-c`, keeping
+// products and signs together with what follows them
+fn split_on_whitespace(term: &str) -> Vec<String> {
+	let mut pieces: Vec<String> = vec![];
+	for token in term.split_whitespace() {
+		let glued = match pieces.last() {
+			Some(last) => last.ends_with('*') || last == "-" || token.starts_with('*'),
+			None => false
+		};
+		if glued {
+			pieces.last_mut().unwrap().push_str(token);
+		} else {
+			pieces.push(token.to_string());
+		}
+	}
+	pieces
+}
+
+// Splits the body of a constraint into the expressions and the relations
+// between them, so `a <=5` reads as `a`, `<=`, `5` whatever the spacing.
+// Fails on an empty expression or an unknown relation, e.g. a lone `=`
+fn tokenize_constraint<'a>(body: &'a str, data: &str) -> Result<Vec<ConstraintToken<'a>>, String> {
+	let is_operator = |c: char| c == '<' || c == '>' || c == '=';
+	let mut tokens = vec![];
+	let mut rest = body;
+
+	loop {
+		let end = rest.find(is_operator).unwrap_or(rest.len());
+		let expression = rest[.. end].trim();
+		if expression.is_empty() {
+			return Err(format!("Invalid constraint \"{}\": missing an expression around a relation", data));
+		}
+		tokens.push(ConstraintToken::Expression(expression));
+		if end == rest.len() {
+			return Ok(tokens);
+		}
+
+		rest = &rest[end ..];
+		let length = rest.find(|c: char| !is_operator(c)).unwrap_or(rest.len());
+		let relation = match &rest[.. length] {
+			"<=" | "=<" => Relation::LessThanOrEqual,
+			">=" | "=>" => Relation::GreaterThanOrEqual,
+			"==" => Relation::Equal,
+			operator => return Err(format!("Invalid constraint \"{}\": unknown relation \"{}\"", data, operator))
+		};
+		tokens.push(ConstraintToken::Relation(relation));
+		rest = &rest[length ..];
+	}
+}
+
+// An objective declared as just `maximize name` (or `minimize name`),
+// where `name` is no variable but has a `name: expr` definition, takes the
+// definition's expression; the definition is used up. Any other objective
+// is returned unchanged.
+fn link_definition(objective: Objective, definitions: &mut Vec<(String, Vec<Variable>)>, variables: &[Variable]) -> Objective {
+	let name = match objective.variables.as_slice() {
+		[ref term] if objective.name == DEFAULT_OBJECTIVE_NAME && term.coefficient == 1. => term.name.clone(),
+		_ => return objective
+	};
+	if variables.iter().any(|var| var.name == name) {
+		return objective;
+	}
+
+	match definitions.iter().position(|(defined, _)| *defined == name) {
+		Some(index) => Objective {
+			name,
+			variables: definitions.remove(index).1,
+			maximize: objective.maximize
+		},
+		None => objective
+	}
+}
+
+// Adds the coefficients given in declarations like `var a cost 3` to
+// the objective, which must not list those variables itself
+fn add_declared_costs(objective: &mut Objective, variables: &[Variable]) -> Result<(), String> {
+	for var in variables.iter().filter(|var| var.coefficient != 0.) {
+		if objective.variables.iter().any(|term| term.name == var.name) {
+			return Err(format!("\"{}\" has a cost in its declaration and is in the objective as well", var.name));
+		}
+		objective.variables.push(var.clone());
+	}
+	Ok(())
+}
+
+// Strips the quotes off a name written as `"total-cost"`, which may contain
+// hyphens without them being read as minus signs
+fn unquote_name(name: &str) -> String {
+	name.trim_matches('"').to_string()
+}
 
 #[cfg(test)]
 mod LPParser_tests {
@@ -268,11 +1285,17 @@ mod LPParser_tests {
 		let max_objective= "maximize obj: 3*a;";
 		let constraint = "subject to foo_constraint: a == 10;";
 
-		assert_eq!(p.get_line_type(comment), LineType::Comment);
-		assert_eq!(p.get_line_type(variable), LineType::Variable);
-		assert_eq!(p.get_line_type(min_objective), LineType::Objective);
-		assert_eq!(p.get_line_type(max_objective), LineType::Objective);
-		assert_eq!(p.get_line_type(constraint), LineType::Constraint);
+		assert_eq!(p.get_line_type(comment).unwrap(), LineType::Comment);
+		assert_eq!(p.get_line_type(variable).unwrap(), LineType::Variable);
+		assert_eq!(p.get_line_type(min_objective).unwrap(), LineType::Objective);
+		assert_eq!(p.get_line_type(max_objective).unwrap(), LineType::Objective);
+		assert_eq!(p.get_line_type(constraint).unwrap(), LineType::Constraint);
+
+		assert_eq!(p.get_line_type("subject to c1: covariance <= 3").unwrap(), LineType::Constraint);
+		assert_eq!(p.get_line_type("subject to c1: maximize_x <= 3").unwrap(), LineType::Constraint);
+		assert_eq!(p.get_line_type("subject\tto c1: a <= 3").unwrap(), LineType::Constraint);
+		assert_eq!(p.get_line_type("subject  to c1: a <= 3").unwrap(), LineType::Constraint);
+		assert_eq!(p.get_line_type("minimize obj: variance").unwrap(), LineType::Objective);
 	}
 
 
@@ -286,7 +1309,7 @@ mod LPParser_tests {
 			coefficient: 0.,
 		};
 
-		assert_eq!(p.parse_variable_declaration(variable), expected);
+		assert_eq!(p.parse_variable_declaration(variable).unwrap(), expected);
 	}
 
 	#[test]
@@ -300,9 +1323,821 @@ mod LPParser_tests {
 			generate_var("c".to_string(), -0.5),
 		];
 
-		assert_eq!(p.parse_objective_vars(data), expected);
+		assert_eq!(p.parse_objective_vars(data).unwrap(), expected);
+	}
+
+	#[test]
+	fn parse_transposed_relation_test() {
+		let p = Parser::new();
+
+		let less = p.parse_constraint("subject to foo: 2*a + b =< 10").unwrap();
+		let greater = p.parse_constraint("subject to bar: 2*a + b => 10").unwrap();
+
+		assert_eq!(less.relation, Relation::LessThanOrEqual);
+		assert_eq!(less.constant, 10.);
+		assert_eq!(less.variables, vec![generate_var("a".to_string(), 2.), generate_var("b".to_string(), 1.)]);
+		assert_eq!(greater.relation, Relation::GreaterThanOrEqual);
+		assert_eq!(greater.constant, 10.);
+	}
+
+	#[test]
+	fn parse_constraint_spacing_test() {
+		let p = Parser::new();
+		let expected = vec![generate_var("a".to_string(), 1.), generate_var("b".to_string(), 1.)];
+
+		for data in &[
+			"subject to c1: a+b<=5",
+			"subject to c1: a+b <=5",
+			"subject to c1: a+b<= 5",
+			"subject to c1:a + b <= 5",
+			"subject to c1 : a + b <= 5",
+			"subject to\tc1: a + b\t<=\t5",
+		] {
+			let constraint = p.parse_constraint(data).unwrap();
+			assert_eq!(constraint.name, "c1", "name for {:?}", data);
+			assert_eq!(constraint.relation, Relation::LessThanOrEqual, "relation for {:?}", data);
+			assert_eq!(constraint.constant, 5., "constant for {:?}", data);
+			assert_eq!(constraint.variables, expected, "variables for {:?}", data);
+		}
+
+		let greater = p.parse_constraint("subject to c2: a >=5").unwrap();
+		assert_eq!(greater.relation, Relation::GreaterThanOrEqual);
+		assert_eq!(greater.constant, 5.);
+		assert_eq!(greater.variables, vec![generate_var("a".to_string(), 1.)]);
+
+		let equal = p.parse_constraint("subject to c3: a==5").unwrap();
+		assert_eq!(equal.relation, Relation::Equal);
+		assert_eq!(equal.constant, 5.);
+
+		let range = p.parse_constraint("subject to c4: 1<=a+b<=5").unwrap();
+		assert_eq!(range.relation, Relation::Range(1.));
+		assert_eq!(range.constant, 5.);
+	}
+
+	#[test]
+	fn tokenize_constraint_test() {
+		let expected = vec![
+			ConstraintToken::Expression("a"),
+			ConstraintToken::Relation(Relation::LessThanOrEqual),
+			ConstraintToken::Expression("5")
+		];
+		for body in &["a<= 5", "a <=5", "a <= 5 ", "a=<5", "\ta\n<=\n5"] {
+			assert_eq!(tokenize_constraint(body, body).unwrap(), expected, "tokens for {:?}", body);
+		}
+
+		assert_eq!(tokenize_constraint("1 >= a + b=>0", "").unwrap(), vec![
+			ConstraintToken::Expression("1"),
+			ConstraintToken::Relation(Relation::GreaterThanOrEqual),
+			ConstraintToken::Expression("a + b"),
+			ConstraintToken::Relation(Relation::GreaterThanOrEqual),
+			ConstraintToken::Expression("0")
+		]);
+
+		let p = Parser::new();
+		let trailing = p.parse_constraint("subject to c1: 2*a <= 5 ").unwrap();
+		assert_eq!(trailing.constant, 5.);
+		let negative = p.parse_constraint("subject to c1: a + b >= -3").unwrap();
+		assert_eq!(negative.relation, Relation::GreaterThanOrEqual);
+		assert_eq!(negative.constant, -3.);
+	}
+
+	#[test]
+	fn constraint_chain_test() {
+		let model = Parser::new().parse_model("
+			var a;
+			var b;
+			var c;
+			maximize obj: a + b + c;
+			subject to order: a <= b + 1 <= 2*c;
+			subject to mixed: a + 2 == b >= c;
+			subject to range: 1 <= a + b <= 4;
+		").unwrap();
+
+		assert_eq!(model.components.constraints, vec![
+			Constraint {
+				name: "order".to_string(),
+				variables: vec![generate_var("a".to_string(), 1.), generate_var("b".to_string(), -1.)],
+				constant: 1.,
+				relation: Relation::LessThanOrEqual
+			},
+			Constraint {
+				name: "order_2".to_string(),
+				variables: vec![generate_var("b".to_string(), 1.), generate_var("c".to_string(), -2.)],
+				constant: -1.,
+				relation: Relation::LessThanOrEqual
+			},
+			Constraint {
+				name: "mixed".to_string(),
+				variables: vec![generate_var("a".to_string(), 1.), generate_var("b".to_string(), -1.)],
+				constant: -2.,
+				relation: Relation::Equal
+			},
+			Constraint {
+				name: "mixed_2".to_string(),
+				variables: vec![generate_var("b".to_string(), 1.), generate_var("c".to_string(), -1.)],
+				constant: 0.,
+				relation: Relation::GreaterThanOrEqual
+			},
+			Constraint {
+				name: "range".to_string(),
+				variables: vec![generate_var("a".to_string(), 1.), generate_var("b".to_string(), 1.)],
+				constant: 4.,
+				relation: Relation::Range(1.)
+			},
+		]);
+		assert_eq!(model.statements[4 ..], [
+			Statement::Constraints(0, 2),
+			Statement::Constraints(2, 4),
+			Statement::Constraint(4)
+		]);
+	}
+
+	#[test]
+	fn expand_groups_test() {
+		let p = Parser::new();
+
+		let components = p.parse_components("
+			var a;
+			var b;
+			var c;
+			maximize obj: 3 * (a + 2*b) + c;
+			subject to c1: 2 * (a + b + c) <= 10;
+			subject to c2: a + -(b + -2*c) >= 1;
+			subject to c3: 2*(a + 1.5*(b + c) + 1) <= 8;
+		");
+		let coefficients = |vars: &[Variable]| vars.iter().map(|var| var.coefficient).collect::<Vec<f64>>();
+
+		assert_eq!(coefficients(&components.objective.variables), vec![3., 6., 1.]);
+		assert_eq!(coefficients(&components.constraints[0].variables), vec![2., 2., 2.]);
+		assert_eq!(components.constraints[0].constant, 10.);
+		assert_eq!(coefficients(&components.constraints[1].variables), vec![1., -1., 2.]);
+		assert_eq!(coefficients(&components.constraints[2].variables), vec![2., 3., 3.]);
+		assert_eq!(components.constraints[2].constant, 6.);
+	}
+
+	#[test]
+	fn parse_arithmetic_constant_test() {
+		let p = Parser::new();
+
+		let sum = p.parse_constraint("subject to c1: 20*a <= 180 + 2").unwrap();
+		assert_eq!(sum.constant, 182.);
+		assert_eq!(sum.variables, vec![generate_var("a".to_string(), 20.)]);
+
+		assert_eq!(p.parse_constraint("subject to c2: a >= 10 - 4 / 2").unwrap().constant, 8.);
+		assert_eq!(p.parse_constraint("subject to c3: a + 1 == 3 * 2 + -1").unwrap().constant, 4.);
+
+		let range = p.parse_constraint("subject to c4: 1 + 1 <= a <= 10 / 4").unwrap();
+		assert_eq!(range.relation, Relation::Range(2.));
+		assert_eq!(range.constant, 2.5);
+
+		let components = p.parse_components("var a; maximize obj: a; subject to c1: 20*a <= 180 + 2;");
+		assert_eq!(components.constraints[0].constant, 182.);
+	}
+
+	#[test]
+	fn tokenize_single_equals_test() {
+		let err = tokenize_constraint("a = 5", "subject to c1: a = 5").unwrap_err();
+		assert!(err.contains("unknown relation \"=\""));
+	}
+
+	#[test]
+	fn tokenize_missing_side_test() {
+		let err = Parser::new().parse_constraint("subject to c1: a + b <=").unwrap_err();
+		assert!(err.contains("missing an expression"));
+	}
+
+	#[test]
+	fn reparse_statement_test() {
+		let p = Parser::new();
+		let text = "
+			# Bakery;
+			var bagels;
+			var doughnuts integer;
+			maximize profits: 3*bagels + 1.25*doughnuts;
+			subject to flour: 12*bagels + 6.5*doughnuts <= 400;
+			subject to milk: bagels + .5*doughnuts <= 200;
+		";
+		let original = p.parse_model(text).unwrap();
+		assert_eq!(original.statements, vec![
+			Statement::Variables(0, 1),
+			Statement::Variables(1, 2),
+			Statement::Objective,
+			Statement::Constraint(0),
+			Statement::Constraint(1)
+		]);
+
+		let mut edited = original.clone();
+		p.reparse_statement(&mut edited, 4, "subject to milk: bagels + doughnuts <= 150;").unwrap();
+		assert_eq!(edited.components.constraints[1], p.parse_constraint("subject to milk: bagels + doughnuts <= 150").unwrap());
+		assert_eq!(edited.components.constraints[0], original.components.constraints[0]);
+		assert_eq!(edited.components.variables, original.components.variables);
+		assert_eq!(edited.components.objective, original.components.objective);
+		assert_eq!(edited.statements, original.statements);
+
+		p.reparse_statement(&mut edited, 2, "minimize cost: bagels").unwrap();
+		assert!(!edited.components.objective.maximize);
+		assert_eq!(edited.components.objective.name, "cost");
+
+		// A declaration growing into a range moves the later ones along
+		p.reparse_statement(&mut edited, 0, "var bagels[1..3] binary;").unwrap();
+		let names: Vec<&str> = edited.components.variables.iter().map(|var| var.name.as_str()).collect();
+		assert_eq!(names, vec!["bagels[1]", "bagels[2]", "bagels[3]", "doughnuts"]);
+		assert_eq!(edited.statements[0], Statement::Variables(0, 3));
+		assert_eq!(edited.statements[1], Statement::Variables(3, 4));
+		assert_eq!(edited.components.kinds, vec![
+			("bagels[1]".to_string(), VariableKind::Binary),
+			("bagels[2]".to_string(), VariableKind::Binary),
+			("bagels[3]".to_string(), VariableKind::Binary),
+			("doughnuts".to_string(), VariableKind::Integer)
+		]);
+		assert_eq!(edited.components.constraints, {
+			let mut constraints = original.components.constraints.clone();
+			constraints[1] = p.parse_constraint("subject to milk: bagels + doughnuts <= 150").unwrap();
+			constraints
+		});
+	}
+
+	#[test]
+	fn reparse_statement_substitution_test() {
+		let p = Parser::new();
+		let mut model = p.parse_model("
+			param cap = 10;
+			coeffs w = [2, 3];
+			var a;
+			var b;
+			maximize obj: a + b;
+			subject to c1: a <= cap;
+		").unwrap();
+
+		p.reparse_statement(&mut model, 3, "subject to c1: w . [$1, $2] <= 2*cap").unwrap();
+		assert_eq!(model.components.constraints[0], p.parse_constraint("subject to c1: 2*a + 3*b <= 20").unwrap());
+		assert_eq!(model, p.parse_model("
+			param cap = 10;
+			coeffs w = [2, 3];
+			var a;
+			var b;
+			maximize obj: a + b;
+			subject to c1: w . [a, b] <= 2*cap;
+		").unwrap());
+	}
+
+	#[test]
+	fn reparse_statement_cost_test() {
+		let p = Parser::new();
+		let mut model = p.parse_model("var a cost 2; var b; minimize obj: b; subject to c1: a + b >= 1;").unwrap();
+
+		p.reparse_statement(&mut model, 2, "minimize total: 3*b").unwrap();
+		assert_eq!(model, p.parse_model("var a cost 2; var b; minimize total: 3*b; subject to c1: a + b >= 1;").unwrap());
+
+		// Costs go along with their declarations
+		p.reparse_statement(&mut model, 0, "var a[1..2] cost 4").unwrap();
+		assert_eq!(model, p.parse_model("var a[1..2] cost 4; var b; minimize total: 3*b; subject to c1: a + b >= 1;").unwrap());
+		p.reparse_statement(&mut model, 0, "var a").unwrap();
+		assert_eq!(model.components.objective.variables, vec![generate_var("b".to_string(), 3.)]);
+
+		let err = p.reparse_statement(&mut model, 1, "var b cost 5").unwrap_err();
+		assert_eq!(err.message, "\"b\" has a cost in its declaration and is in the objective as well");
+		assert_eq!(model, p.parse_model("var a; var b; minimize total: 3*b; subject to c1: a + b >= 1;").unwrap());
+	}
+
+	#[test]
+	fn reparse_statement_chain_test() {
+		let p = Parser::new();
+		let mut model = p.parse_model("
+			var a; var b; var c;
+			maximize obj: a + b + c;
+			subject to c: a <= 1;
+			subject to order: a <= b <= c;
+			subject to last: c <= 5;
+		").unwrap();
+
+		p.reparse_statement(&mut model, 5, "subject to c: a <= b + 1 <= 2*c").unwrap();
+		assert_eq!(model, p.parse_model("
+			var a; var b; var c;
+			maximize obj: a + b + c;
+			subject to c: a <= 1;
+			subject to c: a <= b + 1 <= 2*c;
+			subject to last: c <= 5;
+		").unwrap());
+		let names: Vec<&str> = model.components.constraints.iter().map(|con| con.name.as_str()).collect();
+		assert_eq!(names, vec!["c", "c_2", "c_3", "last"]);
+
+		p.reparse_statement(&mut model, 5, "subject to order: a <= 2").unwrap();
+		assert_eq!(model.statements[5 ..], [Statement::Constraint(1), Statement::Constraint(2)]);
+		p.reparse_statement(&mut model, 4, "subject to first: a <= b <= c").unwrap();
+		assert_eq!(model.statements[4 ..], [Statement::Constraints(0, 2), Statement::Constraint(2), Statement::Constraint(3)]);
+		assert_eq!(model.components.constraints[3].name, "last");
+	}
+
+	#[test]
+	fn reparse_statement_error_test() {
+		let p = Parser::new();
+		let mut model = p.parse_model("var a; maximize obj: a; subject to c1: a <= 1;").unwrap();
+		let original = model.clone();
+
+		let err = p.reparse_statement(&mut model, 2, "var b;").unwrap_err();
+		assert!(err.message.contains("can't be replaced"));
+		let err = p.reparse_statement(&mut model, 3, "subject to c2: a <= 2;").unwrap_err();
+		assert_eq!(err.message, "No statement 3 among 3 statements");
+		let err = p.reparse_statement(&mut model, 2, "param cap = 2;").unwrap_err();
+		assert!(err.message.contains("can't be replaced"));
+		assert!(p.reparse_statement(&mut model, 2, "subject to c1: $2 <= 1;").is_err());
+		assert_eq!(model, original);
+	}
+
+	#[test]
+	fn constraint_block_test() {
+		let p = Parser::new();
+
+		let text = "
+			var a;
+			var b;
+			maximize obj: a + b;
+			subject  to {
+				c1: a <= 5;
+				c2: b <= 3;
+			}
+			subject to c3: a + b <= 7;
+		";
+		let components = p.parse_components(text);
+		let names: Vec<&str> = components.constraints.iter().map(|c| c.name.as_str()).collect();
+
+		assert_eq!(names, vec!["c1", "c2", "c3"]);
+		assert_eq!(components.constraints[0].variables, vec![generate_var("a".to_string(), 1.)]);
+		assert_eq!(components.constraints[0].constant, 5.);
+		assert_eq!(components.constraints[1].variables, vec![generate_var("b".to_string(), 1.)]);
+		assert_eq!(components.constraints[1].constant, 3.);
+	}
+
+	#[test]
+	fn parse_range_constraint_test() {
+		let p = Parser::new();
+
+		let less = p.parse_constraint("subject to r: 5 <= a + 2*b <= 20").unwrap();
+		let greater = p.parse_constraint("subject to r: 20 >= a + 2*b >= 5").unwrap();
+
+		assert_eq!(less.relation, Relation::Range(5.));
+		assert_eq!(less.constant, 20.);
+		assert_eq!(less.variables, vec![generate_var("a".to_string(), 1.), generate_var("b".to_string(), 2.)]);
+		assert_eq!(greater, less);
+	}
+
+	#[test]
+	fn parse_coefficient_after_variable_test() {
+		let p = Parser::with_options(ParserOptions {
+			coefficient_after_variable: true,
+			..ParserOptions::default()
+		});
+
+		assert_eq!(p.parse_variable("a*3.").unwrap(), generate_var("a".to_string(), 3.));
+		assert_eq!(p.parse_variable("a * -2.5").unwrap(), generate_var("a".to_string(), -2.5));
+		assert_eq!(p.parse_variable("3.*a").unwrap(), generate_var("a".to_string(), 3.));
+		assert_eq!(p.parse_objective_vars("a*3. + 2*b"), p.parse_objective_vars("3*a + 2*b"));
+	}
+
+	#[test]
+	fn parse_si_suffixes_test() {
+		let p = Parser::with_options(ParserOptions {
+			si_suffixes: true,
+			..ParserOptions::default()
+		});
+
+		let text = "
+			var television;
+			var newspaper;
+			var x1k;
+			maximize objective: 100k*television + 1.5M*newspaper + x1k;
+			subject to budget: 2k*television + 600*newspaper <= 18.2k;
+			subject to cap: x1k <= 2G;
+		";
+		let components = p.parse_components(text);
+
+		assert_eq!(components.objective.variables, vec![
+			generate_var("television".to_string(), 100000.),
+			generate_var("newspaper".to_string(), 1500000.),
+			generate_var("x1k".to_string(), 1.),
+		]);
+		assert_eq!(components.constraints[0].variables[0], generate_var("television".to_string(), 2000.));
+		assert_eq!(components.constraints[0].constant, 18200.);
+		assert_eq!(components.constraints[1].constant, 2e9);
+	}
+
+	#[test]
+	fn parse_digit_separators_test() {
+		let p = Parser::with_options(ParserOptions {
+			digit_separators: true,
+			..ParserOptions::default()
+		});
+
+		let text = "
+			var a;
+			var x_1;
+			maximize objective: 100_000.*a + 2*x_1;
+			subject to budget: 1_000.5*a + x_1 <= 2_500_000;
+		";
+		let components = p.parse_components(text);
+
+		assert_eq!(components.objective.variables, vec![
+			generate_var("a".to_string(), 100000.),
+			generate_var("x_1".to_string(), 2.),
+		]);
+		assert_eq!(components.constraints[0].variables[0], generate_var("a".to_string(), 1000.5));
+		assert_eq!(components.constraints[0].constant, 2500000.);
+	}
+
+	#[test]
+	fn objective_definition_test() {
+		let p = Parser::new();
+		let split = p.parse_components("
+			var a;
+			var b;
+			maximize profit;
+			subject to cap: a + b <= 4;
+			profit: 3*a + 2*b;
+		");
+		let joined = p.parse_components("
+			var a;
+			var b;
+			maximize profit: 3*a + 2*b;
+			subject to cap: a + b <= 4;
+		");
+		assert_eq!(split.objective, joined.objective);
+		assert_eq!(split.constraints, joined.constraints);
+
+		// A declared variable is maximized itself, as before
+		let own = p.parse_components("var a; var profit; maximize profit; subject to cap: a + profit <= 4;");
+		assert_eq!(own.objective.variables, vec![generate_var("profit".to_string(), 1.)]);
+	}
+
+	#[test]
+	#[should_panic(expected = "\"a\" has a cost in its declaration and is in the objective as well")]
+	fn declared_cost_in_objective_test() {
+		Parser::new().parse_components("var a cost 2; var b; maximize obj: a + b;");
+	}
+
+	#[test]
+	#[should_panic(expected = "\"profit\" is defined but no objective is declared as it")]
+	fn unused_objective_definition_test() {
+		Parser::new().parse_components("var a; maximize a; profit: 3*a;");
+	}
+
+	#[test]
+	fn dot_product_test() {
+		let p = Parser::new();
+		let (vectors, text) = p.collect_coefficient_vectors("
+			coeffs weights = [20, 6, -3];
+			subject to c1: weights . [television, newspaper, x[1]] <= 182;
+		").unwrap();
+		let expanded = p.substitute_dot_products(&text, &vectors).unwrap();
+		assert_eq!(expanded.trim(), "subject to c1: 20*television + 6*newspaper + -3*x[1] <= 182;");
+
+		let dotted = p.parse_components("
+			var television;
+			var newspaper;
+			var radio;
+			param budget = 182;
+			coeffs weights = [20, 6, 3];
+			coeffs prices = [1.5, 2, 0.5];
+			maximize reach: prices.[television, newspaper, radio];
+			subject to c1: weights . [television, newspaper, radio] <= budget;
+		");
+		let expected = p.parse_components("
+			var television;
+			var newspaper;
+			var radio;
+			maximize reach: 1.5*television + 2*newspaper + 0.5*radio;
+			subject to c1: 20*television + 6*newspaper + 3*radio <= 182;
+		");
+		assert_eq!(dotted.objective, expected.objective);
+		assert_eq!(dotted.constraints, expected.constraints);
+	}
+
+	#[test]
+	#[should_panic(expected = "Coefficient vector \"weights\" has 2 entries for 3 variables")]
+	fn dot_product_length_test() {
+		Parser::new().parse_components("var a; var b; var c; coeffs weights = [1, 2]; maximize obj: weights . [a, b, c];");
+	}
+
+	#[test]
+	fn parse_variable_range_test() {
+		let p = Parser::new();
+
+		let text = "
+			var x[1..4];
+			var y;
+			maximize obj: x[1] + 2*x[4] + y;
+			subject to c1: x[2] + 3*x[3] <= 5;
+		";
+		let components = p.parse_components(text);
+		let names: Vec<&str> = components.variables.iter().map(|v| v.name.as_str()).collect();
+
+		assert_eq!(names, vec!["x[1]", "x[2]", "x[3]", "x[4]", "y"]);
+		assert_eq!(components.objective.variables[1], generate_var("x[4]".to_string(), 2.));
+		assert_eq!(components.constraints[0].variables, vec![
+			generate_var("x[2]".to_string(), 1.),
+			generate_var("x[3]".to_string(), 3.),
+		]);
+	}
+
+	#[test]
+	fn parse_variable_kind_test() {
+		let p = Parser::new();
+
+		let text = "
+			var a;
+			var b integer;
+			var x[1..2] binary;
+			maximize obj: a + b + x[1];
+		";
+		let components = p.parse_components(text);
+
+		assert_eq!(components.variables.len(), 4);
+		assert_eq!(components.kinds, vec![
+			("b".to_string(), VariableKind::Integer),
+			("x[1]".to_string(), VariableKind::Binary),
+			("x[2]".to_string(), VariableKind::Binary),
+		]);
+	}
+
+	#[test]
+	fn parse_embedded_constant_test() {
+		let p = Parser::new();
+
+		let constraint = p.parse_constraint("subject to c1: 2*a + 5 + 3*b <= 0").unwrap();
+		assert_eq!(constraint.variables, vec![generate_var("a".to_string(), 2.), generate_var("b".to_string(), 3.)]);
+		assert_eq!(constraint.constant, -5.);
+
+		let constraint = p.parse_constraint("subject to c2: a + -1.5 + 4 == 10").unwrap();
+		assert_eq!(constraint.variables, vec![generate_var("a".to_string(), 1.)]);
+		assert_eq!(constraint.constant, 7.5);
+
+		let range = p.parse_constraint("subject to c3: 1 <= a + 2 <= 6").unwrap();
+		assert_eq!(range.relation, Relation::Range(-1.));
+		assert_eq!(range.constant, 4.);
+	}
+
+	#[test]
+	fn parse_unnamed_objective_test() {
+		let p = Parser::new();
+
+		let objective = p.parse_objective("maximize 3*a + 2*b").unwrap();
+		assert_eq!(objective, Objective {
+			name: "objective".to_string(),
+			variables: vec![generate_var("a".to_string(), 3.), generate_var("b".to_string(), 2.)],
+			maximize: true
+		});
+
+		let components = p.parse_components("var a; var b; minimize a + b;");
+		assert_eq!(components.objective.name, "objective");
+		assert!(!components.objective.maximize);
+		assert_eq!(components.objective.variables.len(), 2);
+
+		assert_eq!(p.parse_objective("maximize profit: a").unwrap().name, "profit");
+	}
+
+	#[test]
+	fn parse_quoted_name_test() {
+		let p = Parser::new();
+
+		let objective = p.parse_objective("minimize \"total-cost\": 3*a + 2*b").unwrap();
+		assert_eq!(objective.name, "total-cost");
+		assert_eq!(objective.variables, vec![generate_var("a".to_string(), 3.), generate_var("b".to_string(), 2.)]);
+
+		let constraint = p.parse_constraint("subject to \"max-a\": a + -1*b <= 4").unwrap();
+		assert_eq!(constraint.name, "max-a");
+		assert_eq!(constraint.variables, vec![generate_var("a".to_string(), 1.), generate_var("b".to_string(), -1.)]);
+		assert_eq!(constraint.constant, 4.);
+
+		let range = p.parse_constraint("subject to \"a-range\": 1 <= a <= 6").unwrap();
+		assert_eq!(range.name, "a-range");
+		assert_eq!(range.relation, Relation::Range(1.));
+
+		let components = p.parse_components("var a; var b; maximize obj: a; subject to { \"a-cap\": a <= 3; \"b-cap\": b <= 2; }");
+		assert_eq!(components.constraints[0].name, "a-cap");
+		assert_eq!(components.constraints[1].name, "b-cap");
+	}
+
+	#[test]
+	fn parse_whitespace_addition_test() {
+		let p = Parser::with_options(ParserOptions {
+			whitespace_addition: true,
+			..ParserOptions::default()
+		});
+
+		let components = p.parse_components("
+			var a;
+			var b;
+			var c;
+			maximize obj: 3*a 2*b c;
+			subject to c1: a 2 * b + -1*c 5 <= 10;
+		");
+		assert_eq!(components.objective.variables, vec![
+			generate_var("a".to_string(), 3.),
+			generate_var("b".to_string(), 2.),
+			generate_var("c".to_string(), 1.),
+		]);
+		assert_eq!(components.constraints[0].variables, vec![
+			generate_var("a".to_string(), 1.),
+			generate_var("b".to_string(), 2.),
+			generate_var("c".to_string(), -1.),
+		]);
+		assert_eq!(components.constraints[0].constant, 5.);
+
+		let lp = p.parse_lp("var a; var b; maximize obj: a 4*b a; subject to c1: a b <= 1;", ::builder::Builder::new());
+		assert_eq!(&lp.c[.. 2], &[2., 4.]);
+	}
+
+	#[test]
+	fn parse_british_spelling_test() {
+		let p = Parser::new();
+
+		assert_eq!(p.get_line_type("maximise obj: a + b").unwrap(), LineType::Objective);
+		assert_eq!(p.get_line_type("minimise obj: a + b").unwrap(), LineType::Objective);
+
+		let maximise = p.parse_objective("maximise obj: 3*a + b").unwrap();
+		assert_eq!(maximise, p.parse_objective("maximize obj: 3*a + b").unwrap());
+		assert!(maximise.maximize);
+
+		let minimise = p.parse_objective("minimise obj: 3*a + b").unwrap();
+		assert_eq!(minimise, p.parse_objective("minimize obj: 3*a + b").unwrap());
+		assert!(!minimise.maximize);
+
+		let components = p.parse_components("var a; var b; objective minimise: [1, 2];");
+		assert!(!components.objective.maximize);
+	}
+
+	#[test]
+	fn parse_vector_objective_test() {
+		let p = Parser::new();
+
+		let declarations = "
+			var television;
+			var newspaper;
+			var radio;
+		";
+		let vector = p.parse_components(&format!("{} objective maximize: [100000, 40000, -18000.5];", declarations));
+		let named = p.parse_components(&format!("{} maximize objective: 100000*television + 40000*newspaper + -18000.5*radio;", declarations));
+
+		assert_eq!(vector.objective, named.objective);
+	}
+
+	#[test]
+	fn parse_interval_constraint_test() {
+		let p = Parser::new();
+
+		let interval = p.parse_constraint("subject to c1: a + b in [5, 20]").unwrap();
+		let chained = p.parse_constraint("subject to c1: 5 <= a + b <= 20").unwrap();
+		assert_eq!(interval, chained);
+
+		let spaced = p.parse_constraint("subject to c1: a + b in[ 5 ,20 ]").unwrap();
+		assert_eq!(spaced, chained);
+	}
+
+	#[test]
+	fn parse_parameter_test() {
+		let p = Parser::new();
+
+		let text = "
+			param budget = 182;
+			param rate = 2.5;
+			var a;
+			var b;
+			maximize obj: rate*a + b;
+			subject to c1: 20*a + rate*b <= budget;
+		";
+		let components = p.parse_components(text);
+
+		assert_eq!(components.variables.len(), 2);
+		assert_eq!(components.objective.variables[0], generate_var("a".to_string(), 2.5));
+		assert_eq!(components.constraints[0].constant, 182.);
+		assert_eq!(components.constraints[0].variables[1], generate_var("b".to_string(), 2.5));
+	}
+
+	#[test]
+	fn parse_positional_reference_test() {
+		let p = Parser::new();
+
+		let text = "
+			var a;
+			var x[1..2];
+			maximize obj: 3*$1 + $3;
+			subject to c1: $2 + 2*b <= 4;
+			subject to c2: $4 + a >= 1;
+			var b;
+		";
+		let components = p.parse_components(text);
+
+		assert_eq!(components.objective.variables, vec![
+			generate_var("a".to_string(), 3.),
+			generate_var("x[2]".to_string(), 1.),
+		]);
+		assert_eq!(components.constraints[0].variables, vec![
+			generate_var("x[1]".to_string(), 1.),
+			generate_var("b".to_string(), 2.),
+		]);
+		assert_eq!(components.constraints[1].variables, vec![
+			generate_var("b".to_string(), 1.),
+			generate_var("a".to_string(), 1.),
+		]);
+	}
+
+	#[test]
+	fn parse_positional_reference_in_comment_test() {
+		let components = Parser::new().parse_components("
+			var a;
+			# $2 would be the second variable;
+			maximize obj: $1;
+		");
+		assert_eq!(components.objective.variables, vec![generate_var("a".to_string(), 1.)]);
+	}
+
+	#[test]
+	#[should_panic]
+	fn parse_positional_reference_out_of_range_test() {
+		Parser::new().parse_components("var a; maximize obj: $2;");
+	}
+
+	#[test]
+	fn parse_parameter_arithmetic_test() {
+		let p = Parser::new();
+
+		let text = "
+			param price = 5;
+			param discount = price / 2 - 0.5;
+			param budget = -price + 3 * price * 2;
+			var a;
+			var b;
+			maximize obj: price * 2 * a + discount*b;
+			subject to c1: 2 * 3 * a + b <= budget;
+		";
+		let components = p.parse_components(text);
+
+		assert_eq!(components.objective.variables, vec![
+			generate_var("a".to_string(), 10.),
+			generate_var("b".to_string(), 2.),
+		]);
+		assert_eq!(components.constraints[0].variables[0], generate_var("a".to_string(), 6.));
+		assert_eq!(components.constraints[0].constant, 25.);
+
+		let lp = p.parse_lp(text, ::builder::Builder::new());
+		assert_eq!(&lp.c[.. 2], &[10., 2.]);
+	}
+
+	#[test]
+	fn parse_parameter_scope_test() {
+		let components = Parser::new().parse_components("
+			param cap = -3;
+			var a;
+			var b;
+			# cap bounds the difference;
+			maximize cap: 2 * cap * a + b;
+			subject to cap: cap*a + b >= cap;
+			subject to b_max: b <= -cap;
+		");
+
+		assert_eq!(components.objective.name, "cap");
+		assert_eq!(components.objective.variables, vec![
+			generate_var("a".to_string(), -6.),
+			generate_var("b".to_string(), 1.),
+		]);
+		assert_eq!(components.constraints[0].name, "cap");
+		assert_eq!(components.constraints[0].variables[0], generate_var("a".to_string(), -3.));
+		assert_eq!(components.constraints[0].constant, -3.);
+		assert_eq!(components.constraints[1].constant, 3.);
+	}
+
+	#[test]
+	#[should_panic]
+	fn parse_unknown_parameter_test() {
+		Parser::new().parse_components("param a = b * 2; var x; maximize obj: x;");
+	}
+
+	#[test]
+	fn parse_const_test() {
+		let p = Parser::new();
+
+		let components = p.parse_components("
+			const CAP = 182;
+			param RATE = 2;
+			var a;
+			maximize obj: a;
+			subject to c1: 20*a + RATE*a <= CAP;
+		");
+		assert_eq!(components.constraints[0].constant, 182.);
+		assert_eq!(components.constraints[0].variables[1], generate_var("a".to_string(), 2.));
+	}
+
+	#[test]
+	#[should_panic(expected = "subject to c1: 20*a <= CAP")]
+	fn parse_undefined_const_test() {
+		Parser::new().parse_components("
+			var a;
+			maximize obj: a;
+			subject to c1: 20*a <= CAP;
+		");
 	}
 
+	#[allow(clippy::redundant_field_names)]
 	fn generate_var(name: String, coeff: f64) -> Variable {
 		Variable {
 			name: name,
@@ -0,0 +1,158 @@
+extern crate toml;
+
+use super::*;
+use super::impl_parser::{build_components, DEFAULT_OBJECTIVE_NAME};
+use self::toml::Value;
+use builder::{Builder, Relation};
+
+impl Parser {
+	/// Builds an Lp from a model written in TOML instead of the LP syntax.
+	///
+	/// The model lists its `variables` at the top, followed by an
+	/// `[objective]` table and one `[[constraints]]` table per constraint,
+	/// whose `coefficients` are tables from variable names (quoted when
+	/// they aren't bare keys, like `"x[1]"`) to numbers:
+	///
+	/// ```
+	/// use rulp::parser::Parser;
+	///
+	/// let lp = Parser::lp_from_toml(r#"
+	/// 	variables = ["bagels", "doughnuts"]
+	///
+	/// 	[objective]
+	/// 	name = "profits"          # optional, "objective" by default
+	/// 	sense = "maximize"        # or "minimize"
+	/// 	coefficients = { bagels = 3, doughnuts = 1.25 }
+	///
+	/// 	[[constraints]]
+	/// 	name = "flour"
+	/// 	coefficients = { bagels = 12, doughnuts = 6.5 }
+	/// 	relation = "<="           # or ">=", "=="
+	/// 	rhs = 400
+	/// "#).unwrap();
+	/// assert_eq!(lp.b, vec![400.]);
+	/// ```
+	///
+	/// Invalid TOML, a model missing one of these keys, or a model the
+	/// Builder rejects gives a ParseError with index 0.
+	pub fn lp_from_toml(text: &str) -> Result<Lp, ParseError> {
+		components_from_toml(text)
+			.and_then(|components| build_components(components, &mut Builder::new()))
+			.map_err(|message| ParseError { index: 0, message })
+	}
+}
+
+fn components_from_toml(text: &str) -> Result<Components, String> {
+	let model = text.parse::<Value>().map_err(|err| err.to_string())?;
+
+	let names = model.get("variables").and_then(Value::as_array).ok_or("Missing `variables`")?;
+	let mut variables = vec![];
+	for name in names {
+		match name.as_str() {
+			Some(name) => variables.push(Variable { name: name.to_string(), coefficient: 0. }),
+			None => return Err(format!("Expected a variable name, found {}", name))
+		}
+	}
+
+	let objective = model.get("objective").ok_or("Missing `[objective]`")?;
+	let name = match objective.get("name") {
+		Some(name) => name.as_str().ok_or("Expected a string as the `name` of the objective")?.to_string(),
+		None => DEFAULT_OBJECTIVE_NAME.to_string()
+	};
+	let maximize = match objective.get("sense").and_then(Value::as_str) {
+		Some("maximize") => true,
+		Some("minimize") => false,
+		_ => return Err("Missing or unknown `sense` of the objective".to_string())
+	};
+	let objective = Objective {
+		name,
+		variables: coefficients(objective, "the objective")?,
+		maximize
+	};
+
+	let mut constraints = vec![];
+	let tables = match model.get("constraints") {
+		Some(tables) => tables.as_array().ok_or("Expected `[[constraints]]` tables")?.as_slice(),
+		None => &[]
+	};
+	for (i, table) in tables.iter().enumerate() {
+		let place = format!("constraint {}", i + 1);
+		let missing = |key: &str| format!("Missing `{}` in {}", key, place);
+		let relation = match table.get("relation").and_then(Value::as_str) {
+			Some("<=") => Relation::LessThanOrEqual,
+			Some(">=") => Relation::GreaterThanOrEqual,
+			Some("==") => Relation::Equal,
+			Some(relation) => return Err(format!("Unknown relation \"{}\" in {}", relation, place)),
+			None => return Err(missing("relation"))
+		};
+
+		constraints.push(Constraint {
+			name: table.get("name").and_then(Value::as_str).ok_or_else(|| missing("name"))?.to_string(),
+			variables: coefficients(table, &place)?,
+			constant: table.get("rhs").and_then(number).ok_or_else(|| missing("rhs"))?,
+			relation
+		});
+	}
+
+	Ok(Components {
+		variables,
+		kinds: vec![],
+		constraints,
+		objective
+	})
+}
+
+// The `coefficients` table of the objective or a constraint
+fn coefficients(table: &Value, place: &str) -> Result<Vec<Variable>, String> {
+	let terms = table.get("coefficients").and_then(Value::as_table)
+		.ok_or_else(|| format!("Missing `coefficients` in {}", place))?;
+	terms.iter().map(|(name, coefficient)| match number(coefficient) {
+		Some(coefficient) => Ok(Variable { name: name.clone(), coefficient }),
+		None => Err(format!("Expected a numeric coefficient for \"{}\" in {}", name, place))
+	}).collect()
+}
+
+// TOML tells integers from floats, the model doesn't
+fn number(value: &Value) -> Option<f64> {
+	value.as_float().or_else(|| value.as_integer().map(|value| value as f64))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn toml_syntax_test() {
+		let components = components_from_toml(r#"
+			variables = ["a", "x,1"]
+
+			[objective]
+			name = "say \"hi\" # not a comment"
+			sense = "minimize"
+			coefficients = { a = 3, "x,1" = -2.5 } # a comment
+
+			[[constraints]]
+			name = "cap"
+			coefficients = { "x,1" = 1_000 }
+			relation = ">="
+			rhs = 2
+		"#).unwrap();
+
+		assert_eq!(components.objective.name, "say \"hi\" # not a comment");
+		assert_eq!(components.objective.variables, vec![
+			Variable { name: "a".to_string(), coefficient: 3. },
+			Variable { name: "x,1".to_string(), coefficient: -2.5 },
+		]);
+		assert_eq!(components.constraints[0].variables, vec![Variable { name: "x,1".to_string(), coefficient: 1000. }]);
+		assert_eq!(components.constraints[0].constant, 2.);
+	}
+
+	#[test]
+	fn toml_error_test() {
+		let err = Parser::lp_from_toml("variables = [\"a\"]\n[objective]\nsense = \"upwards\"\n").unwrap_err();
+		assert_eq!(err.message, "Missing or unknown `sense` of the objective");
+
+		let err = Parser::lp_from_toml("variables = [\"a\"]\n[objective]\nsense = \"maximize\"\ncoefficients = { a = 1 }\n[[constraints]]\nname = \"c1\"\n").unwrap_err();
+		assert_eq!(err.message, "Missing `relation` in constraint 1");
+	}
+}
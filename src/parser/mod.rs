@@ -7,19 +7,51 @@ extern crate regex;
 use self::regex::Regex;
 
 use lp::Lp;
+use std::collections::HashMap;
+use std::fmt;
 use std::fs::File;
-use builder::{Variable, Constraint, Objective, BuilderBase};
+use builder::{Variable, VariableKind, Constraint, Objective, BuilderBase};
 
 
 mod impl_parser;
+mod impl_network;
+mod impl_mps;
+#[cfg(feature = "toml")]
+mod impl_toml;
 
 #[derive(Debug, PartialEq, Clone)]
 pub struct Components {
 	pub variables: Vec<Variable>,
+	/// Variables declared `integer` or `binary`, in declaration order
+	pub kinds: Vec<(String, VariableKind)>,
 	pub constraints: Vec<Constraint>,
 	pub objective: Objective
 }
 
+/// A model from `Parser::parse_model`, which remembers its statements and
+/// declared values for editing it with `Parser::reparse_statement`.
+#[derive(Debug, PartialEq, Clone)]
+pub struct ParsedModel {
+	pub components: Components,
+	/// Where the component of each statement went, in statement order
+	/// (comments, `param` declarations and objective definitions aside)
+	pub statements: Vec<Statement>,
+	parameters: HashMap<String, f64>,
+	vectors: HashMap<String, Vec<f64>>
+}
+
+/// The component parsed from one statement, as an index into `Components`.
+#[derive(Debug, PartialEq, Clone)]
+pub enum Statement {
+	/// A declaration of `variables[start .. end]`
+	Variables(usize, usize),
+	Constraint(usize),
+	/// A chain like `a <= b <= c` between expressions with variables,
+	/// split into `constraints[start .. end]`
+	Constraints(usize, usize),
+	Objective
+}
+
 pub trait ParserBase {
 	fn parse_components_from_text(text: &str) -> Components;
 	fn parse_components_from_file(file: &mut File) -> Components;
@@ -28,11 +60,84 @@ pub trait ParserBase {
 	fn lp_from_file<B: BuilderBase>(file: &mut File, builder: B) -> Lp;
 }
 
+/// Marks the boundary between problems in `Parser::lp_list_from_text`.
+pub const PROBLEM_SEPARATOR: &str = "%%%";
+
+/// A problem of `Parser::lp_list_from_text` that failed to parse or build.
+#[derive(Debug, PartialEq, Clone)]
+pub struct ParseError {
+	/// Position of the problem in the text, counting from 0
+	pub index: usize,
+	pub message: String
+}
+
+impl fmt::Display for ParseError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "Problem {}: {}", self.index, self.message)
+	}
+}
+
+/// Opt-in extensions to the default LP syntax.
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct ParserOptions {
+	/// Also accept terms with the coefficient after the variable, e.g. `a*3.`
+	pub coefficient_after_variable: bool,
+	/// Accept `k`, `M` and `G` suffixes on numbers, e.g. `100k` or `1.5M`
+	pub si_suffixes: bool,
+	/// Ignore underscores within numbers, e.g. `100_000` or `1_000.5`
+	pub digit_separators: bool,
+	/// Read terms separated only by whitespace as a sum, e.g. `3*a 2*b c`
+	pub whitespace_addition: bool,
+	/// Declare variables on their first use in an objective or constraint
+	/// instead of requiring a `var` declaration
+	pub auto_declare: bool,
+	/// Reject problems declaring more than this many variables, checked
+	/// before expanding a range like `var x[1..1000000000]`
+	pub max_variables: Option<usize>,
+}
+
 #[derive(Debug)]
 pub struct Parser {
+	options: ParserOptions,
 	variable_declaration_regex: Regex,
+	variable_range_regex: Regex,
+	variable_kind_regex: Regex,
+	parameter_regex: Regex,
+	parameter_reference_regex: Regex,
+	expression_name_regex: Regex,
+	coefficient_vector_regex: Regex,
+	dot_product_regex: Regex,
+	positional_reference_regex: Regex,
+	expression_token_regex: Regex,
+	group_regex: Regex,
+	constant_product_regex: Regex,
+	constant_term_regex: Regex,
+	vector_objective_regex: Regex,
+	si_suffix_regex: Regex,
+	digit_separator_regex: Regex,
 	variable_regex: Regex,
-	constraint_regex: Regex,
-	equation_component_regex: Regex,
+	constraint_header_regex: Regex,
 	objective_regex: Regex,
+	definition_regex: Regex,
+	constraint_block_regex: Regex,
+	constraint_keyword_regex: Regex,
+	interval_constraint_regex: Regex,
+	reversed_variable_regex: Regex,
+}
+
+/// Parser for network flow shorthand, expanding arcs and node supplies into
+/// a min-cost flow model.
+///
+/// Statements are terminated by a semicolon, like in the LP syntax:
+///
+/// * `arc a_b from A to B cap 10 cost 3;` declares the flow variable
+///   `a_b` from node `A` to node `B`, where `cap` (an upper bound on the
+///   flow) and `cost` (per unit of flow) are optional;
+/// * `node A supply 5;` or `node B demand 5;` sets what a node sends into
+///   or takes out of the network, zero for nodes left out;
+/// * `# ...;` is a comment.
+#[derive(Debug)]
+pub struct NetworkParser {
+	arc_regex: Regex,
+	node_regex: Regex,
 }
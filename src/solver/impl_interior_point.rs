@@ -0,0 +1,262 @@
+use super::*;
+use lp::{Lp, Optimization};
+use rulinalg::matrix::BaseMatrix;
+use rulinalg::vector::Vector;
+
+const MAX_ITERATIONS: usize = 500;
+const TOLERANCE: f64 = 1e-9;
+const DIVERGENCE: f64 = 1e12;		// Iterates growing past this are taken as a sign of infeasibility/unboundedness
+const CENTERING: f64 = 0.1;			// Fraction of the current duality measure targeted by each step
+const STEP_SCALE: f64 = 0.99;		// Keeps iterates strictly inside the positive orthant
+const REGULARIZATION: f64 = 1e-12;	// Guards the normal equations against round-off, not dependent rows
+
+impl SolverBase for InteriorPointSolver {
+	/// Constructor for InteriorPointSolver struct.
+	///
+	/// Requires an input Lp struct.
+	fn new(lp: Lp) -> Self {
+		InteriorPointSolver {
+			lp
+		}
+	}
+
+	/// Solves the InteriorPointSolver.
+	///
+	/// Runs a basic (infeasible start) primal-dual path following method on
+	/// `min c'x s.t. Ax = b, x >= 0`, taking Newton steps towards the central
+	/// path until the primal and dual residuals and the duality gap are all
+	/// within tolerance.
+	///
+	/// Rows of `A` that are combinations of others are dropped first, as
+	/// they would leave the normal equations singular, and get a zero dual.
+	/// If such a row contradicts the others, the Lp is reported infeasible.
+	/// Otherwise infeasibility and unboundedness are not certified: they are
+	/// reported when the dual or primal iterates diverge. Running out of
+	/// iterations or failing to solve the normal equations gives a
+	/// `NumericalError`.
+	///
+	/// Returns a Solution struct.
+	fn solve(&self) -> Solution {
+		self.solve_with_callback(&mut None)
+	}
+}
+
+impl InteriorPointSolver {
+	pub(crate) fn solve_with_callback(&self, on_iteration: &mut Option<IterationCallback>) -> Solution {
+		let m = self.lp.A.rows();
+		let rows = match independent_rows(&self.lp.A, &self.lp.b) {
+			Some(rows) => rows,
+			None => return Solution {
+				lp: self.lp.clone(),
+				values: None,
+				objective: None,
+				duals: None,
+				farkas: None,
+				iterations: 0,
+				residuals: None,
+				status: Status::Infeasible,
+				pivots: vec![],
+				basis: None
+			}
+		};
+		if rows.len() == m {
+			return self.follow_central_path(on_iteration);
+		}
+
+		let mut reduced = self.lp.clone();
+		reduced.A = self.lp.A.select_rows(&rows);
+		reduced.b = rows.iter().map(|&row| self.lp.b[row]).collect();
+		let mut solution = InteriorPointSolver::new(reduced).follow_central_path(on_iteration);
+		solution.lp = self.lp.clone();
+		solution.duals = solution.duals.map(|duals| {
+			let mut all = vec![0.; m];
+			for (&row, dual) in rows.iter().zip(duals) {
+				all[row] = dual;
+			}
+			all
+		});
+		solution
+	}
+
+	fn follow_central_path(&self, on_iteration: &mut Option<IterationCallback>) -> Solution {
+		let m = self.lp.A.rows();
+		let n = self.lp.A.cols();
+
+		let sign = match self.lp.optimization {
+			Optimization::Max => -1.,
+			Optimization::Min => 1.,
+		};
+		let c: Vec<f64> = self.lp.c.iter().map(|coeff| sign * coeff).collect();
+		let b_norm = norm(&self.lp.b);
+		let c_norm = norm(&c);
+
+		let mut x = vec![1.; n];
+		let mut s = vec![1.; n];
+		let mut y = vec![0.; m];
+
+		let mut iterations = 0;
+		let mut residuals = Residuals { primal: f64::INFINITY, dual: f64::INFINITY };
+		let mut status = Status::NumericalError(format!("No convergence within {} iterations", MAX_ITERATIONS));
+
+		while iterations < MAX_ITERATIONS {
+			let r_p: Vec<f64> = (0 .. m).map(|i| self.lp.b[i] - self.row_dot(i, &x)).collect();
+			let r_d: Vec<f64> = (0 .. n).map(|j| c[j] - self.col_dot(j, &y) - s[j]).collect();
+			let mu = dot(&x, &s) / n as f64;
+
+			residuals = Residuals { primal: norm(&r_p), dual: norm(&r_d) };
+			if residuals.primal <= TOLERANCE * (1. + b_norm)
+				&& residuals.dual <= TOLERANCE * (1. + c_norm)
+				&& mu <= TOLERANCE * (1. + dot(&c, &x).abs()) {
+				status = Status::Optimal;
+				break;
+			}
+
+			if norm(&x) > DIVERGENCE {
+				status = Status::Unbounded;
+				break;
+			} else if norm(&y) > DIVERGENCE {
+				status = Status::Infeasible;
+				break;
+			}
+
+			// Newton step for A dx = r_p, A^T dy + ds = r_d, S dx + X ds = r_c,
+			// reduced to the normal equations (A D A^T) dy = rhs with D = X S^-1
+			let d: Vec<f64> = (0 .. n).map(|j| x[j] / s[j]).collect();
+			let r_c: Vec<f64> = (0 .. n).map(|j| CENTERING * mu - x[j] * s[j]).collect();
+			let w: Vec<f64> = (0 .. n).map(|j| d[j] * r_d[j] - r_c[j] / s[j]).collect();
+			let rhs: Vec<f64> = (0 .. m).map(|i| r_p[i] + self.row_dot(i, &w)).collect();
+
+			let dy = match self.solve_normal_equations(&d, rhs) {
+				Some(dy) => dy,
+				None => {
+					status = Status::NumericalError("Singular normal equations".to_string());
+					break;
+				}
+			};
+			let ds: Vec<f64> = (0 .. n).map(|j| r_d[j] - self.col_dot(j, &dy)).collect();
+			let dx: Vec<f64> = (0 .. n).map(|j| r_c[j] / s[j] - d[j] * r_d[j] + d[j] * self.col_dot(j, &dy)).collect();
+
+			let alpha_p = max_step(&x, &dx);
+			let alpha_d = max_step(&s, &ds);
+			for j in 0 .. n {
+				x[j] += alpha_p * dx[j];
+				s[j] += alpha_d * ds[j];
+			}
+			for i in 0 .. m {
+				y[i] += alpha_d * dy[i];
+			}
+
+			iterations += 1;
+
+			if let Some(ref mut callback) = *on_iteration {
+				callback(iterations, sign * dot(&c, &x));
+			}
+		}
+
+		let optimal = status == Status::Optimal;
+		let reported = self.lp.reported_sign();
+		Solution {
+			lp: self.lp.clone(),
+			objective: if optimal { Some(reported * dot(&self.lp.c, &x)) } else { None },
+			values: if optimal { Some(x) } else { None },
+			duals: if optimal { Some(y.iter().map(|v| reported * sign * v).collect()) } else { None },
+			farkas: None,
+			iterations,
+			residuals: Some(residuals),
+			status,
+			pivots: vec![],
+			basis: None
+		}
+	}
+
+	fn row_dot(&self, row: usize, v: &[f64]) -> f64 {
+		(0 .. self.lp.A.cols()).map(|col| self.lp.A[[row, col]] * v[col]).sum()
+	}
+
+	fn col_dot(&self, col: usize, v: &[f64]) -> f64 {
+		(0 .. self.lp.A.rows()).map(|row| self.lp.A[[row, col]] * v[row]).sum()
+	}
+
+	fn solve_normal_equations(&self, d: &[f64], rhs: Vec<f64>) -> Option<Vec<f64>> {
+		let m = self.lp.A.rows();
+		if m == 0 {
+			return Some(vec![]);
+		}
+
+		let mut normal = vec![0.; m * m];
+		for i in 0 .. m {
+			for k in i .. m {
+				let entry: f64 = (0 .. self.lp.A.cols())
+					.map(|j| self.lp.A[[i, j]] * d[j] * self.lp.A[[k, j]])
+					.sum();
+				normal[i * m + k] = entry;
+				normal[k * m + i] = entry;
+			}
+			normal[i * m + i] += REGULARIZATION;
+		}
+
+		Matrix::new(m, m, normal).solve(Vector::new(rhs)).ok().map(|dy| dy.into_vec())
+	}
+}
+
+// Indices of a maximal set of linearly independent rows of `A`, found by
+// Gaussian elimination on `[A | b]` in row order. None if a dependent row
+// disagrees with the others on its right hand side, so `Ax = b` has no
+// solution
+fn independent_rows(A: &Matrix<f64>, b: &[f64]) -> Option<Vec<usize>> {
+	let n = A.cols();
+	let mut reduced: Vec<(usize, Vec<f64>)> = vec![];
+	let mut rows = vec![];
+	for row in 0 .. A.rows() {
+		let mut augmented: Vec<f64> = (0 .. n).map(|col| A[[row, col]]).chain(Some(b[row])).collect();
+		let scale = TOLERANCE * (1. + augmented.iter().fold(0., |max: f64, value| max.max(value.abs())));
+		for &(pivot, ref basis) in &reduced {
+			let factor = augmented[pivot] / basis[pivot];
+			for (entry, value) in augmented.iter_mut().zip(basis.iter()) {
+				*entry -= factor * value;
+			}
+		}
+
+		let pivot = (0 .. n).max_by(|&i, &j| augmented[i].abs().partial_cmp(&augmented[j].abs()).unwrap());
+		match pivot {
+			Some(pivot) if augmented[pivot].abs() > scale => {
+				reduced.push((pivot, augmented));
+				rows.push(row);
+			},
+			_ if augmented[n].abs() > scale => return None,
+			_ => {}
+		}
+	}
+	Some(rows)
+}
+
+// Largest step in [0, 1] keeping v + step * dv strictly positive
+fn max_step(v: &[f64], dv: &[f64]) -> f64 {
+	let mut step: f64 = 1.;
+	for j in 0 .. v.len() {
+		if dv[j] < 0. {
+			step = step.min(-STEP_SCALE * v[j] / dv[j]);
+		}
+	}
+	step
+}
+
+fn dot(a: &[f64], b: &[f64]) -> f64 {
+	a.iter().zip(b.iter()).map(|(x, y)| x * y).sum()
+}
+
+fn norm(v: &[f64]) -> f64 {
+	dot(v, v).sqrt()
+}
+
+#[cfg(test)]
+mod interior_point_tests {
+	use super::*;
+	use assert_approx_eq::*;
+
+	#[test]
+	fn max_step_test() {
+		assert_eq!(max_step(&[1., 2.], &[1., 1.]), 1.);
+		assert_approx_eq!(max_step(&[1., 2.], &[-2., 1.]), 0.495);
+	}
+}
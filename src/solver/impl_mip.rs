@@ -0,0 +1,575 @@
+use super::*;
+use lp::{Lp, Optimization, VariableKind};
+use rulinalg::matrix::BaseMatrix;
+use std::collections::HashMap;
+use std::mem;
+use std::time::Instant;
+
+const INTEGRALITY: f64 = 1e-6;		// Values this close to an integer count as integral
+const GAP_EPSILON: f64 = 1e-10;		// Keeps the relative gap finite for a zero incumbent
+
+impl Solver {
+	/// Solves the Lp with branch and bound, requiring every integer and
+	/// binary variable to take an integral value.
+	///
+	/// The relaxation of every subproblem is solved with the method
+	/// selected in the options, exploring the open subproblem with the
+	/// best bound first. The search stops as soon as `relative_gap()` is
+	/// at most `mip_gap`, returning the incumbent found by then.
+	///
+	/// # Examples
+	/// ```
+	/// use rulp::builder::{Builder, BuilderBase};
+	/// use rulp::parser::{Parser, ParserBase};
+	/// use rulp::solver::{Solver, Status};
+	///
+	/// let lp = Parser::lp_from_text("
+	/// 	var a integer;
+	/// 	var b integer;
+	/// 	maximize total: 5*a + 4*b;
+	/// 	subject to first: 6*a + 4*b <= 24;
+	/// 	subject to second: a + 2*b <= 6;
+	/// ", Builder::new());
+	///
+	/// let mut solver = Solver::new();
+	/// let solution = solver.solve_mip(&lp);
+	///
+	/// assert_eq!(solution.status, Status::Optimal);
+	/// assert!((solution.objective.unwrap() - 20.).abs() < 1e-6);
+	/// assert_eq!(solver.relative_gap(), Some(0.));
+	/// ```
+	pub fn solve_mip(&mut self, lp: &Lp) -> Solution {
+		self.incumbent = None;
+		self.frontier = vec![Node { bounds: vec![], score: f64::INFINITY }];
+		self.branch_and_bound(lp)
+	}
+
+	/// Same as `solve_mip`, but starts from a known integer feasible
+	/// solution, giving a value for every declared variable by name.
+	///
+	/// Subproblems that can't beat the given incumbent are pruned right
+	/// away, so a good incumbent saves exploring nodes. It is returned
+	/// unless the search finds a better one. Panics if the incumbent
+	/// names an unknown variable, leaves one out, gives an integer or
+	/// binary variable a fractional value or violates a constraint.
+	pub fn solve_mip_with_incumbent(&mut self, lp: &Lp, incumbent: HashMap<String, f64>) -> Solution {
+		let num_structural = lp.vars.len() - lp.num_artificial_vars;
+		let mut bounds = vec![];
+		for (name, &value) in &incumbent {
+			let col = match lp.vars[.. num_structural].iter().position(|var| var == name) {
+				Some(col) => col,
+				None => panic!("Unknown variable \"{}\" in incumbent", name)
+			};
+			if lp.kinds[col] != VariableKind::Continuous && (value - value.round()).abs() > INTEGRALITY {
+				panic!("Incumbent gives {} variable \"{}\" the fractional value {}", format!("{:?}", lp.kinds[col]).to_lowercase(), name, value);
+			}
+			bounds.push((col, BranchBound::Upper(value)));
+			bounds.push((col, BranchBound::Lower(value)));
+		}
+		if incumbent.len() != num_structural {
+			panic!("Incumbent gives values for {} of the {} variables", incumbent.len(), num_structural);
+		}
+
+		let fixed = self.solve_relaxation(&with_bounds(lp, &bounds));		// Finds the slack values, if there are any that fit
+		if fixed.status != Status::Optimal {
+			panic!("Incumbent violates the constraints");
+		}
+		let mut values = fixed.values.unwrap();
+		values.truncate(lp.A.cols());
+
+		self.incumbent = Some(Incumbent { values, score: score_sign(lp) * fixed.objective.unwrap() });
+		self.frontier = vec![Node { bounds: vec![], score: f64::INFINITY }];
+		self.branch_and_bound(lp)
+	}
+
+	/// Finds every optimal solution of a MIP: one per distinct assignment
+	/// of the integer and binary variables reaching the optimal objective,
+	/// in the order they're found.
+	///
+	/// After `solve_mip` finds the optimum, the search goes on through
+	/// every subproblem that can still reach it, splitting integral
+	/// relaxations into `x <= v - 1`, `x >= v + 1` and `x = v` on a variable
+	/// not fixed yet. The number of optima can grow exponentially, so the
+	/// search stops after `optima_limit` of them if that is set. Returns an
+	/// empty list if the MIP has no optimal solution.
+	pub fn solve_mip_all_optima(&mut self, lp: &Lp) -> Vec<Solution> {
+		let best = self.solve_mip(lp);
+		if best.status != Status::Optimal {
+			return vec![];
+		}
+
+		let sign = score_sign(lp);
+		let target = sign * best.objective.unwrap();
+		let tolerance = INTEGRALITY * (1. + target.abs());
+		let integers: Vec<usize> = (0 .. lp.kinds.len()).filter(|&col| lp.kinds[col] != VariableKind::Continuous).collect();
+		let mut optima = vec![];
+		let mut open = vec![vec![]];											// Depth first, so optima are completed early
+
+		while let Some(bounds) = open.pop() {
+			if self.options.optima_limit.is_some_and(|limit| optima.len() >= limit) {
+				break;
+			}
+
+			let relaxation = self.solve_relaxation(&with_bounds(lp, &bounds));
+			if relaxation.status != Status::Optimal || sign * relaxation.objective.unwrap() < target - tolerance {
+				continue;
+			}
+			let mut values = relaxation.values.clone().unwrap();
+			values.truncate(lp.A.cols());
+
+			let (col, children) = match fractional_variable(lp, &values) {
+				Some(col) => (col, vec![vec![BranchBound::Upper(values[col].floor())], vec![BranchBound::Lower(values[col].ceil())]]),
+				None => {
+					let fixed = |col: usize, value: f64| {
+						bounds.contains(&(col, BranchBound::Upper(value))) && bounds.contains(&(col, BranchBound::Lower(value)))
+					};
+					match integers.iter().find(|&&col| !fixed(col, values[col].round())) {
+						Some(&col) => {
+							let value = values[col].round();
+							(col, vec![
+								vec![BranchBound::Upper(value - 1.)],
+								vec![BranchBound::Lower(value + 1.)],
+								vec![BranchBound::Upper(value), BranchBound::Lower(value)]
+							])
+						},
+						None => {
+							optima.push(Solution {
+								lp: lp.clone(),
+								values: Some(values),
+								objective: relaxation.objective,
+								duals: None,
+								farkas: None,
+								iterations: relaxation.iterations,
+								residuals: None,
+								status: Status::Optimal,
+								pivots: relaxation.pivots.clone(),
+								basis: None
+							});
+							continue;
+						}
+					}
+				}
+			};
+
+			for child in children {
+				let mut child_bounds = bounds.clone();
+				child_bounds.extend(child.into_iter().map(|bound| (col, bound)));
+				open.push(child_bounds);
+			}
+		}
+
+		optima
+	}
+
+	/// Captures the open subproblems and the incumbent of the last
+	/// `solve_mip` or `resume`, so the search can be continued later.
+	pub fn save_state(&self) -> SolverState {
+		SolverState {
+			frontier: self.frontier.clone(),
+			incumbent: self.incumbent.clone()
+		}
+	}
+
+	/// Continues a branch and bound search on the Lp from a saved state,
+	/// using this solver's options.
+	///
+	/// The state must come from a search on the same Lp: an error of kind
+	/// `InvalidData` is returned if it bounds a column the Lp doesn't have
+	/// or that isn't integer or binary, or if its incumbent has a value
+	/// count other than the Lp's column count.
+	///
+	/// # Examples
+	/// ```
+	/// use rulp::builder::{Builder, BuilderBase};
+	/// use rulp::parser::{Parser, ParserBase};
+	/// use rulp::solver::{Solver, SolverOptions, SolverState};
+	///
+	/// let lp = Parser::lp_from_text("
+	/// 	var a integer;
+	/// 	var b integer;
+	/// 	maximize total: 5*a + 4*b;
+	/// 	subject to first: 6*a + 4*b <= 24;
+	/// 	subject to second: a + 2*b <= 6;
+	/// ", Builder::new());
+	///
+	/// let mut partial = Solver::with_options(SolverOptions { mip_gap: 1., ..SolverOptions::default() });
+	/// partial.solve_mip(&lp);
+	/// let mut checkpoint = vec![];
+	/// partial.save_state().write(&mut checkpoint).unwrap();
+	///
+	/// let state = SolverState::read(&mut &checkpoint[..]).unwrap();
+	/// let solution = Solver::new().resume(&lp, state).unwrap();
+	/// assert!((solution.objective.unwrap() - 20.).abs() < 1e-6);
+	/// ```
+	pub fn resume(&mut self, lp: &Lp, state: SolverState) -> io::Result<Solution> {
+		state.check_columns(lp)?;
+		self.frontier = state.frontier;
+		self.incumbent = state.incumbent;
+		Ok(self.branch_and_bound(lp))
+	}
+
+	fn branch_and_bound(&mut self, lp: &Lp) -> Solution {
+		let sign = score_sign(lp);
+		let mut iterations = 0;
+		let mut pivots = vec![];
+		self.nodes = 0;
+
+		while !self.gap_reached() && !self.deadline_passed() {
+			let node = match self.next_node() {
+				Some(node) => node,
+				None => break
+			};
+			if !self.may_improve(node.score) {
+				continue;
+			}
+
+			let relaxation = self.solve_relaxation(&with_bounds(lp, &node.bounds));
+			iterations += relaxation.iterations;
+			pivots.extend(relaxation.pivots.iter().cloned());
+			self.nodes += 1;
+
+			match relaxation.status {
+				Status::Optimal => {},
+				Status::Unbounded if node.bounds.is_empty() => {
+					self.frontier.clear();
+					return Solution {
+						lp: lp.clone(),
+						values: None,
+						objective: None,
+						duals: None,
+						farkas: None,
+						iterations,
+						residuals: None,
+						status: Status::Unbounded,
+						pivots,
+						basis: None
+					};
+				},
+				_ => continue
+			}
+
+			let score = sign * relaxation.objective.unwrap();
+			if !self.may_improve(score) {
+				continue;
+			}
+
+			let mut values = relaxation.values.unwrap();
+			values.truncate(lp.A.cols());
+
+			match fractional_variable(lp, &values) {
+				Some(col) => {
+					let value = values[col];
+					for bound in [BranchBound::Upper(value.floor()), BranchBound::Lower(value.ceil())] {
+						let mut bounds = node.bounds.clone();
+						bounds.push((col, bound));
+						self.frontier.push(Node { bounds, score });
+					}
+				},
+				None => {
+					self.incumbent = Some(Incumbent { values, score });
+				}
+			}
+		}
+
+		// Open subproblems left by the deadline may still hold a better solution
+		let cut_off = !self.gap_reached() && self.frontier.iter().any(|node| self.may_improve(node.score));
+		let (values, objective, status) = match self.incumbent {
+			Some(ref incumbent) => (Some(incumbent.values.clone()), Some(sign * incumbent.score), if cut_off { Status::Interrupted } else { Status::Optimal }),
+			None if cut_off => (None, None, Status::Interrupted),
+			None => (None, None, Status::Infeasible)
+		};
+
+		Solution {
+			lp: lp.clone(),
+			values,
+			objective,
+			duals: None,
+			farkas: None,
+			iterations,
+			residuals: None,
+			status,
+			pivots,
+			basis: None
+		}
+	}
+
+	/// Runs `solve_mip` until it finishes or `deadline` passes, returning
+	/// the solution found by then along with the best objective any
+	/// solution could still reach, which equals the solution's objective
+	/// once it is proven optimal.
+	///
+	/// The deadline is checked between subproblems, so it may be overrun
+	/// by the time one relaxation takes; an Lp without integer variables
+	/// is a single relaxation and always solved in full. When cut off
+	/// before proving optimality, the Solution has status `Interrupted`
+	/// and holds the best integer solution found, if there is one yet. The
+	/// bound is infinite if not even the root relaxation was solved. The
+	/// search can still be continued from `save_state`.
+	pub fn solve_anytime(&mut self, lp: &Lp, deadline: Instant) -> (Solution, f64) {
+		self.deadline = Some(deadline);
+		let solution = self.solve_mip(lp);
+		self.deadline = None;
+
+		let bound = match solution.status {
+			Status::Unbounded => f64::INFINITY,
+			_ => self.frontier.iter()
+				.fold(self.incumbent.as_ref().map_or(-f64::INFINITY, |incumbent| incumbent.score), |bound, node| bound.max(node.score))
+		};
+		(solution, score_sign(lp) * bound)
+	}
+
+	/// Returns the relative gap `|bound - incumbent| / |incumbent|` of the
+	/// last `solve_mip`, where bound is the best objective any open
+	/// subproblem could still reach.
+	///
+	/// The gap is zero once the incumbent is proven optimal. Returns None
+	/// if no integer feasible solution has been found.
+	pub fn relative_gap(&self) -> Option<f64> {
+		self.incumbent.as_ref().map(|incumbent| {
+			let bound = self.frontier.iter().fold(incumbent.score, |bound, node| bound.max(node.score));
+			(bound - incumbent.score).abs() / (GAP_EPSILON + incumbent.score.abs())
+		})
+	}
+
+	/// Returns the number of subproblem relaxations solved by the last
+	/// `solve_mip` or `resume`.
+	pub fn node_count(&self) -> usize {
+		self.nodes
+	}
+
+	// Solves a subproblem to optimality, as its objective is used as a
+	// bound. Its Lp is gone after the search, so `resolve_with_objective`
+	// has nothing to start from
+	fn solve_relaxation(&mut self, lp: &Lp) -> Solution {
+		let tolerance = mem::replace(&mut self.options.objective_improvement_tolerance, 0.);
+		let singleton_check = mem::replace(&mut self.options.singleton_check, false);
+		let solution = self.solve(lp);
+		self.options.objective_improvement_tolerance = tolerance;
+		self.options.singleton_check = singleton_check;
+		self.warm_lp = None;
+		solution
+	}
+
+	fn deadline_passed(&self) -> bool {
+		self.deadline.is_some_and(|deadline| Instant::now() >= deadline)
+	}
+
+	fn gap_reached(&self) -> bool {
+		self.relative_gap().is_some_and(|gap| gap <= self.options.mip_gap)
+	}
+
+	// Whether a subproblem bounded by score could beat the incumbent
+	fn may_improve(&self, score: f64) -> bool {
+		self.incumbent.as_ref().is_none_or(|incumbent| score > incumbent.score + INTEGRALITY)
+	}
+
+	// Takes the open subproblem with the best bound, preferring the most
+	// recently added one on ties so the search dives towards incumbents
+	fn next_node(&mut self) -> Option<Node> {
+		let mut best = None;
+		for (i, node) in self.frontier.iter().enumerate() {
+			if best.is_none_or(|b: usize| node.score >= self.frontier[b].score) {
+				best = Some(i);
+			}
+		}
+
+		best.map(|i| self.frontier.remove(i))
+	}
+}
+
+impl SolverState {
+	/// Writes the state as text, e.g. to checkpoint a long solve to a file.
+	pub fn write<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+		if let Some(ref incumbent) = self.incumbent {
+			write!(writer, "incumbent {}", incumbent.score)?;
+			for value in &incumbent.values {
+				write!(writer, " {}", value)?;
+			}
+			writeln!(writer)?;
+		}
+
+		for node in &self.frontier {
+			write!(writer, "node {}", node.score)?;
+			for &(col, ref bound) in &node.bounds {
+				match *bound {
+					BranchBound::Upper(value) => write!(writer, " {}<={}", col, value)?,
+					BranchBound::Lower(value) => write!(writer, " {}>={}", col, value)?,
+				}
+			}
+			writeln!(writer)?;
+		}
+
+		Ok(())
+	}
+
+	/// Reads back a state written by `write`.
+	pub fn read<R: BufRead>(reader: &mut R) -> io::Result<SolverState> {
+		let mut state = SolverState { frontier: vec![], incumbent: None };
+
+		for line in reader.lines() {
+			let line = line?;
+			let mut fields = line.split_whitespace();
+			let kind = match fields.next() {
+				Some(kind) => kind,
+				None => continue
+			};
+			let score = parse_field(fields.next())?;
+
+			match kind {
+				"incumbent" => {
+					let values = fields.map(|field| parse_field(Some(field))).collect::<io::Result<Vec<f64>>>()?;
+					state.incumbent = Some(Incumbent { values, score });
+				},
+				"node" => {
+					let bounds = fields.map(parse_bound).collect::<io::Result<Vec<(usize, BranchBound)>>>()?;
+					state.frontier.push(Node { bounds, score });
+				},
+				_ => return Err(invalid_state(&line))
+			}
+		}
+
+		Ok(state)
+	}
+
+	// Every bound must be on an integer or binary column of the Lp, and the
+	// incumbent must have a value per column
+	fn check_columns(&self, lp: &Lp) -> io::Result<()> {
+		let cols = lp.A.cols();
+		if let Some(ref incumbent) = self.incumbent {
+			if incumbent.values.len() != cols {
+				return Err(io::Error::new(io::ErrorKind::InvalidData,
+					format!("Solver state has {} incumbent values for an Lp with {} columns", incumbent.values.len(), cols)));
+			}
+		}
+
+		for &(col, _) in self.frontier.iter().flat_map(|node| node.bounds.iter()) {
+			if col >= cols {
+				return Err(io::Error::new(io::ErrorKind::InvalidData,
+					format!("Solver state bounds column {} of an Lp with {} columns", col, cols)));
+			}
+			if lp.kinds[col] == VariableKind::Continuous {
+				return Err(io::Error::new(io::ErrorKind::InvalidData,
+					format!("Solver state bounds column {} (\"{}\"), which is continuous", col, lp.vars[col])));
+			}
+		}
+
+		Ok(())
+	}
+}
+
+fn parse_field<T: ::std::str::FromStr>(field: Option<&str>) -> io::Result<T> {
+	field.and_then(|field| field.parse().ok()).ok_or_else(|| invalid_state(field.unwrap_or("")))
+}
+
+// Reads `col<=value` or `col>=value`
+fn parse_bound(field: &str) -> io::Result<(usize, BranchBound)> {
+	let (split, upper) = match (field.find("<="), field.find(">=")) {
+		(Some(i), _) => (i, true),
+		(_, Some(i)) => (i, false),
+		_ => return Err(invalid_state(field))
+	};
+
+	let col = parse_field(Some(&field[.. split]))?;
+	let value = parse_field(Some(&field[split + 2 ..]))?;
+	Ok((col, if upper { BranchBound::Upper(value) } else { BranchBound::Lower(value) }))
+}
+
+fn invalid_state(data: &str) -> io::Error {
+	io::Error::new(io::ErrorKind::InvalidData, format!("Invalid solver state \"{}\"", data))
+}
+
+// First integer or binary variable without an integral value
+fn fractional_variable(lp: &Lp, values: &[f64]) -> Option<usize> {
+	(0 .. values.len()).find(|&col| {
+		lp.kinds[col] != VariableKind::Continuous && (values[col] - values[col].round()).abs() > INTEGRALITY
+	})
+}
+
+// Relaxation objectives are reported in the original sense of a negated
+// Lp, scores in maximization form of the sense actually being optimized
+fn score_sign(lp: &Lp) -> f64 {
+	match lp.optimization {
+		Optimization::Max => lp.reported_sign(),
+		Optimization::Min => -lp.reported_sign(),
+	}
+}
+
+// Copies the Lp with an extra row for every bound: `x + slack = upper` or
+// `x - excess = lower`
+fn with_bounds(lp: &Lp, bounds: &[(usize, BranchBound)]) -> Lp {
+	let rows = lp.A.rows();
+	let cols = lp.A.cols();
+	let new_rows = rows + bounds.len();
+	let new_cols = cols + bounds.len();
+
+	let mut A = vec![0.; new_rows * new_cols];
+	for row in 0 .. rows {
+		for col in 0 .. cols {
+			A[row * new_cols + col] = lp.A[[row, col]];
+		}
+	}
+
+	let mut b = lp.b.clone();
+	let mut c = lp.c.clone();
+	let mut vars = lp.vars.clone();
+	let mut kinds = lp.kinds.clone();
+	let mut constraint_names = lp.constraint_names.clone();
+
+	for (i, &(col, ref bound)) in bounds.iter().enumerate() {
+		let row = rows + i;
+		A[row * new_cols + col] = 1.;
+		match *bound {
+			BranchBound::Upper(value) => {
+				A[row * new_cols + cols + i] = 1.;
+				b.push(value);
+			},
+			BranchBound::Lower(value) => {
+				A[row * new_cols + cols + i] = -1.;
+				b.push(value);
+			}
+		}
+
+		c.push(0.);
+		vars.push(format!("branch_{}", i + 1));
+		kinds.push(VariableKind::Continuous);
+		constraint_names.push(format!("branch_{}", i + 1));
+	}
+
+	Lp {
+		A: Matrix::new(new_rows, new_cols, A),
+		b,
+		c,
+		optimization: lp.optimization.clone(),
+		vars,
+		num_artificial_vars: lp.num_artificial_vars + bounds.len(),
+		constraint_names,
+		ranges: lp.ranges.clone(),
+		kinds,
+		objective_negated: lp.objective_negated
+	}
+}
+
+#[cfg(test)]
+#[allow(deprecated)]			// assert_matrix_eq! calls str::trim_right
+mod mip_tests {
+	use super::*;
+	use builder::{Builder, BuilderBase};
+	use parser::{Parser, ParserBase};
+
+	#[test]
+	fn with_bounds_test() {
+		let lp = Parser::lp_from_text("
+			var a integer;
+			maximize obj: a;
+			subject to cap: 2*a <= 3;
+		", Builder::new());
+		let bounded = with_bounds(&lp, &[(0, BranchBound::Upper(1.)), (0, BranchBound::Lower(1.))]);
+
+		assert_matrix_eq!(bounded.A, matrix![2., 1., 0., 0.;
+											1., 0., 1., 0.;
+											1., 0., 0., -1.]);
+		assert_eq!(bounded.b, vec![3., 1., 1.]);
+		assert_eq!(bounded.num_artificial_vars, 3);
+		assert_eq!(bounded.validate(), Ok(()));
+	}
+}
@@ -1,18 +1,20 @@
 use super::*;
-use lp::{Lp, Optimization};
+use lp::{Lp, Optimization, RangedRow, VariableKind};
 use rulinalg::matrix::{BaseMatrixMut, BaseMatrix};
-use std::f64::INFINITY;
+use rulinalg::vector::Vector;
+use std::mem;
 use utils::print_matrix;
 
+// Tableau entries smaller than this are treated as zero when pricing and
+// pivoting, so round-off never gets picked as a pivot element
+const EPSILON: f64 = 1e-9;
+
 impl SolverBase for SimplexSolver {
 	/// Constructor for SolverBase struct.
 	/// 
 	/// Requires an input Lp struct.
 	fn new(lp: Lp) -> Self {
-		SimplexSolver {
-			tableau: SimplexSolver::convert_lp_to_tableau(&lp),
-			lp: lp
-		}	
+		SimplexSolver::with_buffers(lp, vec![], vec![])
 	}
 
 	/// Solves the SimplexSolver.
@@ -25,7 +27,7 @@ impl SolverBase for SimplexSolver {
 	/// # extern crate rulp;
 	/// use rulp::solver::{SimplexSolver, Status, SolverBase};
 	/// use std::collections::HashSet;
-	/// use rulp::lp::{Lp, Optimization};
+	/// use rulp::lp::{Lp, Optimization, VariableKind};
 	/// use rulinalg::matrix::{Matrix, BaseMatrixMut};
 	/// use std::f64::INFINITY;
 	///
@@ -45,7 +47,11 @@ impl SolverBase for SimplexSolver {
 	/// 		c: c,
 	/// 		optimization: Optimization::Max,
 	/// 		vars: vars,
-	///			num_artificial_vars: 0,
+	/// 		num_artificial_vars: 0,
+	/// 		constraint_names: vec!["c1".to_string(), "c2".to_string()],
+	/// 		ranges: vec![],
+	/// 		kinds: vec![VariableKind::Continuous; 4],
+	/// 		objective_negated: false,
 	/// };
 	///
 	/// let simplex = SimplexSolver::new(lp);
@@ -57,51 +63,469 @@ impl SolverBase for SimplexSolver {
 	/// # }
 	/// ```
 	fn solve(&self) -> Solution {
-		// println!("Solver called");
+		self.solve_with_callback(&mut None)
+	}
+}
+
+impl SimplexSolver {
+	pub(crate) fn solve_with_callback(&self, on_iteration: &mut Option<IterationCallback>) -> Solution {
 		let mut local = SimplexSolver::new(self.lp.clone());
-		let has_bfs = local.find_bfs();
+		local.integral_preference = self.integral_preference;
+		local.deterministic = self.deterministic;
+		local.objective_improvement_tolerance = self.objective_improvement_tolerance;
+		local.debug_checks = self.debug_checks;
+		local.solve_in_place(on_iteration)
+	}
 
+	// Builds the solver's tableau and basis inside the given buffers, which
+	// are cleared first, so their storage is reused when large enough
+	fn with_buffers(lp: Lp, mut tableau: Vec<f64>, mut basis: Vec<Option<usize>>) -> Self {
+		SimplexSolver::fill_tableau(&lp, &mut tableau);
+		let tableau = Matrix::new(lp.A.rows() + 1, lp.A.cols() + 2, tableau);
+		SimplexSolver::fill_initial_basis(&tableau, &mut basis);
+		SimplexSolver {
+			basis,
+			tableau,
+			lp,
+			iterations: 0,
+			integral_preference: false,
+			deterministic: false,
+			objective_improvement_tolerance: 0.,
+			stopped_early: false,
+			debug_checks: false,
+			numerical_error: None,
+			pivots: vec![],
+			farkas: None
+		}
+	}
 
-		// print_matrix(&local.tableau);
+	fn zero_rows(&self) -> Vec<usize> {
+		(0 .. self.lp.A.rows())
+			.filter(|&row| (0 .. self.lp.A.cols()).all(|col| self.lp.A[[row, col]].abs() <= EPSILON))
+			.collect()
+	}
+
+	// Solves a freshly built solver, pivoting its own tableau
+	fn solve_in_place(&mut self, on_iteration: &mut Option<IterationCallback>) -> Solution {
+		let zero_rows = self.zero_rows();
+		if !zero_rows.is_empty() {
+			return self.solve_without_zero_rows(&zero_rows, on_iteration);
+		}
 
+		let has_bfs = self.find_bfs(on_iteration);
+
+
+		// print_matrix(&self.tableau);
+
+		if self.numerical_error.is_some() {
+			return self.numerical_error_solution();
+		}
 		if !has_bfs {
 			return Solution {
 				lp: self.lp.clone(),
     			values: None,
     			objective: None,
-    			status: Status::Infeasible
+    			duals: None,
+    			farkas: self.farkas.clone(),
+    			iterations: self.iterations,
+    			residuals: None,
+    			status: Status::Infeasible,
+    			pivots: self.pivots.clone(),
+    			basis: None
     		};
 		}
 
-		// Local has a basic feasible solution so we can optimize
-		let bounded = local.optimize();
+		self.optimize_to_solution(on_iteration)
+	}
+
+	// Replaces the objective of an optimal tableau, keeping its basis. The
+	// basis stays feasible, as only the objective row changes, so solving
+	// again starts from it rather than from scratch.
+	fn replace_objective(&mut self, c: &[f64]) {
+		let sign = if self.lp.optimization == Optimization::Max { 1. } else { -1. };
+		for (col, coeff) in c.iter().enumerate() {
+			self.tableau[[0, col + 1]] = sign * coeff;
+		}
+		let rhs_index = self.tableau.cols() - 1;
+		self.tableau[[0, rhs_index]] = 0.;
+		self.lp.c = c.to_vec();
+		self.iterations = 0;
+		self.pivots.clear();
+		self.write_obj_in_nb_vars();
+	}
+
+	// Optimizes from the current basic feasible solution
+	fn optimize_to_solution(&mut self, on_iteration: &mut Option<IterationCallback>) -> Solution {
+		let bounded = self.optimize(on_iteration);
 
+		if self.numerical_error.is_some() {
+			return self.numerical_error_solution();
+		}
 		if !bounded {
 			return Solution {
 				lp: self.lp.clone(),
     			values: None,
     			objective: None,
-    			status: Status::Unbounded
+    			duals: None,
+    			farkas: None,
+    			iterations: self.iterations,
+    			residuals: None,
+    			status: Status::Unbounded,
+    			pivots: self.pivots.clone(),
+    			basis: None
     		};
 		}
 
-		let coeff;
-		match &self.lp.optimization {
-			&Optimization::Max => coeff = 1.,
-			&Optimization::Min => coeff = -1.,
-		}
-		return Solution {
+		let coeff = match self.lp.optimization {
+			Optimization::Max => 1.,
+			Optimization::Min => -1.,
+		};
+		let reported = self.lp.reported_sign();
+		// The duals of a basis short of the optimum aren't dual feasible
+		let duals = if self.stopped_early { None } else { self.get_duals() };
+		let solution = Solution {
 					lp: self.lp.clone(),
-	    			values: Some(local.get_basic_feasible_solution()),
-	    			objective: Some(local.get_objective() * coeff),
-	    			status: Status::Optimal
+	    			values: Some(self.get_basic_feasible_solution()),
+	    			objective: Some(self.get_objective() * coeff * reported),
+	    			duals: duals.map(|duals| duals.iter().map(|dual| dual * reported).collect()),
+	    			farkas: None,
+	    			iterations: self.iterations,
+	    			residuals: None,
+	    			status: if self.stopped_early { Status::NearOptimal } else { Status::Optimal },
+	    			pivots: self.pivots.clone(),
+	    			basis: Some(self.basis.iter().filter_map(|col| col.map(|col| col - 1)).collect())
 		};
+
+		// With debug_checks, the values found must respect the variables'
+		// bounds as well, up to the same round-off as the invariants
+		if self.debug_checks {
+			const TOLERANCE: f64 = 1e-6;
+
+			if let Some(&(ref name, violation)) = solution.bounds_violations(&self.lp, TOLERANCE).first() {
+				self.numerical_error = Some(format!("Variable \"{}\" is outside its bounds by {}", name, violation));
+				return self.numerical_error_solution();
+			}
+		}
+		solution
 	}
 }
 
 impl SimplexSolver {
+	fn numerical_error_solution(&self) -> Solution {
+		Solution {
+			lp: self.lp.clone(),
+			values: None,
+			objective: None,
+			duals: None,
+			farkas: None,
+			iterations: self.iterations,
+			residuals: None,
+			status: Status::NumericalError(self.numerical_error.clone().unwrap()),
+			pivots: self.pivots.clone(),
+			basis: None
+		}
+	}
+
+	// With debug_checks, every basic value must be non-negative (the basis
+	// stays feasible) and every basic column a unit column with its one in
+	// its own row (the basic columns form an identity), up to round-off
+	fn check_invariants(&self) -> Result<(), String> {
+		const TOLERANCE: f64 = 1e-6;
+
+		let rhs_index = self.tableau.cols() - 1;
+		for (i, basic) in self.basis.iter().enumerate() {
+			let row = i + 1;
+			let col = match *basic {
+				Some(col) => col,
+				None => return Err(format!("Row {} has no basic variable", row))
+			};
+			if self.tableau[[row, rhs_index]] < -TOLERANCE {
+				return Err(format!("Basic variable of row {} is negative: {}", row, self.tableau[[row, rhs_index]]));
+			}
+			for other in 1 .. self.tableau.rows() {
+				let expected = if other == row { 1. } else { 0. };
+				if (self.tableau[[other, col]] - expected).abs() > TOLERANCE {
+					return Err(format!("Basic column {} of row {} has {} in row {} instead of {}",
+						col, row, self.tableau[[other, col]], other, expected));
+				}
+			}
+		}
+
+		Ok(())
+	}
+
+	// A row reading `0 = b` makes the Lp infeasible unless b is zero, in which
+	// case it constrains nothing and is dropped before solving so it never
+	// ends up in the basis. Its dual value is zero.
+	fn solve_without_zero_rows(&self, zero_rows: &[usize], on_iteration: &mut Option<IterationCallback>) -> Solution {
+		if let Some(&row) = zero_rows.iter().find(|&&row| self.lp.b[row].abs() > EPSILON) {
+			let mut farkas = vec![0.; self.lp.A.rows()];
+			farkas[row] = -self.lp.b[row].signum();
+			return Solution {
+				lp: self.lp.clone(),
+				values: None,
+				objective: None,
+				duals: None,
+				farkas: Some(farkas),
+				iterations: 0,
+				residuals: None,
+				status: Status::Infeasible,
+				pivots: vec![],
+				basis: None
+			};
+		}
+
+		let kept: Vec<usize> = (0 .. self.lp.A.rows()).filter(|row| !zero_rows.contains(row)).collect();
+		let new_index = |row: usize| kept.iter().position(|&kept_row| kept_row == row).unwrap();
+
+		let mut data = Vec::with_capacity(kept.len() * self.lp.A.cols());
+		for &row in &kept {
+			for col in 0 .. self.lp.A.cols() {
+				data.push(self.lp.A[[row, col]]);
+			}
+		}
+
+		let reduced = Lp {
+			A: Matrix::new(kept.len(), self.lp.A.cols(), data),
+			b: kept.iter().map(|&row| self.lp.b[row]).collect(),
+			c: self.lp.c.clone(),
+			optimization: self.lp.optimization.clone(),
+			vars: self.lp.vars.clone(),
+			num_artificial_vars: self.lp.num_artificial_vars,
+			constraint_names: kept.iter().map(|&row| self.lp.constraint_names[row].clone()).collect(),
+			ranges: self.lp.ranges.iter().map(|range| RangedRow {
+				row: new_index(range.row),
+				bound_row: new_index(range.bound_row),
+				..range.clone()
+			}).collect(),
+			kinds: self.lp.kinds.clone(),
+			objective_negated: self.lp.objective_negated
+		};
+
+		let mut reduced_solver = SimplexSolver::new(reduced);
+		reduced_solver.integral_preference = self.integral_preference;
+		reduced_solver.deterministic = self.deterministic;
+		reduced_solver.objective_improvement_tolerance = self.objective_improvement_tolerance;
+		reduced_solver.debug_checks = self.debug_checks;
+		let mut solution = reduced_solver.solve_with_callback(on_iteration);
+		solution.lp = self.lp.clone();
+		let expand = |reduced: Vec<f64>| {
+			let mut full = vec![0.; self.lp.A.rows()];
+			for (i, &row) in kept.iter().enumerate() {
+				full[row] = reduced[i];
+			}
+			full
+		};
+		solution.duals = solution.duals.map(&expand);
+		solution.farkas = solution.farkas.map(&expand);
+		solution
+	}
+}
+
+impl Default for Solver {
+	fn default() -> Self {
+		Solver::new()
+	}
+}
+
+impl Solver {
+	/// Constructor for Solver struct using the default options.
+	pub fn new() -> Self {
+		Self::with_options(SolverOptions::default())
+	}
+
+	/// Constructor for Solver struct.
+	pub fn with_options(options: SolverOptions) -> Self {
+		Solver {
+			options,
+			frontier: vec![],
+			incumbent: None,
+			nodes: 0,
+			tableau_buffer: vec![],
+			basis_buffer: vec![],
+			buffer_allocations: 0,
+			deadline: None,
+			warm_lp: None
+		}
+	}
+
+	/// Solves the Lp with the method selected in the options.
+	///
+	/// # Examples
+	/// ```
+	/// use rulp::lp::Lp;
+	/// use rulp::solver::{Solver, SolverOptions, SolverMethod, Status};
+	///
+	/// let lp = Lp::random(4, 3, 1);
+	/// let simplex = Solver::new().solve(&lp);
+	/// let interior_point = Solver::with_options(SolverOptions {
+	/// 	method: SolverMethod::InteriorPoint,
+	/// 	..SolverOptions::default()
+	/// }).solve(&lp);
+	///
+	/// assert_eq!(interior_point.status, Status::Optimal);
+	/// assert!((simplex.objective.unwrap() - interior_point.objective.unwrap()).abs() < 1e-6);
+	/// ```
+	pub fn solve(&mut self, lp: &Lp) -> Solution {
+		// A feasible region made of a single point is its own optimum,
+		// whatever the objective
+		if self.options.singleton_check {
+			if let Some(point) = lp.singleton_point() {
+				self.warm_lp = None;
+				return Solution {
+					lp: lp.clone(),
+					objective: Some(lp.reported_sign() * lp.c.iter().zip(point.iter()).map(|(c, x)| c * x).sum::<f64>()),
+					values: Some(point),
+					duals: None,
+					farkas: None,
+					iterations: 0,
+					residuals: None,
+					status: Status::Optimal,
+					pivots: vec![],
+					basis: None
+				};
+			}
+		}
+
+		let on_iteration = &mut self.options.on_iteration;
+		match self.options.method {
+			SolverMethod::Simplex => {
+				let tableau = mem::take(&mut self.tableau_buffer);
+				let basis = mem::take(&mut self.basis_buffer);
+				if tableau.capacity() < (lp.A.rows() + 1) * (lp.A.cols() + 2) {
+					self.buffer_allocations += 1;
+				}
+
+				let mut simplex = SimplexSolver::with_buffers(lp.clone(), tableau, basis);
+				simplex.integral_preference = self.options.integral_preference;
+				simplex.deterministic = self.options.deterministic;
+				simplex.objective_improvement_tolerance = self.options.objective_improvement_tolerance;
+				simplex.debug_checks = self.options.debug_checks;
+				let solution = simplex.solve_in_place(on_iteration);
+				let warm = solution.status == Status::Optimal && simplex.zero_rows().is_empty();
+
+				self.store_buffers(simplex, warm);
+				solution
+			},
+			SolverMethod::InteriorPoint => {
+				self.warm_lp = None;
+				InteriorPointSolver::new(lp.clone()).solve_with_callback(on_iteration)
+			},
+		}
+	}
+
+	/// Checks that every solver method agrees on the Lp: they must report
+	/// the same status and, when optimal, objectives within `1e-6` times
+	/// the objective's magnitude.
+	///
+	/// This is a testing aid that solves the Lp once per method, so it is
+	/// only built for the crate's own tests or with the `cross-validate`
+	/// feature.
+	#[cfg(any(test, feature = "cross-validate"))]
+	pub fn cross_validate(lp: &Lp) -> bool {
+		let solutions: Vec<Solution> = vec![SolverMethod::Simplex, SolverMethod::InteriorPoint].into_iter()
+			.map(|method| Solver::with_options(SolverOptions {
+				method,
+				..SolverOptions::default()
+			}).solve(lp))
+			.collect();
+
+		let reference = &solutions[0];
+		solutions[1 ..].iter().all(|solution| {
+			solution.status == reference.status && match (solution.objective, reference.objective) {
+				(Some(objective), Some(expected)) => (objective - expected).abs() <= 1e-6 * (1. + expected.abs()),
+				(None, None) => true,
+				_ => false
+			}
+		})
+	}
+
+	/// Solves the Lp of the last `solve` again with the objective
+	/// coefficients replaced by `new_c` (one per column, like `Lp::c`).
+	///
+	/// The simplex method starts from the optimal basis of the last solve,
+	/// which stays feasible since only the objective changed, so an
+	/// objective sweep usually takes a few pivots per step instead of a
+	/// full solve. Panics unless the last solve used the simplex method and
+	/// ended optimal, or if `new_c` has the wrong length. Branch and bound
+	/// solves don't count, their subproblems aren't kept.
+	///
+	/// # Examples
+	/// ```
+	/// use rulp::lp::Lp;
+	/// use rulp::solver::Solver;
+	///
+	/// let lp = Lp::random(4, 3, 5);
+	/// let mut solver = Solver::new();
+	/// solver.solve(&lp);
+	///
+	/// let mut new_c = lp.c.clone();
+	/// new_c[0] *= 2.;
+	/// let warm = solver.resolve_with_objective(&new_c);
+	///
+	/// let mut changed = lp.clone();
+	/// changed.c = new_c;
+	/// let cold = Solver::new().solve(&changed);
+	/// assert!((warm.objective.unwrap() - cold.objective.unwrap()).abs() < 1e-6);
+	/// ```
+	pub fn resolve_with_objective(&mut self, new_c: &[f64]) -> Solution {
+		let lp = self.warm_lp.take().expect("No optimal simplex solve to start from");
+		if new_c.len() != lp.c.len() {
+			panic!("Objective has {} coefficients for {} columns", new_c.len(), lp.c.len());
+		}
+
+		let tableau = mem::take(&mut self.tableau_buffer);
+		let basis = mem::take(&mut self.basis_buffer);
+		let rows = lp.A.rows() + 1;
+		let cols = lp.A.cols() + 2;
+		let mut simplex = SimplexSolver {
+			tableau: Matrix::new(rows, cols, tableau),
+			basis,
+			lp,
+			iterations: 0,
+			integral_preference: self.options.integral_preference,
+			deterministic: self.options.deterministic,
+			objective_improvement_tolerance: self.options.objective_improvement_tolerance,
+			stopped_early: false,
+			debug_checks: self.options.debug_checks,
+			numerical_error: None,
+			pivots: vec![],
+			farkas: None
+		};
+		simplex.replace_objective(new_c);
+		let solution = simplex.optimize_to_solution(&mut self.options.on_iteration);
+
+		let warm = solution.status == Status::Optimal;
+		self.store_buffers(simplex, warm);
+		solution
+	}
+
+	fn store_buffers(&mut self, simplex: SimplexSolver, warm: bool) {
+		let SimplexSolver { tableau, basis, lp, .. } = simplex;
+		self.tableau_buffer = tableau.into_vec();
+		self.basis_buffer = basis;
+		self.warm_lp = if warm { Some(lp) } else { None };
+	}
+
+	/// Returns how many simplex solves needed a larger tableau buffer than
+	/// the one kept from earlier solves, and so had to allocate a new one.
+	#[cfg(test)]
+	fn buffer_allocations(&self) -> usize {
+		self.buffer_allocations
+	}
+}
+
+impl SimplexSolver {
+	#[cfg(test)]
 	fn convert_lp_to_tableau(lp: &Lp) -> Matrix<f64> {
-		let mut mat_builder: Vec<f64> = vec![1.];
+		let mut mat_builder = vec![];
+		SimplexSolver::fill_tableau(lp, &mut mat_builder);
+		Matrix::new(&lp.A.rows()+1, &lp.A.cols()+2, mat_builder)
+	}
+
+	fn fill_tableau(lp: &Lp, mat_builder: &mut Vec<f64>) {
+		mat_builder.clear();
+		mat_builder.push(1.);
 		for opt_coeff in &lp.c {
 			match lp.optimization {
 				Optimization::Min => {
@@ -115,31 +539,58 @@ impl SimplexSolver {
 		mat_builder.push(0.);
 		unsafe {
 			for row in 0 .. lp.A.rows() {
-				mat_builder.push(0.);
-				for col in 0 .. lp.A.cols() {
-					mat_builder.push(*lp.A.get_unchecked([row, col]));
+				let sign = if lp.b[row] < 0. { -1. } else { 1. };			// Rows are negated where needed so every RHS is
+				mat_builder.push(0.);											// non-negative, otherwise a slack could start out
+				for col in 0 .. lp.A.cols() {									// basic with a negative value
+					mat_builder.push(sign * *lp.A.get_unchecked([row, col]));
 				}
-				mat_builder.push(lp.b[row]);
+				mat_builder.push(sign * lp.b[row]);
 			}
 		}
 		
 		// println!("{:}", lp);
 		// println!("{:?}", mat_builder);
 		// println!("{:?}", mat_builder.len());
+	}
 
-		Matrix::new(&lp.A.rows()+1, &lp.A.cols()+2, mat_builder)
+	// A row's initial basic variable is the first column that is a unit
+	// vector with its 1 in that row. Later unit columns for the same row
+	// are non-basic, otherwise they would all claim the row's value.
+	fn fill_initial_basis(tableau: &Matrix<f64>, basis: &mut Vec<Option<usize>>) {
+		basis.clear();
+		basis.resize(tableau.rows() - 1, None);
+		unsafe {
+			for col in 1 .. tableau.cols() - 1 {
+				let mut one_row = None;
+				let mut zero_ct = 0;
+				for row in 1 .. tableau.rows() {
+					let coeff = *tableau.get_unchecked([row, col]);
+					if coeff == 1. {
+						one_row = Some(row);
+					} else if coeff == 0. {
+						zero_ct += 1;
+					}
+				}
+
+				if let Some(row) = one_row {
+					if zero_ct == tableau.rows() - 2 && basis[row - 1].is_none() {
+						basis[row - 1] = Some(col);
+					}
+				}
+			}
+		}
 	}
 
 	fn is_optimal(&self) -> bool {
 		unsafe{
 			for col in 1 .. self.tableau.cols() - 1{
-				if *self.tableau.get_unchecked([0, col]) < 0. {
+				if *self.tableau.get_unchecked([0, col]) < -EPSILON {
 					return false;
 				}
 			}
 		}
 
-		return true
+		true
 	}
 
 	fn get_basic_feasible_solution(&self) -> Vec<f64> {
@@ -157,39 +608,55 @@ impl SimplexSolver {
 				}
 			}
 		}
-		return bfs;
+		bfs
 	}
 
-	fn get_basic_row(&self, col: usize) -> usize {
-		let mut ret = 1;
+	// Maps each constraint row to the column of the basic variable spanning it.
+	// Returns None if some row is not spanned by one of the Lp's own columns.
+	fn get_basis(&self) -> Option<Vec<usize>> {
+		self.basis.iter().cloned().collect()
+	}
+
+	// Dual values are y = c_B * B^-1, found by solving B^T y = c_B with the
+	// final basis taken from the original (untransformed) Lp
+	fn get_duals(&self) -> Option<Vec<f64>> {
+		let basis = self.get_basis()?;
+
+		let m = basis.len();
+		if m == 0 {
+			return Some(vec![]);
+		}
+
+		let mut B_t = Vec::with_capacity(m * m);
+		let mut c_B = Vec::with_capacity(m);
 		unsafe {
-			for row in 1 .. self.tableau.rows() {
-				if *self.tableau.get_unchecked([row, col]) == 1. {
-					ret = row;
+			for &col in &basis {
+				for row in 0 .. m {
+					B_t.push(*self.lp.A.get_unchecked([row, col - 1]));
 				}
+				c_B.push(self.lp.c[col - 1]);
 			}
 		}
-		return ret;
+
+		match Matrix::new(m, m, B_t).solve(Vector::new(c_B)) {
+			Ok(y) => Some(y.into_vec()),
+			Err(_) => None
+		}
+	}
+
+	fn get_basic_row(&self, col: usize) -> usize {
+		match self.basis.iter().position(|&basic| basic == Some(col)) {
+			Some(index) => index + 1,
+			None => panic!("Column {} is not basic", col)
+		}
 	}
 
 	fn is_basic(&self, col: usize) -> bool {
 		if col < 1 || col >= self.tableau.cols() {
 			panic!("Invalid col index {} for basic variable", col);
 		}
-		unsafe {
-			let mut one_ct = 0;
-			let mut zero_ct = 0;
-			for row in 1 .. self.tableau.rows() {
-				let coeff = *self.tableau.get_unchecked([row, col]);
-				if coeff == 1. {
-					one_ct += 1;
-				} else if coeff == 0. {
-					zero_ct += 1;
-				}
-			}
 
-			one_ct == 1 && zero_ct == (self.tableau.rows() - 2)
-		}
+		self.basis.contains(&Some(col))
 	}
 
 	fn calc_pivot_ratio(&self, row: usize, col: usize) -> Option<f64> {
@@ -200,7 +667,7 @@ impl SimplexSolver {
 		}
 		unsafe {
 			let coeff = *self.tableau.get_unchecked([row, col]);
-			if coeff > 0. {
+			if coeff > EPSILON {
 				let rhs_index = self.tableau.cols() - 1;
 				let rhs_val = *self.tableau.get_unchecked([row, rhs_index]);
 				Some(rhs_val / coeff)
@@ -210,19 +677,19 @@ impl SimplexSolver {
 		}
 	}
 
+	// Ties in the ratio test go to the first row, exact ties only unless
+	// deterministic, which also counts ratios within EPSILON as ties
 	fn choose_pivot_row(&self, col: usize) -> usize {
-		let mut min_ratio = INFINITY;
+		let tolerance = if self.deterministic { EPSILON } else { 0. };
+		let mut min_ratio = f64::INFINITY;
 		let mut min_row = 0;
 
 		for row in 1 .. self.tableau.rows() {
-			match self.calc_pivot_ratio(row, col) {
-				Some(ratio) => {
-					if ratio < min_ratio {
-						min_ratio = ratio;
-						min_row = row;
-					}
-				},
-				_ => {}
+			if let Some(ratio) = self.calc_pivot_ratio(row, col) {
+				if ratio < min_ratio - tolerance {
+					min_ratio = ratio;
+					min_row = row;
+				}
 			}
 		}
 		
@@ -234,16 +701,29 @@ impl SimplexSolver {
 		min_row
 	}
 
+	// The entering column is the improving one with the smallest index.
+	// With integral_preference, an integer or binary column whose reduced
+	// cost ties with that one's enters instead, so the preference only
+	// decides between equally priced columns
 	fn choose_pivot_col(&self) -> usize {
-		unsafe {
-			for i in 1 .. self.tableau.cols() - 1 {
-				if *self.tableau.get_unchecked([0, i]) < 0. {
-					return i;
-				}
+		let reduced_cost = |i: usize| unsafe { *self.tableau.get_unchecked([0, i]) };
+
+		let first = match (1 .. self.tableau.cols() - 1).find(|&i| reduced_cost(i) < -EPSILON) {
+			Some(i) => i,
+			None => panic!("No pivot var chosen because optimal solution!")
+		};
+
+		if self.integral_preference {
+			let tied = (first .. self.tableau.cols() - 1).find(|&i| {
+				i <= self.lp.kinds.len() && self.lp.kinds[i - 1] != VariableKind::Continuous
+					&& (reduced_cost(i) - reduced_cost(first)).abs() <= EPSILON
+			});
+			if let Some(i) = tied {
+				return i;
 			}
 		}
 
-		panic!("No pivot var chosen because optimal solution!");
+		first
 	}
 
 	fn normalize_pivot(&mut self, row: usize, col: usize) {
@@ -258,7 +738,7 @@ impl SimplexSolver {
 
 	fn eliminate_row(&mut self, pivot_row: usize, pivot_col: usize, row: usize) {
 		unsafe {
-			let mult_factor = *self.tableau.get_unchecked([row, pivot_col]) / *self.tableau.get_unchecked([pivot_row, pivot_col]) * -1.0;
+			let mult_factor = -(*self.tableau.get_unchecked([row, pivot_col]) / *self.tableau.get_unchecked([pivot_row, pivot_col]));
 			for c in 1 .. self.tableau.cols() {
 				let add_factor = *self.tableau.get_unchecked([pivot_row, c]) * mult_factor;
 				*self.tableau.get_unchecked_mut([row, c]) += add_factor;
@@ -269,6 +749,7 @@ impl SimplexSolver {
 
 	fn pivot(&mut self, row: usize, col:usize) {
 		self.normalize_pivot(row, col);
+		self.basis[row - 1] = Some(col);
 
 		for r in 0 .. self.tableau.rows() {
 			if r == row {
@@ -280,33 +761,41 @@ impl SimplexSolver {
 	}
 
 	fn find_unspanned_rows(&self) -> Option<Vec<usize>> {
-		unsafe {
-			let mut no_basic = vec![];
-			for row in 1 .. self.tableau.rows() {
-				let mut has_basic = false;
-				for col in 1 .. self.tableau.cols() - 1 {
-					if *self.tableau.get_unchecked([row, col]) == 1. && self.is_basic(col) {
-						has_basic = true;
-					}
-				}
+		let no_basic: Vec<usize> = (1 .. self.tableau.rows())
+			.filter(|&row| self.basis[row - 1].is_none())
+			.collect();
+
+		if no_basic.is_empty() {
+			None
+		} else {
+			Some(no_basic)
+		}
+	}
 
-				if !has_basic {
-					no_basic.push(row);
-				}
+	// Pivots Phase I artificial vars that are still basic (at zero) out of the
+	// basis wherever the row has a non-zero coefficient on a real var. Rows
+	// where it has none are redundant and stay without a basic var.
+	fn drive_out_artificial_vars(&mut self, num_real_cols: usize) {
+		for row in 1 .. self.tableau.rows() {
+			match self.basis[row - 1] {
+				Some(col) if col > num_real_cols => {},
+				_ => continue
 			}
-			
-			if no_basic.len() == 0 {
-				return None;
-			} else {
-				return Some(no_basic);
+
+			let entering = (1 .. num_real_cols + 1).find(|&col| unsafe {
+				!self.is_basic(col) && self.tableau.get_unchecked([row, col]).abs() > 1e-9
+			});
+
+			match entering {
+				Some(col) => self.pivot(row, col),
+				None => self.basis[row - 1] = None
 			}
 		}
 	}
 
 	// Can only be called once a BFS has been established
-	fn optimize(&mut self) -> bool {
+	fn optimize(&mut self, on_iteration: &mut Option<IterationCallback>) -> bool {
 		// println!("Beginning optimization actually");
-		// let mut iterations = 0;
 		while !(self.is_optimal()) {
 			// println!(">>> Iteration {}", iterations);
 			// print_matrix(&self.tableau);
@@ -317,23 +806,60 @@ impl SimplexSolver {
 				return false
 			}
 			// println!("Pivot row: {:?} ({} var leaving)", pivot_row, pivot_row - 1);
+			let leaving = self.basis[pivot_row - 1].expect("Pivot row without a basic variable");
+			self.pivots.push((pivot_col - 1, leaving - 1));
+			// A degenerate pivot doesn't move, so its lack of improvement says
+			// nothing about how far the optimum is
+			let rhs_index = self.tableau.cols() - 1;
+			let degenerate = self.tableau[[pivot_row, rhs_index]].abs() <= EPSILON;
+			let objective = self.get_objective();
 			self.pivot(pivot_row, pivot_col);
 			// print_matrix(&self.tableau);
 			// println!("<<< Iteration {}", iterations);
-			// iterations += 1;
+			self.iterations += 1;
+
+			if let Some(ref mut callback) = *on_iteration {
+				callback(self.iterations, self.current_objective());
+			}
+
+			if self.debug_checks {
+				if let Err(invariant) = self.check_invariants() {
+					self.numerical_error = Some(invariant);
+					return true;
+				}
+			}
+
+			if !degenerate && (self.get_objective() - objective).abs() < self.objective_improvement_tolerance && !self.is_optimal() {
+				self.stopped_early = true;
+				break;
+			}
 		}
 
 		true
-		// iterations
+	}
+
+	// Value of the Lp's own objective at the current basic solution. During
+	// Phase I this is the objective of the (possibly infeasible) Phase I point.
+	fn current_objective(&self) -> f64 {
+		let rhs_index = self.tableau.cols() - 1;
+		let mut objective = 0.;
+		for (row, basic) in self.basis.iter().enumerate() {
+			if let Some(col) = *basic {
+				if col <= self.lp.c.len() {
+					objective += self.lp.c[col - 1] * self.tableau[[row + 1, rhs_index]];
+				}
+			}
+		}
+		objective
 	}
 
 	fn get_objective(&self) -> f64 {
 		unsafe {
-			return *self.tableau.get_unchecked([0, self.tableau.cols() - 1]);
+			*self.tableau.get_unchecked([0, self.tableau.cols() - 1])
 		}
 	}
 
-	fn find_bfs(&mut self) -> bool {
+	fn find_bfs(&mut self, on_iteration: &mut Option<IterationCallback>) -> bool {
 		// println!("find_bfs");
 		unsafe {
 			match self.find_unspanned_rows() {
@@ -344,15 +870,24 @@ impl SimplexSolver {
 					// println!("!! Unspanned rows found. Entering Phase I");
 					// println!("{:?}", &unspanned_rows);
 					let mut phase_one = self.generate_phase_one(&unspanned_rows);
-					phase_one.find_bfs();
-					let _ = phase_one.optimize();
+					phase_one.find_bfs(on_iteration);
+					let _ = phase_one.optimize(on_iteration);
+					self.iterations = phase_one.iterations;
+					self.pivots = phase_one.pivots.clone();
+					if phase_one.numerical_error.is_some() {
+						self.numerical_error = phase_one.numerical_error;
+						return false;
+					}
 					
 					let phase_one_obj = phase_one.get_objective();					// If the objective of the optmized Phase I problem
 					// print_matrix(&phase_one.tableau);
 					if !(  relative_eq!(phase_one_obj,  0., epsilon = 0.0000001) 	// is non-zero, then no bfs exists (problem is infeasible)
 						|| relative_eq!(phase_one_obj, -0., epsilon = 0.0000001)) { // kinda hacky way of testing due to f64 precision											
+						self.farkas = self.farkas_certificate(&phase_one, &unspanned_rows);
 						return false
 					} else {														// Bfs exists. Converting to Phase II by copying over
+						phase_one.drive_out_artificial_vars(self.tableau.cols() - 2);
+						self.basis = phase_one.basis.clone();
 						for row in 1 .. self.tableau.rows() {						// new bfs
 							for col in 0 .. self.tableau.cols() - 1 {
 								*self.tableau.get_unchecked_mut([row, col]) =
@@ -375,6 +910,41 @@ impl SimplexSolver {
 		}
 	}
 
+	// The duals of an optimal Phase I problem that failed to drive its
+	// artificial vars to zero prove infeasibility. Phase I maximizes minus
+	// their sum, so its duals y have y'A >= 0 on the real columns while
+	// y'b equals the negative optimum. They are solved from the Phase I
+	// basis in terms of the sign-adjusted rows of the tableau and mapped
+	// back to the rows of the Lp.
+	fn farkas_certificate(&self, phase_one: &SimplexSolver, unspanned_rows: &[usize]) -> Option<Vec<f64>> {
+		let m = self.lp.A.rows();
+		let num_real_cols = self.tableau.cols() - 2;
+		let mut B_t = Vec::with_capacity(m * m);
+		let mut c_B = Vec::with_capacity(m);
+		for basic in &phase_one.basis {
+			let col = (*basic)?;
+			if col <= num_real_cols {
+				for row in 1 .. m + 1 {
+					B_t.push(self.tableau[[row, col]]);
+				}
+				c_B.push(0.);
+			} else {
+				let artificial_row = unspanned_rows[col - num_real_cols - 1];
+				for row in 1 .. m + 1 {
+					B_t.push(if row == artificial_row { 1. } else { 0. });
+				}
+				c_B.push(-1.);
+			}
+		}
+
+		match Matrix::new(m, m, B_t).solve(Vector::new(c_B)) {
+			Ok(y) => Some(y.into_vec().iter().enumerate()
+				.map(|(row, dual)| if self.lp.b[row] < 0. { -dual } else { *dual })
+				.collect()),
+			Err(_) => None
+		}
+	}
+
 	fn generate_phase_one(&self, unspanned_rows: &Vec<usize>) -> Self {
 		unsafe {
 			let new_rows = self.tableau.rows(); 									// Phase I has same number of constraints
@@ -395,15 +965,26 @@ impl SimplexSolver {
 					*self.tableau.get_unchecked([row, self.tableau.cols() - 1]);
 			}
 
-			let mut col = self.tableau.cols() - 1;									// Adding in Phase I artificial vars, starting at the first
-			for &row in unspanned_rows {											// column specific to Phase I. Objective coeff for each Phase I
+			let mut basis = self.basis.clone();
+			let first_col = self.tableau.cols() - 1;								// Adding in Phase I artificial vars, starting at the first
+			for (col, &row) in (first_col ..).zip(unspanned_rows) {					// column specific to Phase I. Objective coeff for each Phase I
 				*phase_one.get_unchecked_mut([row, col]) = 1.;						// artificial var is -1 b/c want to minimize them out of the
 				*phase_one.get_unchecked_mut([0, col]) = -1.;						// basis
-				col += 1;															
+				basis[row - 1] = Some(col);
 			}
 			
 			SimplexSolver {
 				tableau: phase_one,
+				basis,
+				iterations: self.iterations,
+				integral_preference: self.integral_preference,
+				deterministic: self.deterministic,
+				objective_improvement_tolerance: 0.,
+				stopped_early: false,
+				debug_checks: self.debug_checks,
+				numerical_error: None,
+				pivots: self.pivots.clone(),
+				farkas: None,
 				lp: self.lp.clone()													// Don't really need LP here but makes it simpler to keep as
 			}																		// SimplexSolver struct
 		}
@@ -420,22 +1001,11 @@ impl SimplexSolver {
 				*self.tableau.get_unchecked_mut([0, col]) = 0.;						// Then setting the entry to 0
 			}
 
-			for col in 1 .. self.tableau.cols() - 1 {								// Can ignore first and last elements of obj row
-				let obj_coeff = obj_function[col];
+			for (col, &obj_coeff) in obj_function.iter().enumerate().take(self.tableau.cols() - 1).skip(1) {	// Can ignore first and last elements of obj row
 				if !self.is_basic(col) {
 					*self.tableau.get_unchecked_mut([0, col]) -= obj_coeff;			// Basic vars are already written in terms of themselves
 				} else {
-					let mut row = 0;												// Finding the row that the basic var spans					
-					for r in 1 .. self.tableau.rows() {
-						if *self.tableau.get_unchecked([r, col]) == 1. {
-							row = r; 
-							break;
-						}
-					}
-
-					if row == 0 {													// Row cannot be 0 since variable is non-basic
-						panic!("Row cannot be 0");									// Should never happen, but cant hurt to check
-					}
+					let row = self.get_basic_row(col);								// Finding the row that the basic var spans
 
 					for c in 1 .. self.tableau.cols() {							// Iterate through non-basic variables again and 
 						if c == (self.tableau.cols() - 1) || !self.is_basic(c) {									// add the product of their coeff and the non-basic var coeff
@@ -452,9 +1022,14 @@ impl SimplexSolver {
 }
 
 #[cfg(test)]
+#[allow(deprecated)]			// assert_matrix_eq! calls str::trim_right
+#[allow(clippy::redundant_field_names, clippy::vec_init_then_push, clippy::bool_assert_comparison, clippy::useless_vec)]	// Kept as first written
 mod solve_tests {
 	use super::*;
 	use assert_approx_eq::*;
+	use builder::{Builder, BuilderBase};
+	use parser::{Parser, ParserBase};
+	use std::fs::File;
 
 	#[test]
 	fn to_tableau_test () {
@@ -485,6 +1060,10 @@ mod solve_tests {
 				optimization: Optimization::Max,
 				vars: vars.clone(),
 				num_artificial_vars: 0,
+				constraint_names: vec!["c1".to_string(), "c2".to_string()],
+				ranges: vec![],
+				kinds: vec![VariableKind::Continuous; 5],
+				objective_negated: false,
 		};
 		let Lp2 = Lp {
 				A: A,
@@ -492,7 +1071,11 @@ mod solve_tests {
 				c: c2,
 				optimization: Optimization::Max,
 				vars: vars,
-				num_artificial_vars: 0
+				num_artificial_vars: 0,
+				constraint_names: vec!["c1".to_string(), "c2".to_string()],
+				ranges: vec![],
+				kinds: vec![VariableKind::Continuous; 5],
+				objective_negated: false,
 		};
 		let not_optimal = SimplexSolver::new(Lp1);
 		let optimal = SimplexSolver::new(Lp2);
@@ -564,7 +1147,11 @@ mod solve_tests {
 				c: c,
 				optimization: Optimization::Max,
 				vars: vars.clone(),
-				num_artificial_vars: 0
+				num_artificial_vars: 0,
+				constraint_names: vec!["c1".to_string(), "c2".to_string()],
+				ranges: vec![],
+				kinds: vec![VariableKind::Continuous; 5],
+				objective_negated: false,
 		};
 		let simplex = SimplexSolver::new(lp);
 
@@ -656,6 +1243,35 @@ mod solve_tests {
 		assert_eq!(solution.objective.unwrap(), 0.);
 	}
 
+	#[test]
+	fn check_invariants_test() {
+		let mut simplex = SimplexSolver::new(create_dummy_lp());
+		assert_eq!(simplex.check_invariants(), Ok(()));
+
+		simplex.tableau[[1, 5]] = -1.;
+		assert_eq!(simplex.check_invariants(), Err("Basic variable of row 1 is negative: -1".to_string()));
+
+		simplex.tableau[[1, 5]] = 4.;
+		simplex.tableau[[2, 3]] = 0.5;
+		assert_eq!(simplex.check_invariants(), Err("Basic column 3 of row 1 has 0.5 in row 2 instead of 0".to_string()));
+	}
+
+	#[test]
+	fn buffer_allocations_test() {
+		let mut lp = Lp::random(4, 3, 1);
+		let mut solver = Solver::new();
+		for i in 0 .. 50 {
+			lp.b[0] += i as f64;
+			solver.solve(&lp);
+		}
+		assert_eq!(solver.buffer_allocations(), 1);
+
+		// A larger Lp outgrows the buffers once, smaller ones fit again
+		solver.solve(&Lp::random(10, 6, 3));
+		solver.solve(&lp);
+		assert_eq!(solver.buffer_allocations(), 2);
+	}
+
 	fn create_dummy_lp() -> Lp {
 		let A = matrix![2., 1., 1., 0.;
 						1., 2., 0., 1.];
@@ -672,10 +1288,28 @@ mod solve_tests {
 				c: c,
 				optimization: Optimization::Max,
 				vars: vars,
-				num_artificial_vars: 0
+				num_artificial_vars: 0,
+				constraint_names: vec!["c1".to_string(), "c2".to_string()],
+				ranges: vec![],
+				kinds: vec![VariableKind::Continuous; 4],
+				objective_negated: false,
 		}
 	}
 	
+	#[test]
+	fn cross_validate_test() {
+		for file in &["advertisement_example.lp", "radiation_example.lp"] {
+			let mut input_file = File::open(format!("./tests/test_files/{}", file)).unwrap();
+			let lp = Parser::lp_from_file(&mut input_file, Builder::new());
+			assert!(Solver::cross_validate(&lp));
+			assert!(Solver::cross_validate(&lp.to_minimization()));
+		}
+
+		for seed in 0 .. 5 {
+			assert!(Solver::cross_validate(&Lp::random(5, 3, seed)));
+		}
+	}
+
 	#[test]
 	fn case_study_test () {
 		// http://college.cengage.com/mathematics/larson/elementary_linear/4e/shared/downloads/c09s3.pdf
@@ -702,7 +1336,16 @@ mod solve_tests {
 				c: c,
 				optimization: Optimization::Max,
 				vars: vars,
-				num_artificial_vars: 4
+				num_artificial_vars: 4,
+				constraint_names: vec![
+					"constraint_1".to_string(),
+					"constraint_2".to_string(),
+					"constraint_3".to_string(),
+					"constraint_4".to_string(),
+				],
+				ranges: vec![],
+				kinds: vec![VariableKind::Continuous; 7],
+				objective_negated: false,
 		};
 		let simplex = SimplexSolver::new(lp);
 		let solution = simplex.solve();
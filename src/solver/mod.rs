@@ -5,24 +5,85 @@
 //! This is implemented via the Simplex Tableau
 //! method.
 use std::fmt;
-use rulinalg::matrix::{Matrix};
-use lp::Lp;
+use std::io::{self, BufRead, Write};
+use std::time::Instant;
+use rulinalg::matrix::{BaseMatrix, Matrix};
+use builder::{Constraint, Relation};
+use lp::{Lp, Optimization, VariableKind};
 
 mod impl_solver;
+mod impl_interior_point;
+mod impl_mip;
+
+// Solution values within this of zero are left out of sparse reports
+const ZERO_TOLERANCE: f64 = 1e-9;
+
+// `(lower, upper)` limits of a sensitivity range
+type SensitivityRange = (f64, f64);
 
 #[derive(Debug, PartialEq, Clone)]
 pub enum Status {
 	Optimal,
 	Infeasible,
-	Unbounded
+	Unbounded,
+	/// `Solver::solve_anytime` reached its deadline before proving a
+	/// solution optimal or proving there is none. The values and objective
+	/// are those of the best solution found, if any
+	Interrupted,
+	/// The simplex method stopped at a feasible solution after a pivot
+	/// improved the objective by less than the option
+	/// `objective_improvement_tolerance`. There are values and an
+	/// objective, but no duals.
+	NearOptimal,
+	/// With the `debug_checks` option, a simplex invariant failed to hold
+	/// after a pivot or the values found violate a bound; or the interior
+	/// point method didn't converge. The message names the cause
+	NumericalError(String)
+}
+
+/// Which bound of a variable its value sits at.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Bound {
+	Lower,
+	Upper
 }
 
 #[derive(Debug, PartialEq, Clone)]
 pub struct Solution {
-	lp: Lp,
+	pub(crate) lp: Lp,
 	pub values: Option<Vec<f64>>, 
 	pub objective: Option<f64>,
-	pub status: Status
+	pub duals: Option<Vec<f64>>,
+	/// For an Lp the simplex method found infeasible, a Farkas certificate:
+	/// one multiplier `y` per row of the equality form `Ax = b, x >= 0`
+	/// such that `A'y >= 0` but `b'y < 0`. Any `x >= 0` would then have
+	/// `y'Ax >= 0 > y'b`, so no `x` satisfies `Ax = b`.
+	pub farkas: Option<Vec<f64>>,
+	pub iterations: usize,
+	pub residuals: Option<Residuals>,
+	pub status: Status,
+	/// The `(entering, leaving)` columns of every simplex pivot, in order,
+	/// one pair per iteration.
+	///
+	/// Columns index the Lp's columns; the artificial columns of a Phase I
+	/// problem are numbered after them. Interior point solves don't pivot,
+	/// so their history is empty.
+	pub pivots: Vec<(usize, usize)>,
+	/// The columns of the basic variables of an optimal simplex solution,
+	/// in row order. Rows left without a basic variable (e.g. redundant
+	/// ones) are skipped.
+	///
+	/// None for solutions not found by the simplex method or that aren't
+	/// optimal.
+	pub basis: Option<Vec<usize>>
+}
+
+/// Norms of the primal (`Ax - b`) and dual (`A^T y + s - c`) residuals
+/// left at the end of an interior point solve.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Residuals {
+	pub primal: f64,
+	pub dual: f64
 }
 
 pub trait SolverBase {
@@ -33,17 +94,741 @@ pub trait SolverBase {
 #[derive(Debug, PartialEq, Clone)]
 pub struct SimplexSolver {
 	lp: Lp,
-	pub tableau: Matrix<f64>
+	pub tableau: Matrix<f64>,
+	basis: Vec<Option<usize>>,
+	iterations: usize,
+	integral_preference: bool,
+	deterministic: bool,
+	objective_improvement_tolerance: f64,
+	/// Set once a pivot improves the objective by less than the tolerance
+	stopped_early: bool,
+	debug_checks: bool,
+	/// The first invariant found broken with `debug_checks`
+	numerical_error: Option<String>,
+	pivots: Vec<(usize, usize)>,
+	farkas: Option<Vec<f64>>
+}
+
+/// Primal-dual path following interior point solver.
+#[derive(Debug, PartialEq, Clone)]
+pub struct InteriorPointSolver {
+	lp: Lp
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum SolverMethod {
+	Simplex,
+	InteriorPoint
+}
+
+/// Called after every iteration with the iteration number and the
+/// current value of the objective.
+pub type IterationCallback = Box<dyn FnMut(usize, f64)>;
+
+pub struct SolverOptions {
+	pub method: SolverMethod,
+	pub on_iteration: Option<IterationCallback>,
+	/// `solve_mip` stops once the relative gap between the incumbent and
+	/// the best bound is at most this
+	pub mip_gap: f64,
+	/// Experimental: among improving columns with the same reduced cost,
+	/// the simplex method lets integer and binary variables enter the basis
+	/// before continuous ones, steering relaxations towards integral values
+	/// to save branching
+	pub integral_preference: bool,
+	/// The simplex ratio test treats ratios within 1e-9 of each other as
+	/// ties, which go to the first row, so the chosen bases and pivots
+	/// don't flip on last-bit floating point differences between
+	/// platforms. Otherwise only exact ties go to the first row. Bases are
+	/// reproducible run to run either way.
+	pub deterministic: bool,
+	/// `solve_mip_all_optima` stops after finding this many optimal
+	/// solutions, if set
+	pub optima_limit: Option<usize>,
+	/// The simplex method stops as soon as a pivot that moves the solution
+	/// improves the objective by less than this, returning a NearOptimal
+	/// solution. Zero, the default, always pivots to the optimum. Branch
+	/// and bound ignores it, since only optimal relaxations bound a MIP.
+	pub objective_improvement_tolerance: f64,
+	/// After every simplex pivot, checks that the basic values are
+	/// non-negative and that the basic columns form an identity, and at
+	/// the end that the values have no `Solution::bounds_violations`,
+	/// stopping with `Status::NumericalError` if not. Slows solves down, so
+	/// meant for working on the solver itself.
+	pub debug_checks: bool,
+	/// `Solver::solve` first checks whether the feasible region is a
+	/// single point (see `Lp::is_singleton_feasible`) and if so returns it
+	/// without pivoting, and without duals or a basis. The check costs a
+	/// Gaussian elimination, so it only pays off for models that are often
+	/// fully determined. Branch and bound doesn't use it.
+	pub singleton_check: bool
+}
+
+impl Default for SolverOptions {
+	fn default() -> Self {
+		SolverOptions {
+			method: SolverMethod::Simplex,
+			on_iteration: None,
+			mip_gap: 0.,
+			integral_preference: false,
+			deterministic: false,
+			optima_limit: None,
+			objective_improvement_tolerance: 0.,
+			debug_checks: false,
+			singleton_check: false
+		}
+	}
+}
+
+/// Chainable setters, one per option, so a configuration can be written
+/// as a single expression:
+///
+/// ```
+/// use rulp::solver::{SolverMethod, SolverOptions};
+///
+/// let options = SolverOptions::default()
+/// 	.with_method(SolverMethod::InteriorPoint)
+/// 	.with_mip_gap(0.01)
+/// 	.with_deterministic(true);
+/// assert_eq!(options.mip_gap, 0.01);
+/// ```
+impl SolverOptions {
+	pub fn with_method(mut self, method: SolverMethod) -> Self {
+		self.method = method;
+		self
+	}
+
+	pub fn with_on_iteration(mut self, on_iteration: IterationCallback) -> Self {
+		self.on_iteration = Some(on_iteration);
+		self
+	}
+
+	pub fn with_mip_gap(mut self, mip_gap: f64) -> Self {
+		self.mip_gap = mip_gap;
+		self
+	}
+
+	pub fn with_integral_preference(mut self, integral_preference: bool) -> Self {
+		self.integral_preference = integral_preference;
+		self
+	}
+
+	pub fn with_deterministic(mut self, deterministic: bool) -> Self {
+		self.deterministic = deterministic;
+		self
+	}
+
+	pub fn with_optima_limit(mut self, optima_limit: usize) -> Self {
+		self.optima_limit = Some(optima_limit);
+		self
+	}
+
+	pub fn with_objective_improvement_tolerance(mut self, objective_improvement_tolerance: f64) -> Self {
+		self.objective_improvement_tolerance = objective_improvement_tolerance;
+		self
+	}
+
+	pub fn with_debug_checks(mut self, debug_checks: bool) -> Self {
+		self.debug_checks = debug_checks;
+		self
+	}
+
+	pub fn with_singleton_check(mut self, singleton_check: bool) -> Self {
+		self.singleton_check = singleton_check;
+		self
+	}
+}
+
+impl fmt::Debug for SolverOptions {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.debug_struct("SolverOptions")
+			.field("method", &self.method)
+			.field("on_iteration", &self.on_iteration.as_ref().map(|_| "FnMut(usize, f64)"))
+			.field("mip_gap", &self.mip_gap)
+			.field("integral_preference", &self.integral_preference)
+			.field("deterministic", &self.deterministic)
+			.field("optima_limit", &self.optima_limit)
+			.field("objective_improvement_tolerance", &self.objective_improvement_tolerance)
+			.field("debug_checks", &self.debug_checks)
+			.field("singleton_check", &self.singleton_check)
+			.finish()
+	}
+}
+
+/// Solves Lps with a configurable method.
+///
+/// The simplex tableau and basis are kept between calls to `solve`, so
+/// solving many Lps of similar size with one Solver reuses their storage
+/// instead of allocating it anew each time.
+#[derive(Debug)]
+pub struct Solver {
+	options: SolverOptions,
+	frontier: Vec<Node>,
+	incumbent: Option<Incumbent>,
+	nodes: usize,
+	tableau_buffer: Vec<f64>,
+	basis_buffer: Vec<Option<usize>>,
+	buffer_allocations: usize,
+	/// Branch and bound stops exploring subproblems once this has passed
+	deadline: Option<Instant>,
+	/// The Lp of the last simplex solve if it ended optimal, in which case
+	/// its final tableau and basis are still in the buffers
+	warm_lp: Option<Lp>
+}
+
+/// A snapshot of a branch and bound search, taken by `Solver::save_state`
+/// and continued by `Solver::resume`.
+///
+/// It is stored as plain text through `write` and `read`, one line for
+/// the incumbent and one per open subproblem.
+#[derive(Debug, PartialEq, Clone)]
+pub struct SolverState {
+	frontier: Vec<Node>,
+	incumbent: Option<Incumbent>
+}
+
+/// An open branch-and-bound subproblem: the original Lp with the extra
+/// variable bounds leading to it, and the relaxation objective of its
+/// parent, which bounds every solution inside it.
+///
+/// Objectives are kept in maximization form (negated when minimizing) so
+/// larger is always better.
+#[derive(Debug, PartialEq, Clone)]
+struct Node {
+	bounds: Vec<(usize, BranchBound)>,
+	score: f64
+}
+
+#[derive(Debug, PartialEq, Clone)]
+enum BranchBound {
+	Upper(f64),
+	Lower(f64)
+}
+
+/// The best integer feasible solution found so far.
+#[derive(Debug, PartialEq, Clone)]
+struct Incumbent {
+	values: Vec<f64>,
+	score: f64
+}
+
+impl Solution {
+	/// Returns the distances `(to lower, to upper)` from a ranged
+	/// constraint's activity to each of its bounds.
+	///
+	/// A distance of zero means that side of the range is binding.
+	/// Returns None if there is no ranged constraint with that name or
+	/// the solution has no values.
+	pub fn range_slack(&self, constraint_name: &str) -> Option<(f64, f64)> {
+		let values = match self.values {
+			Some(ref values) => values,
+			None => return None
+		};
+
+		self.lp.ranges.iter()
+			.find(|range| self.lp.constraint_names[range.row] == constraint_name)
+			.map(|range| (values[range.range], values[range.slack]))
+	}
+
+	/// Same as `basis`, but gives the names of the basic variables, which
+	/// are either declared or slack, excess and range variables. Empty if
+	/// there is no basis.
+	pub fn basis_names(&self, lp: &Lp) -> Vec<String> {
+		match self.basis {
+			Some(ref basis) => basis.iter().map(|&col| lp.vars[col].clone()).collect(),
+			None => vec![]
+		}
+	}
+
+	/// Returns the slack of the named constraint: how far its activity is
+	/// below the constant of a `<=` row, or above that of a `>=` row.
+	///
+	/// Equality rows have no slack. A ranged constraint's row measures the
+	/// distance to its upper side and its `_range` row the distance to its
+	/// lower side. Returns None if there is no such constraint or the
+	/// solution has no values.
+	pub fn slack(&self, lp: &Lp, constraint_name: &str) -> Option<f64> {
+		let row = lp.constraint_names.iter().position(|name| name == constraint_name)?;
+
+		self.values.as_ref().map(|values| {
+			if let Some(range) = lp.ranges.iter().find(|range| range.row == row) {
+				return values[range.slack];
+			}
+			if let Some(range) = lp.ranges.iter().find(|range| range.bound_row == row) {
+				return values[range.range];
+			}
+
+			let num_structural = lp.vars.len() - lp.num_artificial_vars;
+			let activity: f64 = (0 .. num_structural).map(|col| lp.A[[row, col]] * values[col]).sum();
+			(lp.b[row] - activity).abs()
+		})
+	}
+
+	/// Returns the slack of every constraint in constraint order, as given
+	/// by `slack`. Empty if the solution has no values.
+	pub fn all_slacks(&self, lp: &Lp) -> Vec<f64> {
+		if self.values.is_none() {
+			return vec![];
+		}
+
+		lp.constraint_names.iter()
+			.map(|name| self.slack(lp, name).unwrap())
+			.collect()
+	}
+
+	/// Returns the number of declared variables minus the number of binding
+	/// constraints (zero slack, which every equality has), or zero if more
+	/// constraints bind than there are variables.
+	///
+	/// Zero means the optimum is pinned down by the constraints alone,
+	/// while a positive count leaves that many directions to the
+	/// non-negativity bounds. Each side of a ranged constraint counts on
+	/// its own. Panics if the solution has no values.
+	pub fn degrees_of_freedom(&self, lp: &Lp) -> usize {
+		if self.values.is_none() {
+			panic!("Solution has no values");
+		}
+
+		let num_structural = lp.vars.len() - lp.num_artificial_vars;
+		let scale = 1. + lp.b.iter().fold(0., |max: f64, b| max.max(b.abs()));
+		let binding = self.all_slacks(lp).iter().filter(|slack| slack.abs() <= ZERO_TOLERANCE * scale).count();
+		num_structural.saturating_sub(binding)
+	}
+
+	/// Reads the solution of the dual problem `lp.dual()` off this one,
+	/// without solving the dual.
+	///
+	/// The values are the `y_<i>_pos`/`y_<i>_neg` parts of each
+	/// constraint's dual value followed by the dual slacks (the reduced
+	/// costs), and the duals are this solution's values. If this solution
+	/// has no duals neither does the result have values: its status is
+	/// Infeasible for an unbounded Lp and Unbounded otherwise, leaving
+	/// aside Lps whose dual is infeasible as well.
+	pub fn as_dual_solution(&self, lp: &Lp) -> Solution {
+		let (A, b, c) = lp.to_equality_form();
+		// Duals are reported in the original sense, the dual Lp is over the
+		// equality form's minimization
+		let sign = if lp.optimization == Optimization::Max { -lp.reported_sign() } else { lp.reported_sign() };
+		let dual = match (&self.duals, &self.values) {
+			(Some(duals), Some(values)) => {
+				let y: Vec<f64> = duals.iter().map(|dual| sign * dual).collect();
+				let mut dual_values = vec![];
+				for &y_i in &y {
+					dual_values.push(y_i.max(0.));
+					dual_values.push((-y_i).max(0.));
+				}
+				for j in 0 .. A.cols() {
+					dual_values.push(c[j] - (0 .. A.rows()).map(|i| A[[i, j]] * y[i]).sum::<f64>());
+				}
+				let objective = b.iter().zip(y.iter()).map(|(b_i, y_i)| b_i * y_i).sum::<f64>();
+				Some((dual_values, objective, values.clone()))
+			},
+			_ => None
+		};
+
+		let status = match (&dual, &self.status) {
+			(&Some(_), _) => Status::Optimal,
+			(&None, &Status::Unbounded) => Status::Infeasible,
+			(&None, _) => Status::Unbounded
+		};
+		let (values, objective, duals) = match dual {
+			Some((values, objective, duals)) => (Some(values), Some(objective), Some(duals)),
+			None => (None, None, None)
+		};
+
+		Solution {
+			lp: lp.dual(),
+			values,
+			objective,
+			duals,
+			farkas: None,
+			iterations: self.iterations,
+			residuals: None,
+			status,
+			pivots: vec![],
+			basis: None
+		}
+	}
+
+	/// Measures how far the duals are from being feasible for the dual
+	/// problem: one residual per column `j` of the Lp, by how much its
+	/// constraint `A_j'y <= c_j` of `lp.dual()` is violated (zero if it
+	/// holds).
+	///
+	/// At an optimum every residual should be zero up to round-off, so
+	/// larger ones point at a numerical problem. Empty if the solution has
+	/// no duals.
+	pub fn dual_residuals(&self, lp: &Lp) -> Vec<f64> {
+		let duals = match self.duals {
+			Some(ref duals) => duals,
+			None => return vec![]
+		};
+
+		// As in `as_dual_solution`, the duals are brought to the sense of
+		// the equality form's minimization
+		let (A, _, c) = lp.to_equality_form();
+		let sign = if lp.optimization == Optimization::Max { -lp.reported_sign() } else { lp.reported_sign() };
+		(0 .. A.cols())
+			.map(|j| ((0 .. A.rows()).map(|i| A[[i, j]] * sign * duals[i]).sum::<f64>() - c[j]).max(0.))
+			.collect()
+	}
+
+	/// Writes the dual value of each constraint, one `name: value` pair
+	/// per line in constraint order.
+	///
+	/// Nothing is written if the solution has no dual values (e.g. the
+	/// problem was infeasible or unbounded).
+	pub fn write_duals<W: Write>(&self, lp: &Lp, writer: &mut W) -> io::Result<()> {
+		if let Some(ref duals) = self.duals {
+			for (name, dual) in lp.constraint_names.iter().zip(duals.iter()) {
+				writeln!(writer, "{}: {}", name, dual)?;
+			}
+		}
+
+		Ok(())
+	}
+
+	/// Splits the objective value into each variable's contribution
+	/// `coefficient * value`, largest magnitude first and NaN ones last.
+	///
+	/// Variables without an objective coefficient are left out, so the
+	/// contributions add up to the objective. Empty if the solution has no
+	/// values.
+	pub fn objective_breakdown(&self, lp: &Lp) -> Vec<(String, f64)> {
+		let values = match self.values {
+			Some(ref values) => values,
+			None => return vec![]
+		};
+
+		let mut breakdown: Vec<(String, f64)> = lp.vars.iter().zip(lp.c.iter()).zip(values.iter())
+			.filter(|&((_, &coeff), _)| coeff != 0.)
+			.map(|((name, coeff), value)| (name.clone(), lp.reported_sign() * coeff * value))
+			.collect();
+		breakdown.sort_by(|a, b| b.1.abs().partial_cmp(&a.1.abs()).unwrap_or_else(|| a.1.is_nan().cmp(&b.1.is_nan())));
+		breakdown
+	}
+
+	/// Lists the declared variables whose value exceeds `epsilon` in
+	/// magnitude, in declaration order: the decisions the solution acts on.
+	///
+	/// Slack and excess variables are left out. Empty if the solution has
+	/// no values.
+	pub fn support(&self, lp: &Lp, epsilon: f64) -> Vec<String> {
+		let values = match self.values {
+			Some(ref values) => values,
+			None => return vec![]
+		};
+
+		let num_structural = lp.vars.len() - lp.num_artificial_vars;
+		lp.vars[.. num_structural].iter().zip(values.iter())
+			.filter(|&(_, value)| value.abs() > epsilon)
+			.map(|(name, _)| name.clone())
+			.collect()
+	}
+
+	/// Lists the declared variables whose value lies outside their bounds
+	/// by more than `epsilon`, with the distance to the nearest bound, in
+	/// declaration order.
+	///
+	/// Every variable is bounded below by zero and a binary one above by
+	/// one; a constraint on a single variable (like `x <= 4`, written as a
+	/// constraint since there is no other way) bounds it as well. Empty if
+	/// the solution has no values.
+	pub fn bounds_violations(&self, lp: &Lp, epsilon: f64) -> Vec<(String, f64)> {
+		let values = match self.values {
+			Some(ref values) => values,
+			None => return vec![]
+		};
+
+		let (lower, upper) = lp.variable_bounds();
+		(0 .. lower.len())
+			.map(|col| (col, (lower[col] - values[col]).max(values[col] - upper[col])))
+			.filter(|&(_, violation)| violation > epsilon)
+			.map(|(col, violation)| (lp.vars[col].clone(), violation))
+			.collect()
+	}
+
+	/// Lists the declared variables whose value is within `epsilon` of one
+	/// of their bounds, which are worked out as in `bounds_violations`, in
+	/// declaration order. A variable fixed by its bounds is listed as at
+	/// its lower one. Empty if the solution has no values.
+	pub fn at_bound_variables(&self, lp: &Lp, epsilon: f64) -> Vec<(String, Bound)> {
+		let values = match self.values {
+			Some(ref values) => values,
+			None => return vec![]
+		};
+
+		let (lower, upper) = lp.variable_bounds();
+		(0 .. lower.len())
+			.filter_map(|col| if (values[col] - lower[col]).abs() <= epsilon {
+				Some((lp.vars[col].clone(), Bound::Lower))
+			} else if (values[col] - upper[col]).abs() <= epsilon {
+				Some((lp.vars[col].clone(), Bound::Upper))
+			} else {
+				None
+			})
+			.collect()
+	}
+
+	/// Tells whether the solution stays optimal once `constraint` is added
+	/// to the Lp, without solving again: adding a constraint can only make
+	/// the objective worse, so if the current values satisfy it (within
+	/// `1e-9` times the magnitude of its constant) they remain optimal.
+	///
+	/// False unless the solution is optimal. Panics if the constraint uses
+	/// a variable the Lp doesn't have.
+	pub fn would_remain_optimal(&self, lp: &Lp, constraint: &Constraint) -> bool {
+		const TOLERANCE: f64 = 1e-9;
+
+		let values = match self.values {
+			Some(ref values) if self.status == Status::Optimal => values,
+			_ => return false
+		};
+
+		let activity: f64 = constraint.variables.iter()
+			.map(|var| match lp.vars.iter().position(|name| *name == var.name) {
+				Some(col) => var.coefficient * values[col],
+				None => panic!("Unknown variable \"{}\" in constraint \"{}\"", var.name, constraint.name)
+			})
+			.sum();
+		let tolerance = TOLERANCE * (1. + constraint.constant.abs());
+		match constraint.relation {
+			Relation::Equal => (activity - constraint.constant).abs() <= tolerance,
+			Relation::LessThanOrEqual => activity <= constraint.constant + tolerance,
+			Relation::GreaterThanOrEqual => activity >= constraint.constant - tolerance,
+			Relation::Range(lower) => activity >= lower - tolerance && activity <= constraint.constant + tolerance
+		}
+	}
+
+	/// Panics unless the solution is optimal with an objective within
+	/// `epsilon` of `expected`, naming the status or the objective found.
+	/// Meant for tests:
+	///
+	/// ```
+	/// use rulp::builder::{Builder, BuilderBase};
+	/// use rulp::parser::{Parser, ParserBase};
+	/// use rulp::solver::{SimplexSolver, SolverBase};
+	///
+	/// let lp = Parser::lp_from_text("var a; maximize obj: 2*a; subject to cap: a <= 3;", Builder::new());
+	/// SimplexSolver::new(lp).solve().assert_optimal_value(6., 1e-9);
+	/// ```
+	pub fn assert_optimal_value(&self, expected: f64, epsilon: f64) {
+		let objective = match (&self.status, self.objective) {
+			(&Status::Optimal, Some(objective)) => objective,
+			(status, _) => panic!("Expected an optimal objective of {}, but the solution is {:?}", expected, status)
+		};
+		if (objective - expected).abs() > epsilon || objective.is_nan() {
+			panic!("Expected an optimal objective of {} (within {}), but it is {}, off by {}",
+				expected, epsilon, objective, objective - expected);
+		}
+	}
+
+	/// Writes the value of every user-defined variable as CSV, with a
+	/// `variable,value` header.
+	///
+	/// Nothing is written if the solution has no values.
+	pub fn to_csv<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+		self.write_csv(writer, false, |value| format!("{}", value))
+	}
+
+	/// Same as `to_csv`, but leaves out the variables whose value is (near)
+	/// zero, which keeps reports of sparse solutions short.
+	pub fn to_csv_nonzero<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+		self.write_csv(writer, true, |value| format!("{}", value))
+	}
+
+	/// Same as `to_csv`, but formats every value with a fixed number of
+	/// decimals so floating point noise like `4.999999999` is rounded away.
+	///
+	/// # Examples
+	/// ```
+	/// use rulp::lp::Lp;
+	/// use rulp::solver::Solver;
+	///
+	/// let solution = Solver::new().solve(&Lp::random(2, 1, 3));
+	/// let mut csv = vec![];
+	/// solution.to_csv_with_precision(&mut csv, 2).unwrap();
+	///
+	/// let csv = String::from_utf8(csv).unwrap();
+	/// assert!(csv.starts_with("variable,value\nx_0,"));
+	/// ```
+	pub fn to_csv_with_precision<W: Write>(&self, writer: &mut W, decimals: usize) -> io::Result<()> {
+		self.write_csv(writer, false, |value| {
+			let formatted = format!("{:.*}", decimals, value);
+			if formatted.starts_with('-') && formatted[1 ..].chars().all(|c| c == '0' || c == '.') {
+				formatted[1 ..].to_string()			// Don't print tiny negative values as -0.00
+			} else {
+				formatted
+			}
+		})
+	}
+
+	/// Collects the post-optimality data into a single JSON document with
+	/// the keys `status`, `objective`, `values`, `duals`, `reduced_costs`,
+	/// `objective_ranges` and `rhs_ranges`.
+	///
+	/// `values` and `reduced_costs` map each declared variable to its
+	/// value and reduced cost `c_j - A_j^T y`, `duals` each constraint to
+	/// its dual value. The ranges are the sensitivity analysis of the
+	/// optimal basis: `objective_ranges` maps each declared variable to the
+	/// `[lowest, highest]` objective coefficient, and `rhs_ranges` each
+	/// constraint to the right hand side, for which the basis stays
+	/// optimal (or feasible), one at a time. An unbounded side is `null`.
+	/// Data the solution doesn't have (e.g. when it is infeasible, or has
+	/// no basis as with the interior point method) is written as `null`.
+	///
+	/// # Examples
+	/// ```
+	/// use rulp::lp::Lp;
+	/// use rulp::solver::Solver;
+	///
+	/// let lp = Lp::random(2, 1, 3);
+	/// let report = Solver::new().solve(&lp).to_report_json(&lp);
+	/// assert!(report.starts_with("{\"status\":\"Optimal\",\"objective\":"));
+	/// ```
+	pub fn to_report_json(&self, lp: &Lp) -> String {
+		let num_structural = lp.vars.len() - lp.num_artificial_vars;
+		let object = |names: &[String], values: &[f64]| -> String {
+			let fields: Vec<String> = names.iter().zip(values.iter())
+				.map(|(name, &value)| format!("{}:{}", json_string(name), json_number(value)))
+				.collect();
+			format!("{{{}}}", fields.join(","))
+		};
+
+		let values = match self.values {
+			Some(ref values) => object(&lp.vars[.. num_structural], values),
+			None => "null".to_string()
+		};
+		let (duals, reduced_costs) = match self.duals {
+			Some(ref duals) => {
+				let reduced_costs: Vec<f64> = (0 .. num_structural)
+					.map(|col| lp.reported_sign() * lp.c[col] - (0 .. lp.A.rows()).map(|row| lp.A[[row, col]] * duals[row]).sum::<f64>())
+					.collect();
+				(object(&lp.constraint_names, duals), object(&lp.vars[.. num_structural], &reduced_costs))
+			},
+			None => ("null".to_string(), "null".to_string())
+		};
+
+		let ranges = |names: &[String], ranges: &[SensitivityRange]| -> String {
+			let fields: Vec<String> = names.iter().zip(ranges.iter())
+				.map(|(name, &(lower, upper))| format!("{}:[{},{}]", json_string(name), json_number(lower), json_number(upper)))
+				.collect();
+			format!("{{{}}}", fields.join(","))
+		};
+		let (objective_ranges, rhs_ranges) = match self.sensitivity_ranges(lp) {
+			Some((objective, rhs)) => (ranges(&lp.vars[.. num_structural], &objective), ranges(&lp.constraint_names, &rhs)),
+			None => ("null".to_string(), "null".to_string())
+		};
+
+		format!(
+			"{{\"status\":\"{:?}\",\"objective\":{},\"values\":{},\"duals\":{},\"reduced_costs\":{},\"objective_ranges\":{},\"rhs_ranges\":{}}}",
+			self.status,
+			self.objective.map_or("null".to_string(), json_number),
+			values,
+			duals,
+			reduced_costs,
+			objective_ranges,
+			rhs_ranges
+		)
+	}
+
+	// Ranges of each structural objective coefficient and each right hand
+	// side keeping the optimal basis optimal and feasible, in that order.
+	// Changing c_j by d changes the reduced costs r = c - c_B B^-1 A by
+	// d on column j if it is nonbasic, and by -d (B^-1 A)_k on every
+	// nonbasic column if it is basic in row k. Changing b_i by d changes
+	// the basic values by d times column i of B^-1.
+	fn sensitivity_ranges(&self, lp: &Lp) -> Option<(Vec<SensitivityRange>, Vec<SensitivityRange>)> {
+		let basis = match self.basis {
+			Some(ref basis) if basis.len() == lp.A.rows() => basis,
+			_ => return None
+		};
+		let (m, n) = (lp.A.rows(), lp.A.cols());
+		let num_structural = n - lp.num_artificial_vars;
+		let B = lp.A.select_cols(basis);
+		let B_inv = B.inverse().ok()?;
+
+		let basic_values: Vec<f64> = (0 .. m).map(|k| (0 .. m).map(|i| B_inv[[k, i]] * lp.b[i]).sum()).collect();
+		let rhs = (0 .. m).map(|i| {
+			let (mut lower, mut upper) = (f64::NEG_INFINITY, f64::INFINITY);
+			for (k, &value) in basic_values.iter().enumerate() {
+				let change = B_inv[[k, i]];
+				if change > ZERO_TOLERANCE {
+					lower = lower.max(-value / change);
+				} else if change < -ZERO_TOLERANCE {
+					upper = upper.min(-value / change);
+				}
+			}
+			(lp.b[i] + lower, lp.b[i] + upper)
+		}).collect();
+
+		// Optimality is sense * r <= 0 on every nonbasic column
+		let sense = if lp.optimization == Optimization::Max { 1. } else { -1. };
+		let y: Vec<f64> = (0 .. m).map(|i| (0 .. m).map(|k| lp.c[basis[k]] * B_inv[[k, i]]).sum()).collect();
+		let reduced_cost = |col: usize| lp.c[col] - (0 .. m).map(|i| y[i] * lp.A[[i, col]]).sum::<f64>();
+		let objective = (0 .. num_structural).map(|col| {
+			let (lower, upper) = match basis.iter().position(|&basic| basic == col) {
+				None if sense > 0. => (f64::NEG_INFINITY, -reduced_cost(col)),
+				None => (-reduced_cost(col), f64::INFINITY),
+				Some(k) => {
+					let (mut lower, mut upper) = (f64::NEG_INFINITY, f64::INFINITY);
+					for other in (0 .. n).filter(|other| !basis.contains(other)) {
+						let rate: f64 = (0 .. m).map(|i| B_inv[[k, i]] * lp.A[[i, other]]).sum();
+						if sense * rate > ZERO_TOLERANCE {
+							lower = lower.max(reduced_cost(other) / rate);
+						} else if sense * rate < -ZERO_TOLERANCE {
+							upper = upper.min(reduced_cost(other) / rate);
+						}
+					}
+					(lower, upper)
+				}
+			};
+			// The reported coefficients of a negated Lp are the negated ones
+			if lp.objective_negated {
+				(-(lp.c[col] + upper), -(lp.c[col] + lower))
+			} else {
+				(lp.c[col] + lower, lp.c[col] + upper)
+			}
+		}).collect();
+
+		Some((objective, rhs))
+	}
+
+	fn write_csv<W: Write, F: Fn(f64) -> String>(&self, writer: &mut W, skip_zero: bool, format_value: F) -> io::Result<()> {
+		if let Some(ref values) = self.values {
+			writeln!(writer, "variable,value")?;
+			for (var, &value) in self.lp.vars.iter().zip(values).take(values.len() - self.lp.num_artificial_vars) {
+				if skip_zero && value.abs() <= ZERO_TOLERANCE {
+					continue;
+				}
+				writeln!(writer, "{},{}", var, format_value(value))?;
+			}
+		}
+
+		Ok(())
+	}
+}
+
+fn json_string(value: &str) -> String {
+	format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+// JSON has no infinities or NaN
+fn json_number(value: f64) -> String {
+	if value.is_finite() { format!("{}", value) } else { "null".to_string() }
 }
 
 impl fmt::Display for Solution {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-    	writeln!(f, "").unwrap();
+    	writeln!(f).unwrap();
         match self.status {
-            Status::Optimal => {
+            Status::Optimal | Status::NearOptimal => {
                 match self.objective {
                     None => {
 
+                    },
+                    Some(ref obj) if self.status == Status::NearOptimal => {
+                        writeln!(f, "Near optimal objective: {:}", obj).unwrap();
                     },
                     Some(ref obj) => {
                         writeln!(f, "Optimal objective: {:}", obj).unwrap();
@@ -54,8 +839,8 @@ impl fmt::Display for Solution {
 
                     },
                     Some(ref vals) => {
-                        for i in 0 .. vals.len() - self.lp.num_artificial_vars {
-                            writeln!(f, "{:}: {:?}", self.lp.vars[i],vals[i]).unwrap();
+                        for (var, val) in self.lp.vars.iter().zip(vals).take(vals.len() - self.lp.num_artificial_vars) {
+                            writeln!(f, "{:}: {:?}", var, val).unwrap();
                         }
                     }
                 }
@@ -65,6 +850,18 @@ impl fmt::Display for Solution {
             },
             Status::Unbounded => {
                 writeln!(f, "Unbounded").unwrap();
+            },
+            Status::Interrupted => {
+                writeln!(f, "Interrupted").unwrap();
+                if let (Some(obj), Some(vals)) = (self.objective, &self.values) {
+                    writeln!(f, "Best objective found: {:}", obj).unwrap();
+                    for (var, val) in self.lp.vars.iter().zip(vals).take(vals.len() - self.lp.num_artificial_vars) {
+                        writeln!(f, "{:}: {:?}", var, val).unwrap();
+                    }
+                }
+            },
+            Status::NumericalError(ref invariant) => {
+                writeln!(f, "Numerical error: {}", invariant).unwrap();
             }
         }
 
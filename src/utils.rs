@@ -6,14 +6,14 @@ use std::io::prelude::*;
 /// Prints out a matrix into a well spaced, easy to read form
 pub fn print_matrix(mat: &Matrix<f64>) {
 	unsafe{
-		println!("");
+		println!();
 		for r in 0 .. mat.rows() {
 			for c in 0 .. mat.cols() - 1{
 				print!("{:>6.3}, ", *mat.get_unchecked([r, c]));
 			}
 			println!("{:>6.3}", *mat.get_unchecked([r, mat.cols() -1]));
 		}
-		println!("");	
+		println!();	
 	}
 }
 
@@ -22,4 +22,32 @@ pub fn read_file_contents(file: &mut File) -> String {
 	let mut contents = String::new();
 	file.read_to_string(&mut contents).expect("Failed to read file!");
 	contents
-}
\ No newline at end of file
+}
+
+/// Small xorshift generator so randomized problems are reproducible
+/// from a seed without pulling in an external crate
+pub struct XorShiftRng {
+	state: u64
+}
+
+impl XorShiftRng {
+	pub fn new(seed: u64) -> Self {
+		// A zero state would only ever produce zeros
+		XorShiftRng { state: seed ^ 0x9E37_79B9_7F4A_7C15 }
+	}
+
+	pub fn next_u64(&mut self) -> u64 {
+		let mut x = self.state;
+		x ^= x << 13;
+		x ^= x >> 7;
+		x ^= x << 17;
+		self.state = x;
+		x
+	}
+
+	/// Returns a float uniformly distributed in [low, high)
+	pub fn next_f64(&mut self, low: f64, high: f64) -> f64 {
+		let unit = (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64;
+		low + unit * (high - low)
+	}
+}
@@ -1,14 +1,21 @@
+#![allow(non_snake_case)]		// Allowed to follow LP standard naming procedure
+#![allow(clippy::useless_vec)]		// The original tests spell expected values as vectors
+
+#[macro_use]
 extern crate rulinalg;
 extern crate assert_approx_eq;
 extern crate rulp;
 
 #[allow(unused_imports)]
 use assert_approx_eq::*;
-use rulp::builder::{Builder, BuilderBase};
-use rulp::parser::{Parser, ParserBase};
-use rulp::solver::{SolverBase, SimplexSolver};
-use rulp::solver::Status;
-use std::fs::File;
+use rulp::builder::{Builder, BuilderBase, Constraint, Objective, Relation, Variable};
+use rulp::lp::{ExportError, Lp, Optimization, ProblemType, ValidationError, VariableKind};
+use rulp::parser::{NetworkParser, Parser, ParserBase, ParserOptions};
+use rulp::solver::{SolverBase, SimplexSolver, Solver, SolverOptions, SolverMethod, SolverState};
+use rulp::solver::{Bound, Status};
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{self, Cursor};
 
 #[test]
 fn minimize_text_test() {
@@ -136,4 +143,2907 @@ fn infeasible_file_test() {
 	assert_eq!(solution.objective, None);
 	assert_eq!(solution.values, None);
 	assert_eq!(solution.status, Status::Infeasible);
-}
\ No newline at end of file
+}
+#[test]
+fn random_lp_test() {
+	let lp = Lp::random(6, 4, 7);
+	assert_eq!(lp, Lp::random(6, 4, 7));
+
+	let solver = SimplexSolver::new(lp);
+	let solution = solver.solve();
+
+	assert_eq!(solution.status, Status::Optimal);
+	assert!(solution.objective.unwrap().is_finite());
+	assert!(solution.objective.unwrap() > 0.);
+}
+
+#[test]
+fn advertisement_duals_test() {
+	let builder = Builder::new();
+	let mut input_file = File::open("./tests/test_files/advertisement_example.lp").unwrap();
+	let lp = Parser::lp_from_file(&mut input_file, builder);
+
+	let solver = SimplexSolver::new(lp.clone());
+	let solution = solver.solve();
+
+	let mut output = vec![];
+	solution.write_duals(&lp, &mut output).unwrap();
+	let output = String::from_utf8(output).unwrap();
+
+	let expected = [
+		("constraint_1", 1180. / 23.),
+		("constraint_2", 272000. / 23.),
+		("constraint_3", 60000. / 23.),
+		("constraint_4", 0.),
+	];
+	let lines: Vec<&str> = output.lines().collect();
+	assert_eq!(lines.len(), expected.len());
+	for (line, &(name, dual)) in lines.iter().zip(expected.iter()) {
+		let mut parts = line.split(": ");
+		assert_eq!(parts.next().unwrap(), name);
+		assert_approx_eq!(parts.next().unwrap().parse::<f64>().unwrap(), dual);
+	}
+}
+
+#[test]
+fn advertisement_dual_residuals_test() {
+	let mut input_file = File::open("./tests/test_files/advertisement_example.lp").unwrap();
+	let lp = Parser::lp_from_file(&mut input_file, Builder::new());
+
+	let mut solution = SimplexSolver::new(lp.clone()).solve();
+	let residuals = solution.dual_residuals(&lp);
+	assert_eq!(residuals.len(), lp.c.len());
+	assert!(residuals.iter().all(|&residual| residual <= 1e-9 * (1. + lp.c.iter().fold(0., |max: f64, c| max.max(c.abs())))));
+
+	// Zero duals price nothing, so each profitable variable's dual
+	// constraint is violated by its profit
+	solution.duals = Some(vec![0.; lp.b.len()]);
+	let residuals = solution.dual_residuals(&lp);
+	for (&residual, &profit) in residuals.iter().zip(lp.c.iter()) {
+		assert_approx_eq!(residual, profit.max(0.));
+	}
+
+	solution.duals = None;
+	assert!(solution.dual_residuals(&lp).is_empty());
+}
+
+#[test]
+#[allow(deprecated)]			// assert_matrix_eq! calls str::trim_right
+fn minimax_objective_test() {
+	let mut builder = Builder::new();
+	builder.add_variable(Variable { name: "a".to_string(), coefficient: 0. });
+	builder.add_variable(Variable { name: "b".to_string(), coefficient: 0. });
+	builder.add_constraint(Constraint {
+		name: "total".to_string(),
+		variables: vec![
+			Variable { name: "a".to_string(), coefficient: 1. },
+			Variable { name: "b".to_string(), coefficient: 1. },
+		],
+		constant: 10.,
+		relation: Relation::Equal
+	});
+
+	// minimize max(a, 2b) subject to a + b == 10
+	builder.add_minimax_objective(&[
+		vec![Variable { name: "a".to_string(), coefficient: 1. }],
+		vec![Variable { name: "b".to_string(), coefficient: 2. }],
+	]);
+	let lp = builder.build_lp();
+
+	let expected_A = matrix![
+		 1.,  1., 0.,  0.,  0.,  0.;
+		-1.,  0., 1., -1., -1.,  0.;
+		 0., -2., 1., -1.,  0., -1.
+	];
+	assert_matrix_eq!(lp.A, expected_A);
+	assert_eq!(lp.b, vec![10., 0., 0.]);
+	assert_eq!(lp.c, vec![0., 0., 1., -1., 0., 0.]);
+	assert_eq!(lp.constraint_names, vec!["total", "minimax_0", "minimax_1"]);
+
+	let solution = SimplexSolver::new(lp).solve();
+	let values = solution.values.unwrap();
+	assert_eq!(solution.status, Status::Optimal);
+	assert_approx_eq!(values[0], 20. / 3.);
+	assert_approx_eq!(values[1], 10. / 3.);
+	assert_approx_eq!(solution.objective.unwrap(), 20. / 3.);
+}
+
+#[test]
+fn negative_minimax_objective_test() {
+	let mut builder = Builder::new();
+	builder.add_variable(Variable { name: "a".to_string(), coefficient: 0. });
+	builder.add_constraint(Constraint::from_terms("a_min", vec![("a".to_string(), 1.)], Relation::GreaterThanOrEqual, 1.));
+
+	// minimize max(-a, -2a) subject to 1 <= a <= 3, reached at a = 3
+	builder.add_constraint(Constraint::from_terms("a_max", vec![("a".to_string(), 1.)], Relation::LessThanOrEqual, 3.));
+	builder.add_minimax_objective(&[
+		vec![Variable { name: "a".to_string(), coefficient: -1. }],
+		vec![Variable { name: "a".to_string(), coefficient: -2. }],
+	]);
+	let lp = builder.build_lp();
+
+	let solution = SimplexSolver::new(lp).solve();
+	assert_eq!(solution.status, Status::Optimal);
+	assert_approx_eq!(solution.values.unwrap()[0], 3.);
+	assert_approx_eq!(solution.objective.unwrap(), -3.);
+}
+
+#[test]
+#[should_panic]
+fn minimax_name_collision_test() {
+	let mut builder = Builder::new();
+	builder.add_variable(Variable { name: "minimax_pos".to_string(), coefficient: 0. });
+	builder.add_minimax_objective(&[vec![Variable { name: "minimax_pos".to_string(), coefficient: 1. }]]);
+}
+
+#[test]
+fn clone_and_compare_lp_test() {
+	let builder = Builder::new();
+	let mut input_file = File::open("./tests/test_files/advertisement_example.lp").unwrap();
+	let lp = Parser::lp_from_file(&mut input_file, builder);
+
+	let mut copy = lp.clone();
+	assert_eq!(copy, lp);
+	assert!(copy.approx_eq(&lp, 0.));
+
+	copy.b[0] += 1e-9;
+	assert!(copy != lp);
+	assert!(copy.approx_eq(&lp, 1e-6));
+	assert!(!copy.approx_eq(&lp, 1e-12));
+
+	copy.vars[0] = "tv".to_string();
+	assert!(!copy.approx_eq(&lp, 1e-6));
+}
+
+#[test]
+fn range_slack_test() {
+	let text_problem = "
+		var a;
+		var b;
+
+		maximize total: a + b;
+		subject to range: 2 <= a + b <= 6;
+		subject to a_cap: a <= 4;
+	";
+	let lp = Parser::lp_from_text(text_problem, Builder::new());
+	let solution = SimplexSolver::new(lp).solve();
+
+	assert_approx_eq!(solution.objective.unwrap(), 6.);
+	let (to_lower, to_upper) = solution.range_slack("range").unwrap();
+	assert_approx_eq!(to_lower, 4.);
+	assert_approx_eq!(to_upper, 0.);
+	assert_eq!(solution.range_slack("a_cap"), None);
+
+	let text_problem = "
+		var a;
+		var b;
+
+		minimize total: a + b;
+		subject to range: 2 <= a + b <= 6;
+	";
+	let lp = Parser::lp_from_text(text_problem, Builder::new());
+	let solution = SimplexSolver::new(lp).solve();
+
+	assert_approx_eq!(solution.objective.unwrap(), 2.);
+	let (to_lower, to_upper) = solution.range_slack("range").unwrap();
+	assert_approx_eq!(to_lower, 0.);
+	assert_approx_eq!(to_upper, 4.);
+}
+
+#[test]
+fn interior_point_matches_simplex_test() {
+	let builder = Builder::new();
+	let mut input_file = File::open("./tests/test_files/advertisement_example.lp").unwrap();
+	let advertisement = Parser::lp_from_file(&mut input_file, builder);
+
+	let mut interior_point = Solver::with_options(SolverOptions {
+		method: SolverMethod::InteriorPoint,
+		..SolverOptions::default()
+	});
+	let mut problems = vec![advertisement];
+	problems.push(Lp::random(10, 8, 3));
+	problems.push(Lp::random(20, 15, 4));
+
+	for lp in problems {
+		let simplex_solution = Solver::new().solve(&lp);
+		let solution = interior_point.solve(&lp);
+		let expected = simplex_solution.objective.unwrap();
+
+		assert_eq!(solution.status, Status::Optimal);
+		assert!(solution.iterations > 0);
+		assert!((solution.objective.unwrap() - expected).abs() <= 1e-6 * (1. + expected.abs()));
+
+		let residuals = solution.residuals.unwrap();
+		assert!(residuals.primal < 1e-6);
+		assert!(residuals.dual < 1e-6);
+	}
+}
+
+#[test]
+fn interior_point_dependent_rows_test() {
+	let mut interior_point = Solver::with_options(SolverOptions::default().with_method(SolverMethod::InteriorPoint));
+	let lp = Parser::lp_from_text("
+		var a;
+		var b;
+		maximize obj: a + b;
+		subject to c1: a + b == 4;
+		subject to c2: a + b == 4;
+		subject to c3: a <= 3;
+	", Builder::new());
+
+	let solution = interior_point.solve(&lp);
+	assert_eq!(solution.status, Status::Optimal);
+	assert!((solution.objective.unwrap() - 4.).abs() < 1e-6);
+	// The duplicate gets no dual, and the duals are the simplex's ones
+	let duals = solution.duals.unwrap();
+	assert_eq!(duals.len(), 3);
+	assert_eq!(duals[1], 0.);
+	assert!((duals[0] - 1.).abs() < 1e-6);
+
+	let contradicting = Parser::lp_from_text("
+		var a;
+		var b;
+		maximize obj: a + b;
+		subject to c1: a + b == 4;
+		subject to c2: 2*a + 2*b == 9;
+	", Builder::new());
+	assert_eq!(interior_point.solve(&contradicting).status, Status::Infeasible);
+}
+
+#[test]
+#[allow(deprecated)]			// assert_matrix_eq! calls str::trim_right
+fn equality_form_test() {
+	let builder = Builder::new();
+	let mut input_file = File::open("./tests/test_files/advertisement_example.lp").unwrap();
+	let lp = Parser::lp_from_file(&mut input_file, builder);
+
+	let (A, b, c) = lp.to_equality_form();
+
+	let expected_A = matrix![
+		2000.,  600., 300., 1., 0., 0., 0.;
+		   0.,    1.,   0., 0., 1., 0., 0.;
+		  -1.,   -1.,   1., 0., 0., 1., 0.;
+		  -9.,    1.,   1., 0., 0., 0., 1.
+	];
+	assert_matrix_eq!(A, expected_A);
+	assert_eq!(b, vec![18200., 10., 0., 0.]);
+	assert_eq!(c, vec![-100000., -40000., -18000., 0., 0., 0., 0.]);
+}
+
+#[test]
+fn iteration_callback_test() {
+	use std::cell::RefCell;
+	use std::rc::Rc;
+
+	let builder = Builder::new();
+	let mut input_file = File::open("./tests/test_files/radiation_example.lp").unwrap();
+	let lp = Parser::lp_from_file(&mut input_file, builder);
+
+	let calls = Rc::new(RefCell::new(vec![]));
+	let recorded = calls.clone();
+	let mut solver = Solver::with_options(SolverOptions {
+		on_iteration: Some(Box::new(move |iteration, objective| {
+			recorded.borrow_mut().push((iteration, objective));
+		})),
+		..SolverOptions::default()
+	});
+	let solution = solver.solve(&lp);
+
+	let calls = calls.borrow();
+	assert!(solution.iterations > 0);
+	assert_eq!(calls.len(), solution.iterations);
+	for (i, &(iteration, _)) in calls.iter().enumerate() {
+		assert_eq!(iteration, i + 1);
+	}
+	assert_approx_eq!(calls.last().unwrap().1, solution.objective.unwrap());
+}
+
+#[test]
+fn validate_test() {
+	let text_problem = "
+		var a;
+		var b;
+
+		maximize total: a + b;
+		subject to range: 2 <= a + b <= 6;
+		subject to a_cap: a <= 4;
+	";
+	let lp = Parser::lp_from_text(text_problem, Builder::new());
+	assert_eq!(lp.validate(), Ok(()));
+
+	let mut bad = lp.clone();
+	bad.b.push(1.);
+	assert_eq!(bad.validate(), Err(ValidationError::RowCountMismatch { rows: 3, b: 4 }));
+
+	let mut bad = lp.clone();
+	bad.c.pop();
+	assert_eq!(bad.validate(), Err(ValidationError::ColumnCountMismatch { cols: 5, c: 4 }));
+
+	let mut bad = lp.clone();
+	bad.vars.pop();
+	assert_eq!(bad.validate(), Err(ValidationError::VariableCountMismatch { vars: 4, cols: 5 }));
+
+	let mut bad = lp.clone();
+	bad.constraint_names.pop();
+	assert_eq!(bad.validate(), Err(ValidationError::ConstraintCountMismatch { constraint_names: 2, rows: 3 }));
+
+	let mut bad = lp.clone();
+	bad.num_artificial_vars = 6;
+	assert_eq!(bad.validate(), Err(ValidationError::ArtificialCountMismatch { num_artificial_vars: 6, vars: 5 }));
+
+	let mut bad = lp.clone();
+	bad.ranges[0].bound_row = 3;
+	assert_eq!(bad.validate(), Err(ValidationError::InvalidRange { row: 0 }));
+
+	let mut bad = lp.clone();
+	bad.kinds.pop();
+	assert_eq!(bad.validate(), Err(ValidationError::KindCountMismatch { kinds: 4, cols: 5 }));
+}
+
+// Leaves set_variable_kind to the trait's default
+struct ContinuousBuilder(Builder);
+
+impl BuilderBase for ContinuousBuilder {
+	fn new() -> Self {
+		ContinuousBuilder(Builder::new())
+	}
+
+	fn add_variable(&mut self, variable: Variable) {
+		self.0.add_variable(variable);
+	}
+
+	fn add_constraint(&mut self, constraint: Constraint) {
+		self.0.add_constraint(constraint);
+	}
+
+	fn add_objective(&mut self, objective: Objective) {
+		self.0.add_objective(objective);
+	}
+
+	fn build_lp(&mut self) -> Lp {
+		self.0.build_lp()
+	}
+}
+
+#[test]
+fn default_variable_kind_test() {
+	let lp = Parser::lp_from_text("var a; maximize obj: a; subject to cap: a <= 2;", ContinuousBuilder::new());
+	assert_eq!(lp.kinds, vec![VariableKind::Continuous; 2]);
+
+	let mut builder = ContinuousBuilder::new();
+	builder.add_variable(Variable { name: "a".to_string(), coefficient: 0. });
+	builder.set_variable_kind("a", VariableKind::Continuous);
+}
+
+#[test]
+#[should_panic]
+fn default_variable_kind_integer_test() {
+	Parser::lp_from_text("var a integer; maximize obj: a; subject to cap: a <= 2;", ContinuousBuilder::new());
+}
+
+#[test]
+fn mip_gap_test() {
+	let text_problem = "
+		var a binary;
+		var b binary;
+		var c binary;
+		var d binary;
+
+		maximize value: 8*a + 11*b + 6*c + 4*d;
+		subject to weight: 5*a + 7*b + 4*c + 3*d <= 14;
+	";
+	let lp = Parser::lp_from_text(text_problem, Builder::new());
+
+	let mut exact = Solver::new();
+	let optimum = exact.solve_mip(&lp);
+	assert_eq!(optimum.status, Status::Optimal);
+	assert_approx_eq!(optimum.objective.unwrap(), 21.);
+	assert_eq!(exact.relative_gap(), Some(0.));
+
+	// The optimal incumbent is found before it is proven optimal
+	let mut early = Solver::with_options(SolverOptions { mip_gap: 0.05, ..SolverOptions::default() });
+	let solution = early.solve_mip(&lp);
+	let gap = early.relative_gap().unwrap();
+	assert_approx_eq!(solution.objective.unwrap(), 21.);
+	assert!(gap > 0. && gap <= 0.05);
+
+	// A looser target settles for a near-optimal incumbent
+	let mut loose = Solver::with_options(SolverOptions { mip_gap: 0.5, ..SolverOptions::default() });
+	let solution = loose.solve_mip(&lp);
+	let gap = loose.relative_gap().unwrap();
+	assert!(solution.objective.unwrap() < 21.);
+	assert!(gap > 0. && gap <= 0.5);
+	for value in &solution.values.unwrap()[.. 4] {
+		assert_approx_eq!(*value, value.round());
+	}
+}
+
+#[test]
+fn csv_precision_test() {
+	let text_problem = "
+		var a;
+		var b;
+
+		maximize total: a + b;
+		subject to a_cap: 3*a <= 2;
+		subject to b_cap: b <= 5;
+	";
+	let lp = Parser::lp_from_text(text_problem, Builder::new());
+	let solution = Solver::new().solve(&lp);
+
+	let mut csv = vec![];
+	solution.to_csv(&mut csv).unwrap();
+	assert_eq!(String::from_utf8(csv).unwrap(), format!("variable,value\na,{}\nb,5\n", 2. / 3.));
+
+	let mut csv = vec![];
+	solution.to_csv_with_precision(&mut csv, 3).unwrap();
+	assert_eq!(String::from_utf8(csv).unwrap(), "variable,value\na,0.667\nb,5.000\n");
+
+	let mut csv = vec![];
+	solution.to_csv_with_precision(&mut csv, 0).unwrap();
+	assert_eq!(String::from_utf8(csv).unwrap(), "variable,value\na,1\nb,5\n");
+}
+
+#[test]
+fn parameter_test() {
+	let text_problem = "
+		param budget = 182;
+		var a;
+		var b;
+
+		maximize total: 3*a + 2*b;
+		subject to c1: 20*a + 10*b <= budget;
+		subject to c2: b <= 5;
+	";
+	let lp = Parser::lp_from_text(text_problem, Builder::new());
+	assert_eq!(lp.b, vec![182., 5.]);
+
+	let solution = SimplexSolver::new(lp).solve();
+	assert_approx_eq!(solution.objective.unwrap(), 29.8);
+}
+
+#[test]
+fn empty_constraint_test() {
+	let satisfiable = "
+		var a;
+		var b;
+
+		maximize total: a + b;
+		subject to cancelled: a + -1*a <= 3;
+		subject to a_cap: a <= 2;
+		subject to b_cap: b <= 1;
+	";
+	let lp = Parser::lp_from_text(satisfiable, Builder::new());
+	assert_eq!(lp.constraint_names, vec!["a_cap", "b_cap"]);
+
+	let solution = SimplexSolver::new(lp).solve();
+	assert_eq!(solution.status, Status::Optimal);
+	assert_approx_eq!(solution.objective.unwrap(), 3.);
+
+	let unsatisfiable = "
+		var a;
+
+		maximize total: a;
+		subject to cancelled: 2*a + -2*a == 3;
+		subject to a_cap: a <= 2;
+	";
+	let lp = Parser::lp_from_text(unsatisfiable, Builder::new());
+	assert_eq!(lp.A[[0, 0]], 0.);
+	assert_eq!(SimplexSolver::new(lp).solve().status, Status::Infeasible);
+}
+
+#[test]
+fn repeated_terms_test() {
+	let lp = Parser::lp_from_text("
+		var a;
+		var b;
+
+		maximize total: a + 2*a + b;
+		subject to cap: 2*a + b + 3*a <= 10;
+		subject to cancelled: a + b + -1*a <= 4;
+	", Builder::new());
+	assert_eq!(lp.c[.. 2], [3., 1.]);
+	assert_eq!(lp.A[[0, 0]], 5.);
+	assert_eq!(lp.A[[1, 0]], 0.);
+	assert_eq!(lp.A[[1, 1]], 1.);
+}
+
+#[test]
+fn zero_row_test() {
+	let A = matrix![1., 1., 0.;
+					0., 0., 0.];
+	let mut lp = Lp {
+		A,
+		b: vec![4., 0.],
+		c: vec![1., 2., 0.],
+		optimization: Optimization::Max,
+		vars: vec!["a".to_string(), "b".to_string(), "slack_1".to_string()],
+		num_artificial_vars: 1,
+		constraint_names: vec!["cap".to_string(), "empty".to_string()],
+		ranges: vec![],
+		kinds: vec![VariableKind::Continuous; 3],
+		objective_negated: false
+	};
+
+	let solution = SimplexSolver::new(lp.clone()).solve();
+	assert_eq!(solution.status, Status::Optimal);
+	assert_approx_eq!(solution.objective.unwrap(), 8.);
+	assert_eq!(solution.duals.unwrap(), vec![2., 0.]);
+
+	lp.b[1] = 1.;
+	assert_eq!(SimplexSolver::new(lp).solve().status, Status::Infeasible);
+}
+
+#[test]
+fn save_and_resume_test() {
+	let text_problem = "
+		var a binary;
+		var b binary;
+		var c binary;
+		var d binary;
+
+		maximize value: 8*a + 11*b + 6*c + 4*d;
+		subject to weight: 5*a + 7*b + 4*c + 3*d <= 14;
+	";
+	let lp = Parser::lp_from_text(text_problem, Builder::new());
+
+	let mut partial = Solver::with_options(SolverOptions { mip_gap: 0.5, ..SolverOptions::default() });
+	let early = partial.solve_mip(&lp);
+	assert!(early.objective.unwrap() < 21.);
+
+	let mut checkpoint = vec![];
+	partial.save_state().write(&mut checkpoint).unwrap();
+	let state = SolverState::read(&mut &checkpoint[..]).unwrap();
+	assert_eq!(state, partial.save_state());
+
+	let mut resumed = Solver::new();
+	let solution = resumed.resume(&lp, state).unwrap();
+	let full = Solver::new().solve_mip(&lp);
+	assert_approx_eq!(solution.objective.unwrap(), 21.);
+	assert_approx_eq!(solution.objective.unwrap(), full.objective.unwrap());
+	assert_eq!(resumed.relative_gap(), Some(0.));
+
+	// A state from another Lp is rejected instead of indexing past its columns
+	let state = SolverState::read(&mut &b"node 10 9<=0\n"[..]).unwrap();
+	let err = Solver::new().resume(&lp, state).unwrap_err();
+	assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+	assert_eq!(err.to_string(), format!("Solver state bounds column 9 of an Lp with {} columns", lp.vars.len()));
+
+	let continuous = Parser::lp_from_text("var a; var b binary; maximize obj: a + b; subject to cap: a <= 2;", Builder::new());
+	let state = SolverState::read(&mut &b"node 3 0<=1\n"[..]).unwrap();
+	assert!(Solver::new().resume(&continuous, state).is_err());
+
+	let state = SolverState::read(&mut &b"incumbent 5 1 0\n"[..]).unwrap();
+	assert!(Solver::new().resume(&lp, state).is_err());
+}
+
+#[test]
+fn diff_test() {
+	let before = "
+		var a;
+		var b;
+
+		maximize total: a + 2*b;
+		subject to first: a + b <= 4;
+		subject to second: a <= 3;
+		subject to third: b <= 3;
+	";
+	let after = "
+		var a;
+		var c;
+
+		maximize total: 3*a + c;
+		subject to first: a + c <= 4;
+		subject to second: a <= 3;
+		subject to fourth: c <= 2;
+	";
+	let old = Parser::lp_from_text(before, Builder::new());
+	let new = Parser::lp_from_text(after, Builder::new());
+	let diff = old.diff(&new);
+
+	assert_eq!(diff.added_variables, vec!["c"]);
+	assert_eq!(diff.removed_variables, vec!["b"]);
+	assert_eq!(diff.added_constraints, vec!["fourth"]);
+	assert_eq!(diff.removed_constraints, vec!["third"]);
+	assert_eq!(diff.changed_constraints, vec!["first"]);
+	assert_eq!(diff.objective_deltas, vec![
+		("a".to_string(), 2.),
+		("b".to_string(), -2.),
+		("c".to_string(), 1.),
+	]);
+	assert!(!diff.optimization_changed);
+	assert!(!diff.is_empty());
+	assert!(old.diff(&old.clone()).is_empty());
+
+	// Dropping a row renumbers the slack variables of the later ones,
+	// which is no change to them
+	let dropped = Parser::lp_from_text("
+		var a;
+		var b;
+
+		maximize total: a + 2*b;
+		subject to second: a <= 3;
+		subject to third: b <= 3;
+	", Builder::new());
+	let diff = old.diff(&dropped);
+	assert_eq!(diff.removed_constraints, vec!["first"]);
+	assert!(diff.changed_constraints.is_empty());
+	assert!(diff.added_variables.is_empty() && diff.removed_variables.is_empty());
+}
+
+#[test]
+fn soft_constraint_test() {
+	let mut builder = Builder::new();
+	for name in &["a", "b"] {
+		builder.add_variable(Variable { name: name.to_string(), coefficient: 0. });
+	}
+	builder.add_constraint(Constraint {
+		name: "a_min".to_string(),
+		variables: vec![Variable { name: "a".to_string(), coefficient: 1. }],
+		constant: 5.,
+		relation: Relation::GreaterThanOrEqual
+	});
+	builder.add_constraint(Constraint {
+		name: "b_min".to_string(),
+		variables: vec![Variable { name: "b".to_string(), coefficient: 1. }],
+		constant: 1.,
+		relation: Relation::GreaterThanOrEqual
+	});
+	builder.add_soft_constraint(Constraint {
+		name: "total_cap".to_string(),
+		variables: vec![
+			Variable { name: "a".to_string(), coefficient: 1. },
+			Variable { name: "b".to_string(), coefficient: 1. },
+		],
+		constant: 4.,
+		relation: Relation::LessThanOrEqual
+	}, 10.);
+	builder.add_objective(rulp::builder::Objective {
+		name: "cost".to_string(),
+		variables: vec![
+			Variable { name: "a".to_string(), coefficient: 1. },
+			Variable { name: "b".to_string(), coefficient: 2. },
+		],
+		maximize: false
+	});
+	let lp = builder.build_lp();
+
+	let solution = SimplexSolver::new(lp.clone()).solve();
+	assert_eq!(solution.status, Status::Optimal);
+
+	let values = solution.values.unwrap();
+	let value = |name: &str| values[lp.vars.iter().position(|var| var == name).unwrap()];
+	assert_approx_eq!(value("a"), 5.);
+	assert_approx_eq!(value("b"), 1.);
+	assert_approx_eq!(value("total_cap_over"), 2.);
+	assert_approx_eq!(solution.objective.unwrap(), 5. + 2. + 20.);
+
+	let hard = "
+		var a;
+		var b;
+
+		minimize cost: a + 2*b;
+		subject to a_min: a >= 5;
+		subject to b_min: b >= 1;
+		subject to total_cap: a + b <= 4;
+	";
+	let lp = Parser::lp_from_text(hard, Builder::new());
+	assert_eq!(SimplexSolver::new(lp).solve().status, Status::Infeasible);
+}
+
+#[test]
+fn constraints_with_variable_test() {
+	let builder = Builder::new();
+	let mut input_file = File::open("./tests/test_files/advertisement_example.lp").unwrap();
+	let lp = Parser::lp_from_file(&mut input_file, builder);
+
+	assert_eq!(lp.constraints_with_variable("television"), vec!["constraint_1", "constraint_3", "constraint_4"]);
+	assert_eq!(lp.constraints_with_variable("newspaper"), vec!["constraint_1", "constraint_2", "constraint_3", "constraint_4"]);
+	assert!(lp.constraints_with_variable("billboard").is_empty());
+}
+
+#[test]
+fn csv_nonzero_test() {
+	let text_problem = "
+		var a;
+		var b;
+		var c;
+
+		maximize total: 3*a + b + c;
+		subject to cap: a + b + c <= 2;
+	";
+	let lp = Parser::lp_from_text(text_problem, Builder::new());
+	let solution = Solver::new().solve(&lp);
+
+	let mut csv = vec![];
+	solution.to_csv(&mut csv).unwrap();
+	assert_eq!(String::from_utf8(csv).unwrap(), "variable,value\na,2\nb,0\nc,0\n");
+
+	let mut csv = vec![];
+	solution.to_csv_nonzero(&mut csv).unwrap();
+	assert_eq!(String::from_utf8(csv).unwrap(), "variable,value\na,2\n");
+}
+
+#[test]
+fn to_minimization_test() {
+	let builder = Builder::new();
+	let mut input_file = File::open("./tests/test_files/advertisement_example.lp").unwrap();
+	let lp = Parser::lp_from_file(&mut input_file, builder);
+
+	let min = lp.to_minimization();
+	assert_eq!(min.optimization, Optimization::Min);
+	assert_eq!(min.c[0], -100000.);
+	assert_eq!(min.to_minimization(), min);
+
+	let max_solution = SimplexSolver::new(lp).solve();
+	let min_solution = SimplexSolver::new(min.clone()).solve();
+	assert_approx_eq!(min_solution.objective.unwrap(), 1052000.);
+	assert_approx_eq!(min_solution.objective.unwrap(), max_solution.objective.unwrap());
+	for (max_dual, min_dual) in max_solution.duals.unwrap().iter().zip(min_solution.duals.unwrap().iter()) {
+		assert!((max_dual - min_dual).abs() < 1e-6);
+	}
+
+	// Without the recorded negation the solver reports the minimized value
+	let mut raw = min;
+	raw.objective_negated = false;
+	assert_approx_eq!(SimplexSolver::new(raw).solve().objective.unwrap(), -1052000.);
+}
+
+#[test]
+fn integral_preference_test() {
+	let text_problem = "
+		var x;
+		var y integer;
+
+		maximize total: x + y;
+		subject to total_cap: x + y <= 2.5;
+		subject to x_cap: x <= 1;
+		subject to y_cap: y <= 2;
+	";
+	let lp = Parser::lp_from_text(text_problem, Builder::new());
+
+	let mut plain = Solver::new();
+	let plain_solution = plain.solve_mip(&lp);
+	let mut preferring = Solver::with_options(SolverOptions { integral_preference: true, ..SolverOptions::default() });
+	let preferring_solution = preferring.solve_mip(&lp);
+
+	assert_approx_eq!(plain_solution.objective.unwrap(), 2.5);
+	assert_approx_eq!(preferring_solution.objective.unwrap(), 2.5);
+	assert_eq!(preferring.node_count(), 1);
+	assert!(preferring.node_count() < plain.node_count());
+
+	let relaxation = preferring.solve(&lp);
+	assert_approx_eq!(relaxation.objective.unwrap(), Solver::new().solve(&lp).objective.unwrap());
+
+	// A better priced continuous column still enters first
+	let lp = Parser::lp_from_text(&text_problem.replace("x + y;", "x + 0.5*y;"), Builder::new());
+	assert_eq!(preferring.solve(&lp).pivots[0].0, 0);
+}
+
+#[test]
+fn deterministic_test() {
+	// Degenerate: both rows tie in the first ratio test
+	let text_problem = "
+		var a;
+		var b;
+		var c;
+
+		maximize total: 2*a + b + c;
+		subject to first: a + b <= 1;
+		subject to second: a + c <= 1;
+		subject to third: 0.1*a + 0.3*b + 0.3*c <= 0.1;
+	";
+	let lp = Parser::lp_from_text(text_problem, Builder::new());
+	let options = || SolverOptions { deterministic: true, ..SolverOptions::default() };
+
+	let first = Solver::with_options(options()).solve(&lp);
+	assert_approx_eq!(first.objective.unwrap(), 2.);
+	for _ in 0 .. 20 {
+		let solution = Solver::with_options(options()).solve(&lp);
+		assert_eq!(solution.basis, first.basis);
+		assert_eq!(solution.pivots, first.pivots);
+		assert_eq!(solution.values, first.values);
+	}
+
+	// The second row's ratio is smaller by less than rounding error, so
+	// only the deterministic rule lets the first row win the tie
+	let lp = Parser::lp_from_text("
+		var a;
+		var b;
+		maximize total: 2*a + b;
+		subject to first: a + b <= 1.0000000001;
+		subject to second: a + 2*b <= 1;
+	", Builder::new());
+	let plain = Solver::new().solve(&lp);
+	let tied = Solver::with_options(options()).solve(&lp);
+	assert_eq!(plain.pivots, vec![(0, 3)]);
+	assert_eq!(tied.pivots, vec![(0, 2)]);
+	assert_approx_eq!(tied.objective.unwrap(), plain.objective.unwrap());
+}
+
+#[test]
+fn mip_incumbent_test() {
+	let text_problem = "
+		var a integer;
+		var b integer;
+
+		maximize total: a + b;
+		subject to total_cap: a + b <= 2;
+		subject to spread: 2*a + -2*b <= 1;
+	";
+	let lp = Parser::lp_from_text(text_problem, Builder::new());
+
+	let mut plain = Solver::new();
+	let plain_solution = plain.solve_mip(&lp);
+
+	let mut incumbent = HashMap::new();
+	incumbent.insert("a".to_string(), 1.);
+	incumbent.insert("b".to_string(), 1.);
+	let mut warm = Solver::new();
+	let warm_solution = warm.solve_mip_with_incumbent(&lp, incumbent);
+
+	assert_approx_eq!(plain_solution.objective.unwrap(), 2.);
+	assert_approx_eq!(warm_solution.objective.unwrap(), 2.);
+	assert!(warm.node_count() < plain.node_count());
+}
+
+#[test]
+fn mip_all_optima_test() {
+	// Any two of the three items fit, and they're all worth the same
+	let text_problem = "
+		var x[1..3] binary;
+		var y integer;
+
+		maximize total: x[1] + x[2] + x[3] + 2*y;
+		subject to weight: x[1] + x[2] + x[3] <= 2;
+		subject to y_cap: 2*y <= 3;
+	";
+	let lp = Parser::lp_from_text(text_problem, Builder::new());
+
+	let optima = Solver::new().solve_mip_all_optima(&lp);
+	let mut picks: Vec<Vec<f64>> = optima.iter()
+		.map(|solution| solution.values.as_ref().unwrap()[.. 4].iter().map(|x| x.round()).collect())
+		.collect();
+	picks.sort_by(|a, b| a.partial_cmp(b).unwrap());
+	assert_eq!(picks, vec![vec![0., 1., 1., 1.], vec![1., 0., 1., 1.], vec![1., 1., 0., 1.]]);
+	for solution in &optima {
+		assert_eq!(solution.status, Status::Optimal);
+		assert_approx_eq!(solution.objective.unwrap(), 4.);
+	}
+
+	let mut capped = Solver::with_options(SolverOptions { optima_limit: Some(2), ..SolverOptions::default() });
+	assert_eq!(capped.solve_mip_all_optima(&lp).len(), 2);
+
+	let infeasible = Parser::lp_from_text("var a integer; maximize obj: a; subject to c1: 2*a == 1;", Builder::new());
+	assert!(Solver::new().solve_mip_all_optima(&infeasible).is_empty());
+}
+
+#[test]
+#[should_panic]
+fn mip_fractional_incumbent_test() {
+	let lp = Parser::lp_from_text("var a integer; maximize total: a; subject to cap: a <= 2;", Builder::new());
+	let mut incumbent = HashMap::new();
+	incumbent.insert("a".to_string(), 0.5);
+	Solver::new().solve_mip_with_incumbent(&lp, incumbent);
+}
+
+#[test]
+fn to_latex_test() {
+	let builder = Builder::new();
+	let mut input_file = File::open("./tests/test_files/advertisement_example.lp").unwrap();
+	let lp = Parser::lp_from_file(&mut input_file, builder);
+	let latex = lp.to_latex();
+
+	assert!(latex.contains("\\begin{align*}"));
+	assert!(latex.contains("\\maximize \\quad 100000 \\mathit{television} + 40000 \\mathit{newspaper} + 18000 \\mathit{radio}"));
+	assert!(latex.contains("2000 \\mathit{television} + 600 \\mathit{newspaper} + 300 \\mathit{radio} &\\leq 18200 \\\\"));
+	assert!(latex.contains("-\\mathit{television} - \\mathit{newspaper} + \\mathit{radio} &\\leq 0 \\\\"));
+	assert!(latex.contains("\\mathit{television}, \\mathit{newspaper}, \\mathit{radio} &\\geq 0"));
+	assert!(!latex.contains("slack"));
+
+	let lp = Parser::lp_from_text("
+		var a;
+		var b binary;
+		maximize obj: a + b;
+		subject to band: 1 <= a + b <= 3;
+	", Builder::new());
+	let latex = lp.to_latex();
+	assert!(latex.contains("1 \\leq \\mathit{a} + \\mathit{b} &\\leq 3 \\\\\n"));
+	assert!(latex.contains("\\mathit{a}, \\mathit{b} &\\geq 0 \\\\\n\\mathit{b} &\\in \\{0, 1\\}\n"));
+	assert!(!latex.contains("range") && !latex.contains("b\\_binary"));
+}
+
+#[test]
+fn duplicate_constraint_name_test() {
+	let text_problem = "
+		var a;
+		var b;
+
+		maximize total: a + b;
+		subject to cap: a <= 2;
+		subject to cap: b <= 3;
+		subject to cap_2: a + b <= 10;
+		subject to cap: a + b <= 4;
+	";
+	let lp = Parser::lp_from_text(text_problem, Builder::new());
+	assert_eq!(lp.constraint_names, vec!["cap", "cap_2", "cap_2_2", "cap_3"]);
+
+	let solution = SimplexSolver::new(lp.clone()).solve();
+	let mut duals = vec![];
+	solution.write_duals(&lp, &mut duals).unwrap();
+	let duals = String::from_utf8(duals).unwrap();
+	assert_eq!(duals.lines().map(|line| line.split(':').next().unwrap()).collect::<Vec<&str>>(),
+		vec!["cap", "cap_2", "cap_2_2", "cap_3"]);
+}
+
+#[test]
+fn vector_objective_test() {
+	let named = "
+		var television;
+		var newspaper;
+		var radio;
+
+		maximize objective: 100000*television + 40000*newspaper + 18000*radio;
+		subject to budget: 2000*television + 600*newspaper + 300*radio <= 18200;
+	";
+	let vector = "
+		var television;
+		var newspaper;
+		var radio;
+
+		objective maximize: [100000, 40000, 18000];
+		subject to budget: 2000*television + 600*newspaper + 300*radio <= 18200;
+	";
+	let named_lp = Parser::lp_from_text(named, Builder::new());
+	let vector_lp = Parser::lp_from_text(vector, Builder::new());
+
+	assert_eq!(vector_lp.c, named_lp.c);
+	assert_eq!(vector_lp.c, vec![100000., 40000., 18000., 0.]);
+	assert_eq!(vector_lp, named_lp);
+}
+
+#[test]
+fn declared_cost_test() {
+	let inline = Parser::lp_from_text("
+		var television cost 100000;
+		var newspaper value 40000;
+		var radio cost 18000;
+		var spots cost -500 integer;
+		var reach;
+
+		maximize objective:;
+		subject to budget: 2000*television + 600*newspaper + 300*radio + spots <= 18200;
+		subject to coverage: reach + -1*radio <= 0;
+	", Builder::new());
+	assert_eq!(inline.c, vec![100000., 40000., 18000., -500., 0., 0., 0.]);
+	assert_eq!(inline.kinds[3], VariableKind::Integer);
+
+	// Declared costs add to the objective's own terms
+	let mixed = Parser::lp_from_text("
+		var a cost 3;
+		var b;
+		minimize b;
+		subject to cap: a + b >= 1;
+	", Builder::new());
+	assert_eq!(mixed.c, vec![3., 1., 0.]);
+	assert_eq!(mixed.optimization, Optimization::Min);
+}
+
+#[test]
+fn analytic_center_test() {
+	let text_problem = "
+		var a;
+		var b;
+
+		maximize total: a + b;
+		subject to total_cap: a + b <= 4;
+		subject to a_cap: a <= 3;
+		subject to b_cap: b <= 3;
+	";
+	let lp = Parser::lp_from_text(text_problem, Builder::new());
+	let center = lp.analytic_center().unwrap();
+
+	assert!(center.iter().all(|&x| x > 0.));
+	for row in 0 .. lp.b.len() {
+		let lhs: f64 = (0 .. center.len()).map(|col| lp.A[[row, col]] * center[col]).sum();
+		assert_approx_eq!(lhs, lp.b[row]);
+	}
+	assert_approx_eq!(center[0], 1.);
+	assert_approx_eq!(center[1], 1.);
+
+	let flat = "
+		var a;
+		var b;
+
+		maximize total: a + b;
+		subject to total_cap: a + b <= 0;
+	";
+	assert_eq!(Parser::lp_from_text(flat, Builder::new()).analytic_center(), None);
+}
+
+#[test]
+fn interval_constraint_test() {
+	let interval = "
+		var a;
+		var b;
+
+		minimize total: a + 2*b;
+		subject to window: a + b in [5, 20];
+		subject to a_cap: a <= 3;
+	";
+	let chained = "
+		var a;
+		var b;
+
+		minimize total: a + 2*b;
+		subject to window: 5 <= a + b <= 20;
+		subject to a_cap: a <= 3;
+	";
+	let interval_lp = Parser::lp_from_text(interval, Builder::new());
+	let chained_lp = Parser::lp_from_text(chained, Builder::new());
+	assert_eq!(interval_lp, chained_lp);
+
+	let solution = SimplexSolver::new(interval_lp).solve();
+	assert_approx_eq!(solution.objective.unwrap(), 7.);
+}
+
+#[test]
+fn objective_breakdown_test() {
+	let builder = Builder::new();
+	let mut input_file = File::open("./tests/test_files/advertisement_example.lp").unwrap();
+	let lp = Parser::lp_from_file(&mut input_file, builder);
+	let solution = SimplexSolver::new(lp.clone()).solve();
+
+	let breakdown = solution.objective_breakdown(&lp);
+	let mut names: Vec<&str> = breakdown.iter().map(|(name, _)| name.as_str()).collect();
+	assert_eq!(names.pop(), Some("radio"));
+	names.sort();
+	assert_eq!(names, vec!["newspaper", "television"]);			// Tied at 400000
+	assert_approx_eq!(breakdown[0].1, 400000.);
+	assert_approx_eq!(breakdown[1].1, 400000.);
+	assert_approx_eq!(breakdown[2].1, 252000.);
+
+	let total: f64 = breakdown.iter().map(|&(_, contribution)| contribution).sum();
+	assert_approx_eq!(total, solution.objective.unwrap());
+
+	let mut solution = solution;
+	solution.values.as_mut().unwrap()[0] = f64::NAN;
+	let breakdown = solution.objective_breakdown(&lp);
+	assert_eq!(breakdown[2].0, "television");
+	assert!(breakdown[2].1.is_nan());
+}
+
+#[test]
+fn support_test() {
+	let lp = Parser::lp_from_text("
+		var a;
+		var b;
+		var c;
+
+		maximize obj: 2*a + b + c;
+		subject to cap: a + b + c <= 4;
+		subject to c_min: c >= 1;
+	", Builder::new());
+	let mut solution = SimplexSolver::new(lp.clone()).solve();
+
+	// b is left at zero, while the slack of c_min doesn't count
+	assert_eq!(solution.support(&lp, 1e-9), vec!["a".to_string(), "c".to_string()]);
+
+	solution.values.as_mut().unwrap()[2] = 1e-12;
+	assert_eq!(solution.support(&lp, 1e-9), vec!["a".to_string()]);
+	assert_eq!(solution.support(&lp, 1e-15), vec!["a".to_string(), "c".to_string()]);
+
+	solution.values = None;
+	assert!(solution.support(&lp, 1e-9).is_empty());
+}
+
+#[test]
+fn bounds_violations_test() {
+	let lp = Parser::lp_from_text("
+		var a;
+		var b binary;
+		var c;
+		var d;
+
+		maximize obj: a + b + c + d;
+		subject to a_cap: a <= 4;
+		subject to c_band: 1 <= 2*c <= 6;
+		subject to d_cap: -1*d >= -2;
+		subject to total: a + b + c + d <= 20;
+	", Builder::new());
+	let mut solution = SimplexSolver::new(lp.clone()).solve();
+	assert!(solution.bounds_violations(&lp, 1e-9).is_empty());
+
+	{
+		let values = solution.values.as_mut().unwrap();
+		values[0] = 5.;
+		values[1] = 1.5;
+		values[2] = 0.25;
+		values[3] = 2.;
+	}
+	assert_eq!(solution.bounds_violations(&lp, 1e-9), vec![
+		("a".to_string(), 1.),
+		("b".to_string(), 0.5),
+		("c".to_string(), 0.25)
+	]);
+
+	solution.values.as_mut().unwrap()[3] = -1.;
+	assert_eq!(solution.bounds_violations(&lp, 1e-9).last(), Some(&("d".to_string(), 1.)));
+	assert_eq!(solution.bounds_violations(&lp, 0.9).len(), 2);
+}
+
+#[test]
+fn complementary_slackness_test() {
+	let builder = Builder::new();
+	let mut input_file = File::open("./tests/test_files/advertisement_example.lp").unwrap();
+	let lp = Parser::lp_from_file(&mut input_file, builder);
+
+	let primal = vec![4., 10., 14., 0., 0., 0., 12.];
+	let dual = vec![1180. / 23., 272000. / 23., 60000. / 23., 0.];
+	assert!(lp.check_complementary_slackness(&primal, &dual, 1e-9));
+
+	let solution = SimplexSolver::new(lp.clone()).solve();
+	assert!(lp.check_complementary_slackness(&solution.values.unwrap(), &solution.duals.unwrap(), 1e-9));
+
+	// Feasible but suboptimal primal
+	let suboptimal = vec![4., 10., 13., 300., 0., 1., 13.];
+	assert!(!lp.check_complementary_slackness(&suboptimal, &dual, 1e-9));
+
+	// Dual no longer prices every column out
+	let mut perturbed = dual.clone();
+	perturbed[0] -= 1.;
+	assert!(!lp.check_complementary_slackness(&primal, &perturbed, 1e-9));
+
+	assert!(!lp.check_complementary_slackness(&primal[.. 3], &dual, 1e-9));
+}
+
+#[test]
+fn default_nonnegative_variables_test() {
+	let text_problem = "
+		var a;
+		var b;
+		var c;
+		var d binary;
+		var e;
+
+		maximize obj: a + b + c + d + e;
+		subject to a_min: a >= 2;
+		subject to b_min: -1*b <= 0;
+		subject to c_max: c <= 4;
+		subject to total: a + b + c + d + e <= 10;
+	";
+	let mut builder = Builder::new();
+	assert!(builder.default_nonnegative_variables().is_empty());
+
+	let components = Parser::parse_components_from_text(text_problem);
+	for v in components.variables {
+		builder.add_variable(v);
+	}
+	for (name, kind) in components.kinds {
+		builder.set_variable_kind(&name, kind);
+	}
+	for c in components.constraints {
+		builder.add_constraint(c);
+	}
+	builder.add_objective(components.objective);
+	builder.build_lp();
+
+	assert_eq!(builder.default_nonnegative_variables(), ["c".to_string(), "e".to_string()]);
+}
+
+#[test]
+fn reusable_solver_test() {
+	let builder = Builder::new();
+	let mut input_file = File::open("./tests/test_files/advertisement_example.lp").unwrap();
+	let mut lp = Parser::lp_from_file(&mut input_file, builder);
+	let mut solver = Solver::new();
+
+	for i in 0 .. 500 {
+		lp.b[0] = 18200. + 10. * i as f64;
+		lp.b[1] = (i % 20) as f64;
+		let reused = solver.solve(&lp);
+		let fresh = SimplexSolver::new(lp.clone()).solve();
+
+		assert_eq!(reused.status, fresh.status);
+		assert_eq!(reused.values, fresh.values);
+		assert_eq!(reused.objective, fresh.objective);
+		assert_eq!(reused.duals, fresh.duals);
+	}
+
+	// A larger Lp outgrows the buffers, smaller ones fit again
+	let larger = Lp::random(10, 6, 3);
+	assert_eq!(solver.solve(&larger), SimplexSolver::new(larger.clone()).solve());
+	assert_eq!(solver.solve(&lp).objective, SimplexSolver::new(lp.clone()).solve().objective);
+}
+
+#[test]
+fn constraint_from_terms_test() {
+	let mut coefficients = HashMap::new();
+	coefficients.insert("television".to_string(), 2000.);
+	coefficients.insert("newspaper".to_string(), 600.);
+	coefficients.insert("radio".to_string(), 300.);
+
+	let constraint = Constraint::from_terms("budget", coefficients.clone(), Relation::LessThanOrEqual, 18200.);
+	assert_eq!(constraint.name, "budget");
+	assert_eq!(constraint.relation, Relation::LessThanOrEqual);
+	assert_eq!(constraint.constant, 18200.);
+	assert_eq!(constraint.variables.len(), 3);
+	for var in &constraint.variables {
+		assert_eq!(coefficients[&var.name], var.coefficient);
+	}
+
+	let mut builder = Builder::new();
+	for name in &["television", "newspaper", "radio"] {
+		builder.add_variable(Variable { name: name.to_string(), coefficient: 0. });
+	}
+	builder.add_constraint(constraint);
+	builder.add_objective(rulp::builder::Objective {
+		name: "reach".to_string(),
+		variables: vec![Variable { name: "radio".to_string(), coefficient: 1. }],
+		maximize: true
+	});
+	let lp = builder.build_lp();
+
+	assert_eq!(lp.A[[0, 0]], 2000.);
+	assert_eq!(lp.A[[0, 1]], 600.);
+	assert_eq!(lp.A[[0, 2]], 300.);
+}
+
+#[test]
+fn relaxation_bound_test() {
+	let lp = Parser::lp_from_text("
+		var a integer;
+		var b integer;
+		maximize total: 5*a + 4*b;
+		subject to first: 6*a + 4*b <= 24;
+		subject to second: a + 2*b <= 6;
+	", Builder::new());
+
+	let bound = lp.relaxation_bound().unwrap();
+	let integer_optimum = Solver::new().solve_mip(&lp).objective.unwrap();
+	assert_approx_eq!(bound, 21.);
+	assert_approx_eq!(integer_optimum, 20.);
+	assert!(bound > integer_optimum + 0.5);
+
+	let builder = Builder::new();
+	let mut input_file = File::open("./tests/test_files/unbounded_example.lp").unwrap();
+	assert_eq!(Parser::lp_from_file(&mut input_file, builder).relaxation_bound(), None);
+}
+
+#[test]
+fn integer_objective_bound_test() {
+	let lp = Parser::lp_from_text("
+		var a integer;
+		var b integer;
+		maximize total: 5*a + 4*b;
+		subject to first: 6*a + 4*b <= 25;
+		subject to second: a + 2*b <= 6;
+	", Builder::new());
+
+	assert_approx_eq!(lp.relaxation_bound().unwrap(), 21.75);
+	assert_eq!(lp.integer_objective_bound(), Some(21.));
+	assert!(Solver::new().solve_mip(&lp).objective.unwrap() <= 21. + 1e-9);
+	assert_eq!(lp.to_minimization().integer_objective_bound(), Some(21.));
+
+	let min = Parser::lp_from_text("
+		var a integer;
+		var b integer;
+		minimize total: 2*a + 3*b;
+		subject to cover: 4*a + 5*b >= 9;
+	", Builder::new());
+	// The relaxation reaches 4.5 with a = 2.25
+	assert_eq!(min.integer_objective_bound(), Some(5.));
+
+	// Neither a fractional coefficient nor a continuous variable give an
+	// integer objective
+	let fractional = Parser::lp_from_text("
+		var a integer;
+		maximize total: 2.5*a;
+		subject to cap: a <= 3;
+	", Builder::new());
+	assert_eq!(fractional.integer_objective_bound(), None);
+	let continuous = Parser::lp_from_text("
+		var a integer;
+		var b;
+		maximize total: 2*a + b;
+		subject to cap: a + b <= 3.5;
+	", Builder::new());
+	assert_eq!(continuous.integer_objective_bound(), None);
+}
+
+#[test]
+fn relax_constraint_effect_test() {
+	let builder = Builder::new();
+	let mut input_file = File::open("./tests/test_files/advertisement_example.lp").unwrap();
+	let lp = Parser::lp_from_file(&mut input_file, builder);
+	let duals = SimplexSolver::new(lp.clone()).solve().duals.unwrap();
+
+	// The budget is binding, so a small raise earns its shadow price
+	assert!(duals[0] > 0.);
+	assert_approx_eq!(lp.relax_constraint_effect("constraint_1", 10.) / 10., duals[0]);
+	assert_approx_eq!(lp.relax_constraint_effect("constraint_4", 1.), 0.);
+}
+
+#[test]
+fn objective_coefficient_effect_test() {
+	let lp = Parser::lp_from_text("
+		var a;
+		var b;
+		maximize obj: 3*a + 2*b;
+		subject to total: a + b <= 4;
+		subject to a_max: a <= 3;
+	", Builder::new());
+	let values = SimplexSolver::new(lp.clone()).solve().values.unwrap();
+	assert_approx_eq!(values[1], 1.);
+
+	// (3, 1) stays optimal until the coefficient of b reaches that of a
+	assert_approx_eq!(lp.objective_coefficient_effect("b", 0.5), 0.5 * values[1]);
+	assert_approx_eq!(lp.objective_coefficient_effect("b", 1.), values[1]);
+	// Past that the optimum moves to (0, 4) and gains more than b's value
+	assert_approx_eq!(lp.objective_coefficient_effect("b", 2.), 5.);
+	assert_approx_eq!(lp.objective_coefficient_effect("a", -0.5), -0.5 * values[0]);
+}
+
+#[test]
+fn strong_duality_test() {
+	let builder = Builder::new();
+	let mut input_file = File::open("./tests/test_files/advertisement_example.lp").unwrap();
+	let lp = Parser::lp_from_file(&mut input_file, builder);
+	assert!(lp.verify_strong_duality(1e-9));
+	assert!(lp.to_minimization().verify_strong_duality(1e-9));
+
+	let builder = Builder::new();
+	let mut input_file = File::open("./tests/test_files/radiation_example.lp").unwrap();
+	let lp = Parser::lp_from_file(&mut input_file, builder);
+	assert!(lp.verify_strong_duality(1e-9));
+
+	let builder = Builder::new();
+	let mut input_file = File::open("./tests/test_files/unbounded_example.lp").unwrap();
+	let lp = Parser::lp_from_file(&mut input_file, builder);
+	assert!(!lp.verify_strong_duality(1e-9));
+}
+
+#[test]
+fn as_dual_solution_test() {
+	for file in &["advertisement_example.lp", "radiation_example.lp"] {
+		let mut input_file = File::open(format!("./tests/test_files/{}", file)).unwrap();
+		let lp = Parser::lp_from_file(&mut input_file, Builder::new());
+		let primal = SimplexSolver::new(lp.clone()).solve();
+
+		let read = primal.as_dual_solution(&lp);
+		let solved = SimplexSolver::new(lp.dual()).solve();
+		assert_eq!(read.status, Status::Optimal);
+		assert_approx_eq!(read.objective.unwrap(), solved.objective.unwrap());
+		for (read_value, solved_value) in read.values.unwrap().iter().zip(solved.values.unwrap().iter()) {
+			assert!((read_value - solved_value).abs() < 1e-6, "{} in {}", read_value, file);
+		}
+		assert_eq!(read.duals, primal.values);
+	}
+
+	let mut input_file = File::open("./tests/test_files/unbounded_example.lp").unwrap();
+	let unbounded = Parser::lp_from_file(&mut input_file, Builder::new());
+	let dual = SimplexSolver::new(unbounded.clone()).solve().as_dual_solution(&unbounded);
+	assert_eq!(dual.status, Status::Infeasible);
+	assert_eq!(dual.values, None);
+}
+
+#[test]
+fn update_constraint_test() {
+	let components = Parser::parse_components_from_file(&mut File::open("./tests/test_files/advertisement_example.lp").unwrap());
+	let mut builder = Builder::new();
+	for v in components.variables {
+		builder.add_variable(v);
+	}
+	for c in components.constraints {
+		builder.add_constraint(c);
+	}
+	builder.add_objective(components.objective);
+
+	builder.update_constraint("constraint_2", 12.);
+	builder.set_constraint_coefficient("constraint_1", "newspaper", 500.);
+	builder.set_constraint_coefficient("constraint_2", "radio", 1.);
+	let lp = builder.build_lp();
+
+	assert_eq!(lp.b[1], 12.);
+	assert_eq!(lp.A[[0, 1]], 500.);
+	assert_eq!(lp.A[[1, 1]], 1.);
+	assert_eq!(lp.A[[1, 2]], 1.);
+	assert_eq!(lp.constraint_names.len(), 4);
+
+	let mut builder = Builder::new();
+	builder.add_variable(Variable { name: "a".to_string(), coefficient: 0. });
+	builder.add_constraint(Constraint {
+		name: "cap".to_string(),
+		variables: vec![
+			Variable { name: "a".to_string(), coefficient: 1. },
+			Variable { name: "a".to_string(), coefficient: 2. },
+		],
+		constant: 6.,
+		relation: Relation::LessThanOrEqual
+	});
+	builder.set_constraint_coefficient("cap", "a", 4.);
+	builder.add_objective(rulp::builder::Objective {
+		name: "obj".to_string(),
+		variables: vec![Variable { name: "a".to_string(), coefficient: 1. }],
+		maximize: true
+	});
+	let lp = builder.build_lp();
+
+	assert_eq!(lp.A[[0, 0]], 4.);
+	assert_approx_eq!(SimplexSolver::new(lp).solve().objective.unwrap(), 1.5);
+}
+
+#[test]
+#[should_panic]
+fn update_unknown_constraint_test() {
+	Builder::new().update_constraint("missing", 1.);
+}
+
+#[test]
+fn solve_reader_test() {
+	let mut input = Cursor::new(fs::read("./tests/test_files/advertisement_example.lp").unwrap());
+	let solution = rulp::solve_reader(&mut input).unwrap();
+
+	assert_eq!(solution.status, Status::Optimal);
+	assert_eq!(solution.objective.unwrap(), 1052000.);
+
+	let mut invalid = Cursor::new(vec![0xff, 0xfe, b';']);
+	match rulp::solve_reader(&mut invalid) {
+		Err(rulp::RulpError::Io(_)) => {},
+		other => panic!("Expected a read error, got {:?}", other)
+	}
+
+	match rulp::solve_reader(&mut Cursor::new("garbage;")) {
+		Err(rulp::RulpError::Parse(err)) => assert_eq!(err.index, 0),
+		other => panic!("Expected a parse error, got {:?}", other)
+	}
+}
+
+#[test]
+fn run_test() {
+	let text = fs::read_to_string("./tests/test_files/advertisement_example.lp").unwrap();
+	let mut report = vec![];
+	rulp::run(&text, &mut report).unwrap();
+	let report = String::from_utf8(report).unwrap();
+
+	assert!(report.contains("max LP:"));
+	assert!(report.contains("Optimal objective: 1052000"));
+	let value = |name: &str| -> f64 {
+		let prefix = format!("{}: ", name);
+		let line = report.lines().find(|line| line.starts_with(&prefix)).unwrap();
+		line[prefix.len() ..].parse().unwrap()
+	};
+	assert_approx_eq!(value("television"), 4.);
+	assert_approx_eq!(value("newspaper"), 10.);
+	assert_approx_eq!(value("radio"), 14.);
+	assert!(report.contains("Duals:"));
+	assert_approx_eq!(value("constraint_1"), 1180. / 23.);
+
+	match rulp::run("var a; maximize obj: b;", &mut vec![]) {
+		Err(rulp::RulpError::Parse(err)) => assert_eq!(err.message, "Unknown variable in objective"),
+		other => panic!("Expected a parse error, got {:?}", other)
+	}
+}
+
+#[test]
+fn pivot_history_test() {
+	for file in &["advertisement_example.lp", "radiation_example.lp"] {
+		let builder = Builder::new();
+		let mut input_file = File::open(format!("./tests/test_files/{}", file)).unwrap();
+		let lp = Parser::lp_from_file(&mut input_file, builder);
+
+		let solution = SimplexSolver::new(lp.clone()).solve();
+		let history = &solution.pivots;
+		assert!(solution.iterations > 0);
+		assert_eq!(history.len(), solution.iterations);
+		for &(entering, leaving) in history {
+			assert!(entering < lp.c.len());
+			assert!(entering != leaving);
+		}
+	}
+
+	let lp = Lp::random(4, 3, 1);
+	let interior_point = Solver::with_options(SolverOptions {
+		method: SolverMethod::InteriorPoint,
+		..SolverOptions::default()
+	}).solve(&lp);
+	assert!(interior_point.pivots.is_empty());
+}
+
+#[test]
+fn to_constraints_test() {
+	let text_problem = "
+		var a;
+		var b;
+		var c binary;
+
+		maximize obj: a + b + c;
+		subject to cap: a + 2*b <= 10;
+		subject to floor: a + -1*c >= 1;
+		subject to balance: b + c == 3;
+		subject to band: 1 <= a + b <= 6;
+	";
+	let components = Parser::parse_components_from_text(text_problem);
+	let lp = Parser::lp_from_text(text_problem, Builder::new());
+
+	assert_eq!(lp.to_constraints(), components.constraints);
+
+	// A constraint named like the binary row doesn't clash with it
+	let text_problem = "
+		var a;
+		var c binary;
+
+		maximize obj: a + c;
+		subject to c_binary: a + c <= 4;
+	";
+	let components = Parser::parse_components_from_text(text_problem);
+	let lp = Parser::lp_from_text(text_problem, Builder::new());
+
+	let mut expected = components.constraints;
+	expected[0].name = "c_binary_2".to_string();
+	assert_eq!(lp.constraint_names.len(), 2);
+	assert_eq!(lp.to_constraints(), expected);
+}
+
+#[test]
+fn to_rust_builder_code_test() {
+	let lp = Parser::lp_from_text("
+		var a;
+		var b integer;
+		var c binary;
+		maximize profit: 3*a + 2*b + -1*c;
+		subject to cap: a + b <= 10;
+		subject to floor: a + -1*b >= 1;
+		subject to link: a + c == 2;
+		subject to band: 2 <= b + c <= 6;
+	", Builder::new());
+	let code = lp.to_rust_builder_code();
+
+	assert!(code.starts_with("let mut builder = Builder::new();\n"));
+	assert!(code.ends_with("let lp = builder.build_lp();\n"));
+	assert!(code.contains("builder.add_variable(Variable { name: \"a\".to_string(), coefficient: 0. });\n"));
+	assert!(code.contains("builder.set_variable_kind(\"b\", VariableKind::Integer);\n"));
+	assert!(code.contains("builder.set_variable_kind(\"c\", VariableKind::Binary);\n"));
+	assert!(code.contains(concat!(
+		"builder.add_constraint(Constraint {\n",
+		"\tname: \"cap\".to_string(),\n",
+		"\tvariables: vec![\n",
+		"\t\tVariable { name: \"a\".to_string(), coefficient: 1.0 },\n",
+		"\t\tVariable { name: \"b\".to_string(), coefficient: 1.0 },\n",
+		"\t],\n",
+		"\tconstant: 10.0,\n",
+		"\trelation: Relation::LessThanOrEqual\n",
+		"});\n"
+	)));
+	assert!(code.contains("\tname: \"floor\".to_string(),\n"));
+	assert!(code.contains("\t\tVariable { name: \"b\".to_string(), coefficient: -1.0 },\n"));
+	assert!(code.contains("\tconstant: 1.0,\n\trelation: Relation::GreaterThanOrEqual\n"));
+	assert!(code.contains("\tconstant: 2.0,\n\trelation: Relation::Equal\n"));
+	assert!(code.contains("\tconstant: 6.0,\n\trelation: Relation::Range(2.0)\n"));
+	assert!(code.contains("\t\tVariable { name: \"c\".to_string(), coefficient: -1.0 },\n\t],\n\tmaximize: true\n"));
+	assert!(!code.contains("band_range"));
+	assert!(!code.contains("c_binary"));
+	assert_eq!(code.matches("add_constraint").count(), 4);
+}
+
+#[test]
+fn lp_list_from_text_test() {
+	let text = "
+		var a;
+		maximize obj: a;
+		subject to cap: a <= 4;
+		%%%
+		var x;
+		var y;
+		minimize cost: x + 2*y;
+		subject to demand: x + y >= 3;
+		%%%
+		var z;
+		maximize obj: w;
+		%%%
+	";
+	let lps = Parser::lp_list_from_text(text);
+	assert_eq!(lps.len(), 3);
+
+	let first = lps[0].as_ref().unwrap();
+	assert_eq!(first.vars[0], "a");
+	assert_approx_eq!(SimplexSolver::new(first.clone()).solve().objective.unwrap(), 4.);
+
+	let second = lps[1].as_ref().unwrap();
+	assert_eq!(second.optimization, Optimization::Min);
+	assert_approx_eq!(SimplexSolver::new(second.clone()).solve().objective.unwrap(), 3.);
+
+	let error = lps[2].as_ref().unwrap_err();
+	assert_eq!(error.index, 2);
+	assert!(error.message.contains("Unknown variable"));
+
+	let lps = Parser::lp_list_from_text("var a; maximize obj: a; subject to cap: a = 4; %%% var b; maximize obj: b;");
+	assert!(lps[0].as_ref().unwrap_err().message.contains("unknown relation"));
+	assert!(lps[1].is_ok());
+}
+
+#[test]
+fn all_slacks_test() {
+	let builder = Builder::new();
+	let mut input_file = File::open("./tests/test_files/advertisement_example.lp").unwrap();
+	let lp = Parser::lp_from_file(&mut input_file, builder);
+	let solution = SimplexSolver::new(lp.clone()).solve();
+
+	let slacks = solution.all_slacks(&lp);
+	assert_eq!(slacks.len(), 4);
+	for (name, slack) in lp.constraint_names.iter().zip(slacks.iter()) {
+		assert_eq!(solution.slack(&lp, name), Some(*slack));
+	}
+	for (slack, expected) in slacks.iter().zip(vec![0., 0., 0., 12.]) {
+		assert_approx_eq!(*slack, expected);
+	}
+	assert_eq!(solution.slack(&lp, "missing"), None);
+
+	let lp = Parser::lp_from_text("
+		var a;
+		var b;
+		maximize total: a + b;
+		subject to band: 2 <= a + b <= 6;
+		subject to a_max: a <= 1;
+		subject to b_min: b >= 1;
+	", Builder::new());
+	let solution = SimplexSolver::new(lp.clone()).solve();
+	let slack = |name: &str| solution.slack(&lp, name).unwrap();
+
+	assert_approx_eq!(slack("band"), 0.);
+	assert_approx_eq!(slack("band_range"), 4.);
+	assert_approx_eq!(slack("a_max"), 0.);
+	assert_approx_eq!(slack("b_min"), 4.);
+	assert_eq!(solution.all_slacks(&lp).len(), lp.constraint_names.len());
+}
+
+#[test]
+fn degrees_of_freedom_test() {
+	let builder = Builder::new();
+	let mut input_file = File::open("./tests/test_files/advertisement_example.lp").unwrap();
+	let lp = Parser::lp_from_file(&mut input_file, builder);
+
+	// The budget, newspaper and radio constraints meet at the optimum
+	let solution = SimplexSolver::new(lp.clone()).solve();
+	assert_eq!(solution.degrees_of_freedom(&lp), 0);
+
+	// Only x is pinned by a constraint, y rests on its bound
+	let lp = Parser::lp_from_text("var x; var y; maximize obj: x + -1*y; subject to x_cap: x <= 4;", Builder::new());
+	let solution = SimplexSolver::new(lp.clone()).solve();
+	assert_eq!(solution.degrees_of_freedom(&lp), 1);
+}
+
+#[test]
+fn project_point_test() {
+	let builder = Builder::new();
+	let mut input_file = File::open("./tests/test_files/advertisement_example.lp").unwrap();
+	let lp = Parser::lp_from_file(&mut input_file, builder);
+
+	// Only the budget is violated, and cutting television frees it fastest
+	let point = [10., 10., 10.];
+	let projected = lp.project_point(&point).unwrap();
+	assert_eq!(projected.len(), 3);
+	let (tv, np, radio) = (projected[0], projected[1], projected[2]);
+	assert!(2000. * tv + 600. * np + 300. * radio <= 18200. + 1e-6);
+	assert!(np <= 10. + 1e-6);
+	assert!(-tv - np + radio <= 1e-6);
+	assert!(-9. * tv + np + radio <= 1e-6);
+	assert!(projected.iter().all(|&value| value >= -1e-9));
+
+	let distance: f64 = projected.iter().zip(point.iter()).map(|(x, p)| (x - p).abs()).sum();
+	assert_approx_eq!(distance, 5.4);
+
+	let feasible = [4., 10., 14.];
+	let unchanged = lp.project_point(&feasible).unwrap();
+	for (x, p) in unchanged.iter().zip(feasible.iter()) {
+		assert_approx_eq!(x, p);
+	}
+
+	let mut input_file = File::open("./tests/test_files/infeasible_example.lp").unwrap();
+	let infeasible = Parser::lp_from_file(&mut input_file, Builder::new());
+	let num_vars = infeasible.vars.len() - infeasible.num_artificial_vars;
+	assert_eq!(infeasible.project_point(&vec![0.; num_vars]), None);
+}
+
+#[test]
+fn chebyshev_center_test() {
+	let text_problem = "
+		var x;
+		var y;
+
+		maximize obj: x + y;
+		subject to x_min: x >= 1;
+		subject to x_max: x <= 5;
+		subject to y_range: 0 <= y <= 2;
+	";
+	let lp = Parser::lp_from_text(text_problem, Builder::new());
+
+	// A 4 by 2 box fits a ball of radius 1, centered across y but free to
+	// slide along x
+	let (center, radius) = lp.chebyshev_center().unwrap();
+	assert_approx_eq!(radius, 1.);
+	assert_approx_eq!(center[1], 1.);
+	assert!(center[0] >= 2. - 1e-9 && center[0] <= 4. + 1e-9);
+
+	let square = Parser::lp_from_text("
+		var x;
+		var y;
+
+		maximize obj: x + y;
+		subject to x_max: x <= 2;
+		subject to y_max: y <= 2;
+	", Builder::new());
+	let (center, radius) = square.chebyshev_center().unwrap();
+	assert_approx_eq!(radius, 1.);
+	assert_approx_eq!(center[0], 1.);
+	assert_approx_eq!(center[1], 1.);
+
+	let unbounded = Parser::lp_from_text("var x; maximize obj: x; subject to x_min: x >= 1;", Builder::new());
+	assert_eq!(unbounded.chebyshev_center(), None);
+
+	// A binary variable keeps its upper bound of one
+	let binary = Parser::lp_from_text("
+		var x binary;
+		var y;
+
+		maximize obj: x + y;
+		subject to y_max: y <= 4;
+	", Builder::new());
+	let (center, radius) = binary.chebyshev_center().unwrap();
+	assert_approx_eq!(radius, 0.5);
+	assert_approx_eq!(center[0], 0.5);
+}
+
+#[test]
+fn resolve_with_objective_test() {
+	let builder = Builder::new();
+	let mut input_file = File::open("./tests/test_files/advertisement_example.lp").unwrap();
+	let lp = Parser::lp_from_file(&mut input_file, builder);
+	let mut solver = Solver::new();
+	solver.solve(&lp);
+
+	let objectives = vec![
+		vec![100000., 40000., 18000.],
+		vec![10000., 40000., 18000.],
+		vec![1., 1., 1.],
+		vec![0., 0., 5.],
+		vec![100000., 40000., 18000.],
+	];
+	for coefficients in objectives {
+		let mut new_c = lp.c.clone();
+		new_c[.. 3].copy_from_slice(&coefficients);
+
+		let warm = solver.resolve_with_objective(&new_c);
+		let mut changed = lp.clone();
+		changed.c = new_c;
+		let cold = SimplexSolver::new(changed).solve();
+
+		assert_eq!(warm.status, Status::Optimal);
+		assert_approx_eq!(warm.objective.unwrap(), cold.objective.unwrap());
+		assert!(warm.iterations <= cold.iterations);
+		let values = warm.values.unwrap();
+		let activity = 2000. * values[0] + 600. * values[1] + 300. * values[2];
+		assert!(activity <= 18200. + 1e-6);
+	}
+
+	// Changing nothing needs no pivots at all
+	let unchanged = solver.resolve_with_objective(&lp.c);
+	assert_eq!(unchanged.iterations, 0);
+	assert_eq!(unchanged.objective.unwrap(), 1052000.);
+}
+
+#[test]
+#[should_panic]
+fn resolve_without_solve_test() {
+	Solver::new().resolve_with_objective(&[1.]);
+}
+
+#[test]
+#[should_panic]
+fn resolve_after_solve_mip_test() {
+	let lp = Parser::lp_from_text("var a integer; maximize obj: a; subject to cap: 2*a <= 3;", Builder::new());
+	let mut solver = Solver::new();
+	solver.solve(&lp);
+	solver.solve_mip(&lp);
+	solver.resolve_with_objective(&lp.c);
+}
+
+#[test]
+fn basis_names_test() {
+	let builder = Builder::new();
+	let mut input_file = File::open("./tests/test_files/advertisement_example.lp").unwrap();
+	let lp = Parser::lp_from_file(&mut input_file, builder);
+	let solution = SimplexSolver::new(lp.clone()).solve();
+
+	// Only constraint_4 has slack left at the optimum
+	let mut names = solution.basis_names(&lp);
+	names.sort();
+	assert_eq!(names, vec!["newspaper", "radio", "slack_3", "television"]);
+
+	let basis = solution.basis.as_ref().unwrap();
+	assert_eq!(basis.len(), lp.constraint_names.len());
+	for (&col, name) in basis.iter().zip(solution.basis_names(&lp).iter()) {
+		assert_eq!(&lp.vars[col], name);
+	}
+
+	let mut input_file = File::open("./tests/test_files/infeasible_example.lp").unwrap();
+	let infeasible = Parser::lp_from_file(&mut input_file, Builder::new());
+	let solution = SimplexSolver::new(infeasible.clone()).solve();
+	assert_eq!(solution.basis, None);
+	assert!(solution.basis_names(&infeasible).is_empty());
+}
+
+#[test]
+fn problem_type_test() {
+	let problem_type = |declarations: &str| {
+		let text = format!("{} maximize obj: a + b; subject to cap: a + b <= 4;", declarations);
+		Parser::lp_from_text(&text, Builder::new()).problem_type()
+	};
+
+	assert_eq!(problem_type("var a; var b;"), ProblemType::Linear);
+	assert_eq!(problem_type("var a integer; var b integer;"), ProblemType::Integer);
+	assert_eq!(problem_type("var a integer; var b binary;"), ProblemType::Integer);
+	assert_eq!(problem_type("var a; var b integer;"), ProblemType::MixedInteger);
+	assert_eq!(problem_type("var a binary; var b;"), ProblemType::MixedInteger);
+	assert_eq!(problem_type("var a binary; var b binary;"), ProblemType::BinaryInteger);
+}
+
+#[test]
+fn relax_integrality_test() {
+	let lp = Parser::lp_from_text("
+		var a integer;
+		var b binary;
+		var c;
+
+		maximize obj: a + 3*b + c;
+		subject to cap: 2*a + 2*b + c <= 5;
+		subject to c_cap: c <= 0.5;
+	", Builder::new());
+	let relaxed = lp.relax_integrality();
+
+	assert!(relaxed.kinds.iter().all(|&kind| kind == VariableKind::Continuous));
+	assert_eq!(relaxed.problem_type(), ProblemType::Linear);
+	assert_eq!(lp.problem_type(), ProblemType::MixedInteger);
+
+	// b keeps its upper bound of 1, so a takes the fractional rest
+	assert!(relaxed.constraint_names.contains(&"b_binary".to_string()));
+	let solution = SimplexSolver::new(relaxed.clone()).solve();
+	let values = solution.values.clone().unwrap();
+	assert_approx_eq!(values[1], 1.);
+	assert_approx_eq!(values[0], 1.25);
+	assert!(solution.bounds_violations(&relaxed, 1e-9).is_empty());
+	assert_eq!(relaxed.relaxation_bound(), lp.relaxation_bound());
+}
+
+#[test]
+fn report_json_test() {
+	let builder = Builder::new();
+	let mut input_file = File::open("./tests/test_files/advertisement_example.lp").unwrap();
+	let lp = Parser::lp_from_file(&mut input_file, builder);
+	let report = SimplexSolver::new(lp.clone()).solve().to_report_json(&lp);
+
+	assert!(report.starts_with("{\"status\":\"Optimal\",\"objective\":1052000,\"values\":{\"television\":"));
+	for key in &["\"values\":{", "\"duals\":{\"constraint_1\":", "\"reduced_costs\":{\"television\":",
+		"\"objective_ranges\":{\"television\":[", "\"rhs_ranges\":{\"constraint_1\":["] {
+		assert!(report.contains(key), "missing {} in {}", key, report);
+	}
+	assert!(!report.contains("slack_"));
+	assert!(report.ends_with("}}"));
+
+	let mut input_file = File::open("./tests/test_files/infeasible_example.lp").unwrap();
+	let infeasible = Parser::lp_from_file(&mut input_file, Builder::new());
+	let report = SimplexSolver::new(infeasible.clone()).solve().to_report_json(&infeasible);
+	assert_eq!(report, "{\"status\":\"Infeasible\",\"objective\":null,\"values\":null,\"duals\":null,\"reduced_costs\":null,\"objective_ranges\":null,\"rhs_ranges\":null}");
+
+	// At a = 3, b = 1 the basis holds while b's coefficient stays within
+	// [0, 3] (a's is 2 or more), and while c1 stays within [3, 5], c3
+	// within [1.5, 4] and the slack c2 isn't cut below 6
+	let lp = Parser::lp_from_text("
+		var a;
+		var b;
+		maximize obj: 3*a + 2*b;
+		subject to c1: a + b <= 4;
+		subject to c2: a + 3*b <= 9;
+		subject to c3: a <= 3;
+	", Builder::new());
+	let report = SimplexSolver::new(lp.clone()).solve().to_report_json(&lp);
+	let range = |key: &str, name: &str| -> (f64, f64) {
+		let section = &report[report.find(&format!("\"{}\":", key)).unwrap() ..];
+		let start = section.find(&format!("\"{}\":[", name)).unwrap() + name.len() + 4;
+		let bounds: Vec<f64> = section[start .. start + section[start ..].find(']').unwrap()].split(',')
+			.map(|bound| bound.parse().unwrap_or(f64::INFINITY))
+			.collect();
+		(bounds[0], bounds[1])
+	};
+	for &(key, name, lower, upper) in &[
+		("objective_ranges", "a", 2., f64::INFINITY),
+		("objective_ranges", "b", 0., 3.),
+		("rhs_ranges", "c1", 3., 5.),
+		("rhs_ranges", "c2", 6., f64::INFINITY),
+		("rhs_ranges", "c3", 1.5, 4.),
+	] {
+		let (low, high) = range(key, name);
+		assert_approx_eq!(low, lower);
+		assert!(high == upper || (high - upper).abs() < 1e-9, "{} of {} in {}", key, name, report);
+	}
+}
+
+#[test]
+fn auto_declare_test() {
+	let parser = Parser::with_options(ParserOptions {
+		auto_declare: true,
+		..ParserOptions::default()
+	});
+	let lp = parser.parse_lp("
+		var a;
+		maximize obj: a + 2*b;
+		subject to cap: a + b + spare <= 4;
+		subject to spare_min: spare >= 1;
+	", Builder::new());
+
+	assert_eq!(&lp.vars[.. 3], &["a", "b", "spare"]);
+	assert_eq!(&lp.c[.. 3], &[1., 2., 0.]);
+	assert_eq!(lp.A[[0, 2]], 1.);
+
+	let solution = SimplexSolver::new(lp.clone()).solve();
+	let values = solution.values.unwrap();
+	assert_approx_eq!(values[1], 3.);
+	assert_approx_eq!(values[2], 1.);
+	assert_approx_eq!(solution.objective.unwrap(), 6.);
+}
+
+#[test]
+fn declaration_free_test() {
+	let parser = Parser::with_options(ParserOptions {
+		auto_declare: true,
+		..ParserOptions::default()
+	});
+	let lp = parser.parse_lp("
+		subject to budget: 2*covariance + hedge <= 10;
+		subject to hedge_cap: hedge + spread <= 4;
+		subject to spread_min: spread >= 1;
+		maximize gain: hedge + 3*covariance + spread;
+	", Builder::new());
+
+	assert_eq!(&lp.vars[.. 3], &["covariance", "hedge", "spread"]);
+	assert_eq!(&lp.c[.. 3], &[3., 1., 1.]);
+
+	let solution = SimplexSolver::new(lp.clone()).solve();
+	assert_eq!(solution.status, Status::Optimal);
+	let values = solution.values.unwrap();
+	assert_approx_eq!(values[0], 5.);
+	assert_approx_eq!(values[1], 0.);
+	assert_approx_eq!(values[2], 4.);
+	assert_approx_eq!(solution.objective.unwrap(), 19.);
+}
+
+#[test]
+fn feasibility_restoration_test() {
+	let lp = Parser::lp_from_text("
+		var x;
+		var y;
+
+		maximize obj: x + y;
+		subject to demand: x + y >= 10;
+		subject to x_cap: 2*x <= 10;
+		subject to y_cap: 2*y <= 4;
+	", Builder::new());
+	assert_eq!(SimplexSolver::new(lp.clone()).solve().status, Status::Infeasible);
+
+	// Meeting the demand costs 3 units of it, raising a cap would cost 6
+	let restoration = lp.to_feasibility_restoration();
+	assert_eq!(restoration.validate(), Ok(()));
+	let solution = SimplexSolver::new(restoration.clone()).solve();
+	assert_eq!(solution.status, Status::Optimal);
+	assert_approx_eq!(solution.objective.unwrap(), 3.);
+
+	let relaxed: Vec<String> = solution.support(&restoration, 1e-9).into_iter()
+		.filter(|name| name.ends_with("_shortfall") || name.ends_with("_overflow"))
+		.collect();
+	assert_eq!(relaxed, vec!["demand_shortfall".to_string()]);
+
+	let feasible = Parser::lp_from_text("var x; maximize obj: x; subject to x_cap: x <= 5;", Builder::new());
+	let solution = SimplexSolver::new(feasible.to_feasibility_restoration()).solve();
+	assert_approx_eq!(solution.objective.unwrap(), 0.);
+}
+
+#[test]
+fn mps_import_test() {
+	let text = fs::read_to_string("./tests/test_files/kinds_example.mps").unwrap();
+	let lp = Parser::lp_from_mps(&text).unwrap();
+
+	let num_structural = lp.vars.len() - lp.num_artificial_vars;
+	assert_eq!(&lp.vars[.. num_structural], &["crates", "bonus", "extra", "pallets", "shift_pos", "shift_neg"]);
+	assert_eq!(&lp.kinds[.. num_structural], &[
+		VariableKind::Integer,
+		VariableKind::Binary,
+		VariableKind::Continuous,
+		VariableKind::Integer,
+		VariableKind::Continuous,
+		VariableKind::Continuous
+	]);
+	assert_eq!(lp.optimization, Optimization::Max);
+
+	let constraints = lp.to_constraints();
+	let names: Vec<&str> = constraints.iter().map(|constraint| constraint.name.as_str()).collect();
+	assert_eq!(names, vec!["budget", "floor", "crates_upper", "extra_lower", "extra_upper", "pallets_lower", "pallets_upper"]);
+	assert_eq!(constraints[1], Constraint::from_terms("floor", vec![
+		("shift_pos".to_string(), 1.),
+		("shift_neg".to_string(), -1.)
+	], Relation::GreaterThanOrEqual, -4.));
+
+	// Every variable goes to the bound that pays off, the free one below zero
+	let solution = Solver::new().solve_mip(&lp);
+	assert_approx_eq!(solution.objective.unwrap(), 29.5);
+	let values = solution.values.unwrap();
+	assert_approx_eq!(values[0], 4.);
+	assert_approx_eq!(values[1], 1.);
+	assert_approx_eq!(values[3], 3.);
+	assert_approx_eq!(values[4] - values[5], -4.);
+}
+
+#[test]
+fn mps_round_trip_test() {
+	let lp = Parser::lp_from_text("
+		var a;
+		var b integer;
+		var c binary;
+		var d;
+		maximize obj: 3*a + 2*b + c + d;
+		subject to band: 2 <= a + b <= 6;
+		subject to mix: a + 2*b + c + d <= 9;
+		subject to a_upper: a <= 4;
+		subject to d_fixed: 2*d == 3;
+	", Builder::new());
+
+	let mps = lp.to_mps().unwrap();
+	assert!(mps.contains("RANGES\n    RNG    band    4\n"));
+	assert!(mps.contains("BOUNDS\n UP BND    a    4\n BV BND    c\n FX BND    d    1.5\n"));
+	assert!(mps.contains("    MARKER    'MARKER'    'INTORG'\n    b    objective    2\n"));
+
+	let read = Parser::lp_from_mps(&mps).unwrap();
+	assert_eq!(read.vars, lp.vars);
+	assert_eq!(read.kinds, lp.kinds);
+	assert_eq!(read.optimization, lp.optimization);
+	let mut constraints = lp.to_constraints();
+	constraints[3] = Constraint::from_terms("d_fixed", vec![("d".to_string(), 1.)], Relation::Equal, 1.5);
+	assert_eq!(read.to_constraints(), constraints);
+	assert_eq!(read.to_mps().unwrap(), mps);
+
+	let solution = Solver::new().solve_mip(&read);
+	assert_approx_eq!(solution.objective.unwrap(), Solver::new().solve_mip(&lp).objective.unwrap());
+
+	let mut spaced = lp.clone();
+	spaced.vars[0] = "a b".to_string();
+	assert_eq!(spaced.to_mps(), Err(ExportError::InvalidName { name: "a b".to_string() }));
+}
+
+#[test]
+fn network_parser_test() {
+	let network = NetworkParser::lp_from_text("
+		# Ship 4 units from s to t;
+		node s supply 4;
+		node t demand 4;
+		arc s_a from s to a cap 4 cost 2;
+		arc s_b from s to b cap 2 cost 2;
+		arc a_b from a to b cap 2 cost 1;
+		arc a_t from a to t cap 3 cost 3;
+		arc b_t from b to t cap 5 cost 1;
+	", Builder::new());
+
+	let hand_built = Parser::lp_from_text("
+		var s_a;
+		var s_b;
+		var a_b;
+		var a_t;
+		var b_t;
+
+		minimize total_cost: 2*s_a + 2*s_b + a_b + 3*a_t + b_t;
+		subject to s_a_cap: s_a <= 4;
+		subject to s_b_cap: s_b <= 2;
+		subject to a_b_cap: a_b <= 2;
+		subject to a_t_cap: a_t <= 3;
+		subject to b_t_cap: b_t <= 5;
+		subject to s_balance: s_a + s_b == 4;
+		subject to t_balance: -1*a_t + -1*b_t == -4;
+		subject to a_balance: -1*s_a + a_b + a_t == 0;
+		subject to b_balance: -1*s_b + -1*a_b + b_t == 0;
+	", Builder::new());
+
+	assert_eq!(&network.vars[.. 5], &hand_built.vars[.. 5]);
+	assert_eq!(network.constraint_names, hand_built.constraint_names);
+
+	// Both routes through b fill up, leaving the expensive arc a_t unused
+	let solution = SimplexSolver::new(network).solve();
+	let expected = SimplexSolver::new(hand_built).solve();
+	assert_eq!(solution.status, Status::Optimal);
+	assert_approx_eq!(solution.objective.unwrap(), expected.objective.unwrap());
+	assert_approx_eq!(solution.objective.unwrap(), 14.);
+	let values = solution.values.unwrap();
+	assert_approx_eq!(values[4], 4.);
+}
+
+#[test]
+#[should_panic(expected = "Arc \"road\" given twice")]
+fn network_duplicate_arc_test() {
+	NetworkParser::default().parse_components("
+		node plant supply 4;
+		node store demand 4;
+		arc road from plant to store cost 1;
+		arc road from store to plant cost 2;
+	");
+}
+
+#[test]
+fn to_dimacs_test() {
+	let network = NetworkParser::lp_from_text("
+		node s supply 3;
+		node t demand 3;
+		arc s_a from s to a cap 2 cost 4;
+		arc a_t from a to t cost 1;
+		arc s_t from s to t cap 5 cost 7;
+	", Builder::new());
+
+	let expected = "p min 3 3\n\
+		c node 1 s_balance\n\
+		n 1 3\n\
+		c node 2 t_balance\n\
+		n 2 -3\n\
+		c node 3 a_balance\n\
+		c arc 1 s_a\n\
+		a 1 3 0 2 4\n\
+		c arc 2 a_t\n\
+		a 3 2 0 3 1\n\
+		c arc 3 s_t\n\
+		a 1 2 0 5 7\n";
+	assert_eq!(network.to_dimacs().unwrap(), expected);
+
+	let lp = Parser::lp_from_text("
+		var a;
+		var b;
+		maximize obj: a + b;
+		subject to c1: a + 2*b <= 4;
+	", Builder::new());
+	assert_eq!(lp.to_dimacs(), Err(ExportError::NotANetwork { constraint: "c1".to_string() }));
+}
+
+#[test]
+fn variable_increase_headroom_test() {
+	let lp = Parser::lp_from_text("
+		var a;
+		var b;
+		maximize obj: a + b;
+		subject to c1: a + 2*b <= 10;
+		subject to c2: 2*a + b <= 8;
+		subject to c3: b + -1*a >= -3;
+		subject to c4: 1 <= b <= 3;
+	", Builder::new());
+
+	// From (1, 1): c1 allows 8 more, c2 allows 2.5 and c3 allows 3
+	assert_approx_eq!(lp.variable_increase_headroom("a", &[1., 1.]), 2.5);
+	// c1 allows 3.5 more of b, c2 allows 5 and the upper side of c4 allows 2
+	assert_approx_eq!(lp.variable_increase_headroom("b", &[1., 1.]), 2.);
+	// c2 is already binding at (3.5, 1)
+	assert_approx_eq!(lp.variable_increase_headroom("a", &[3.5, 1.]), 0.);
+}
+
+#[test]
+#[should_panic(expected = "Too many variables: 4 exceeds the limit of 3")]
+fn max_variables_test() {
+	let mut builder = Builder::new();
+	builder.max_variables(3);
+	Parser::lp_from_text("
+		var x[1..5];
+		maximize obj: x[1] + x[5];
+		subject to c1: x[1] + x[2] <= 4;
+	", builder);
+}
+
+#[test]
+fn max_variables_range_test() {
+	let parser = Parser::with_options(ParserOptions {
+		max_variables: Some(1000),
+		..ParserOptions::default()
+	});
+	let err = parser.try_parse_components("
+		var x[1..1000000000];
+		maximize obj: x[1];
+	").unwrap_err();
+	assert_eq!(err.message, "Too many variables: 1000000000 exceeds the limit of 1000");
+
+	let mut builder = Builder::new();
+	builder.max_variables(3);
+	let err = parser.try_parse_lp("var x[1..5]; maximize obj: x[1];", builder).unwrap_err();
+	assert_eq!(err.message, "Too many variables: 4 exceeds the limit of 3");
+}
+
+#[test]
+fn try_add_variable_test() {
+	let mut builder = Builder::new();
+	builder.max_variables(2);
+	builder.try_add_variable(Variable { name: "a".to_string(), coefficient: 0. }).unwrap();
+	builder.try_add_variable(Variable { name: "b".to_string(), coefficient: 0. }).unwrap();
+	builder.try_add_variable(Variable { name: "a".to_string(), coefficient: 0. }).unwrap();
+	assert_eq!(builder.try_add_variable(Variable { name: "c".to_string(), coefficient: 0. }),
+		Err("Too many variables: 3 exceeds the limit of 2".to_string()));
+}
+
+#[test]
+fn max_variables_build_test() {
+	let variables = vec![
+		Variable { name: "a".to_string(), coefficient: 1. },
+		Variable { name: "b".to_string(), coefficient: 1. },
+	];
+	let model = |builder: &mut Builder| {
+		for var in &variables {
+			builder.add_variable(var.clone());
+		}
+		builder.add_objective(Objective { name: "obj".to_string(), variables: variables.clone(), maximize: true });
+		builder.add_constraint(Constraint { name: "cap".to_string(), variables: variables.clone(), constant: 4., relation: Relation::LessThanOrEqual });
+		builder.add_constraint(Constraint { name: "band".to_string(), variables: variables.clone(), constant: 3., relation: Relation::Range(1.) });
+	};
+
+	// The slack and range columns of the rows aren't counted
+	let mut builder = Builder::new();
+	builder.max_variables(2);
+	model(&mut builder);
+	let lp = builder.try_build_lp().unwrap();
+	assert_eq!(lp.vars.len() - lp.num_artificial_vars, 2);
+
+	// Nor are they when the model is already past the limit
+	let mut builder = Builder::new();
+	model(&mut builder);
+	builder.max_variables(1);
+	assert_eq!(builder.try_build_lp(), Err("Too many variables: 2 exceeds the limit of 1".to_string()));
+}
+
+#[test]
+fn max_constraints_test() {
+	let text = "
+		var a;
+		var b;
+		maximize obj: a + b;
+		subject to c1: a <= 4;
+		subject to c2: b <= 4;
+		subject to c3: a + b <= 6;
+	";
+
+	let mut builder = Builder::new();
+	builder.max_constraints(3);
+	assert_eq!(Parser::lp_from_text(text, builder).b.len(), 3);
+
+	let mut builder = Builder::new();
+	builder.max_constraints(2);
+	let result = std::panic::catch_unwind(|| Parser::lp_from_text(text, builder));
+	let err = result.unwrap_err();
+	assert_eq!(err.downcast_ref::<String>().unwrap(), "Too many constraints: 3 exceeds the limit of 2");
+}
+
+fn assert_farkas_certificate(lp: &Lp, farkas: &[f64]) {
+	let (A, b, _) = lp.to_equality_form();
+	for col in 0 .. lp.vars.len() {
+		let combination: f64 = (0 .. b.len()).map(|row| farkas[row] * A[[row, col]]).sum();
+		assert!(combination >= -1e-9);
+	}
+	let rhs: f64 = (0 .. b.len()).map(|row| farkas[row] * b[row]).sum();
+	assert!(rhs < -1e-9);
+}
+
+#[test]
+fn farkas_certificate_test() {
+	let mut input_file = File::open("./tests/test_files/infeasible_example.lp").unwrap();
+	let infeasible = Parser::lp_from_file(&mut input_file, Builder::new());
+	let solution = SimplexSolver::new(infeasible.clone()).solve();
+	assert_eq!(solution.status, Status::Infeasible);
+	assert_farkas_certificate(&infeasible, &solution.farkas.unwrap());
+
+	let lp = Parser::lp_from_text("
+		var a;
+		var b;
+		minimize obj: a + b;
+		subject to low: a + b >= 5;
+		subject to high: a + 2*b <= 3;
+		subject to split: a + -1*b == 1;
+	", Builder::new());
+	let solution = SimplexSolver::new(lp.clone()).solve();
+	assert_eq!(solution.status, Status::Infeasible);
+	assert_farkas_certificate(&lp, &solution.farkas.unwrap());
+
+	let feasible = SimplexSolver::new(Parser::lp_from_text("
+		var a;
+		maximize obj: a;
+		subject to c1: a <= 2;
+	", Builder::new())).solve();
+	assert_eq!(feasible.farkas, None);
+}
+
+#[test]
+fn equality_constraints_test() {
+	let lp = Parser::lp_from_text("
+		var a;
+		var b;
+		var c;
+		var d;
+		maximize obj: 2*a + 3*b + c + d;
+		subject to total: a + b + c + d == 10;
+		subject to gap: a + -1*b == 2;
+		subject to ratio: 2*b + -1*c == 1;
+		subject to pair: c + -1*d == 0;
+		subject to cap: a <= 5;
+	", Builder::new());
+
+	// Only the inequality gets a slack column, the equalities none
+	assert_eq!(lp.vars.len(), 5);
+	assert_eq!(lp.num_artificial_vars, 1);
+
+	let solution = SimplexSolver::new(lp.clone()).solve();
+	assert_eq!(solution.status, Status::Optimal);
+	let values = solution.values.unwrap();
+	assert_eq!(values.len(), lp.vars.len());
+
+	// a = b + 2, c = d = 2b - 1 leave a single feasible point
+	let expected = [11. / 3., 5. / 3., 7. / 3., 7. / 3., 4. / 3.];
+	for i in 0 .. expected.len() {
+		assert_approx_eq!(values[i], expected[i]);
+	}
+	assert_approx_eq!(solution.objective.unwrap(), 22. / 3. + 5. + 14. / 3.);
+	for row in 0 .. lp.b.len() {
+		let activity: f64 = (0 .. values.len()).map(|col| lp.A[[row, col]] * values[col]).sum();
+		assert_approx_eq!(activity, lp.b[row]);
+	}
+}
+
+#[test]
+fn singleton_feasible_test() {
+	let text = "
+		var a;
+		var b;
+		var c;
+		minimize obj: a + -4*b + 2*c;
+		subject to total: a + b + c == 6;
+		subject to gap: a + -1*b == 1;
+		subject to twice: c + -2*a == -2;
+		subject to cap: a + b <= 10;
+	";
+	let lp = Parser::lp_from_text(text, Builder::new());
+	assert!(lp.is_singleton_feasible());
+
+	// a = 9/4, b = 5/4 and c = 5/2 is the only feasible point, so it is
+	// returned without pivoting when asked to check
+	let solution = Solver::with_options(SolverOptions::default().with_singleton_check(true)).solve(&lp);
+	assert_eq!(solution.status, Status::Optimal);
+	assert_eq!(solution.iterations, 0);
+	let values = solution.values.unwrap();
+	assert_eq!(values.len(), lp.vars.len());
+	for (&value, &expected) in values.iter().zip([9. / 4., 5. / 4., 5. / 2., 6.5].iter()) {
+		assert_approx_eq!(value, expected);
+	}
+	assert_approx_eq!(solution.objective.unwrap(), 9. / 4. - 5. + 5.);
+	let pivoted = Solver::new().solve(&lp);
+	assert!(pivoted.iterations > 0 && pivoted.duals.is_some());
+	assert_approx_eq!(pivoted.objective.unwrap(), solution.objective.unwrap());
+
+	// Without the last equality b is free to move, and a point infeasible
+	// for the cap leaves no feasible region at all
+	let underdetermined = Parser::lp_from_text(&text.replace("subject to twice: c + -2*a == -2;", ""), Builder::new());
+	assert!(!underdetermined.is_singleton_feasible());
+	let infeasible = Parser::lp_from_text(&text.replace("<= 10", "<= 3"), Builder::new());
+	assert!(!infeasible.is_singleton_feasible());
+	assert_eq!(Solver::new().solve(&infeasible).status, Status::Infeasible);
+}
+
+#[test]
+fn total_violation_test() {
+	let lp = Parser::lp_from_text("
+		var a;
+		var b;
+		var c binary;
+		maximize obj: a + b + c;
+		subject to c1: a + 2*b <= 10;
+		subject to c2: a + -1*b >= -1;
+		subject to c3: a + c == 3;
+		subject to c4: 1 <= b <= 4;
+	", Builder::new());
+
+	assert_eq!(lp.total_violation(&[2., 2., 1.]), 0.);
+	// c1 over by 3, c2 short by 4, c3 off by 0.5, c4 over by 2 and c
+	// above its binary bound by 0.5
+	assert_approx_eq!(lp.total_violation(&[1., 6., 1.5]), 3. + 4. + 0.5 + 2. + 0.5);
+	// a is negative by 1, c2 short by 0.5, c3 off by 3 and c4 under by 0.5
+	assert_approx_eq!(lp.total_violation(&[-1., 0.5, 1.]), 1. + 0.5 + 3. + 0.5);
+}
+
+#[test]
+fn solver_options_builder_test() {
+	use std::cell::RefCell;
+	use std::rc::Rc;
+
+	let mut input_file = File::open("./tests/test_files/advertisement_example.lp").unwrap();
+	let lp = Parser::lp_from_file(&mut input_file, Builder::new());
+
+	let calls = Rc::new(RefCell::new(0));
+	let counted = calls.clone();
+	let options = SolverOptions::default()
+		.with_method(SolverMethod::Simplex)
+		.with_deterministic(true)
+		.with_mip_gap(0.05)
+		.with_optima_limit(2)
+		.with_on_iteration(Box::new(move |_, _| *counted.borrow_mut() += 1));
+	assert!(options.deterministic);
+	assert_eq!(options.mip_gap, 0.05);
+	assert_eq!(options.optima_limit, Some(2));
+
+	let solution = Solver::with_options(options).solve(&lp);
+	let expected = SimplexSolver::new(lp.clone()).solve();
+	assert_approx_eq!(solution.objective.unwrap(), expected.objective.unwrap());
+	assert_eq!(*calls.borrow(), solution.iterations);
+
+	let interior = Solver::with_options(SolverOptions::default().with_method(SolverMethod::InteriorPoint)).solve(&lp);
+	assert!((interior.objective.unwrap() - expected.objective.unwrap()).abs() < 1e-4);
+}
+
+#[test]
+fn objective_improvement_tolerance_test() {
+	let mut input_file = File::open("./tests/test_files/advertisement_example.lp").unwrap();
+	let lp = Parser::lp_from_file(&mut input_file, Builder::new());
+	let exact = Solver::new().solve(&lp);
+	assert_eq!(exact.iterations, 3);
+
+	// The pivots improve the objective by 910000, 100000 and 42000, so the
+	// second falls short of the tolerance and the last one is skipped
+	let tolerance = 150000.;
+	let solution = Solver::with_options(SolverOptions::default().with_objective_improvement_tolerance(tolerance)).solve(&lp);
+	assert_eq!(solution.status, Status::NearOptimal);
+	assert_eq!(solution.iterations, 2);
+	assert!(solution.duals.is_none());
+	let objective = solution.objective.unwrap();
+	assert!(objective < exact.objective.unwrap());
+	assert!(exact.objective.unwrap() - objective < tolerance);
+	assert!(lp.total_violation(&solution.values.unwrap()[.. 3]) < 1e-9);
+
+	// A pivot reaching the optimum leaves it optimal however small its step
+	let optimal = Solver::with_options(SolverOptions::default().with_objective_improvement_tolerance(50000.)).solve(&lp);
+	assert_eq!(optimal.status, Status::Optimal);
+	assert_approx_eq!(optimal.objective.unwrap(), exact.objective.unwrap());
+
+	// Branch and bound still solves every relaxation to optimality
+	let mip = Parser::lp_from_text(&fs::read_to_string("./tests/test_files/advertisement_example.lp").unwrap()
+		.replace("var radio;", "var radio integer;"), Builder::new());
+	let exact = Solver::new().solve_mip(&mip);
+	let solution = Solver::with_options(SolverOptions::default().with_objective_improvement_tolerance(tolerance)).solve_mip(&mip);
+	assert_eq!(solution.status, Status::Optimal);
+	assert_eq!(solution.objective, exact.objective);
+}
+
+#[test]
+fn debug_checks_test() {
+	// The radiation example needs Phase I, whose pivots are checked too
+	for file in &["advertisement_example.lp", "radiation_example.lp", "unbounded_example.lp", "infeasible_example.lp"] {
+		let mut input_file = File::open(format!("./tests/test_files/{}", file)).unwrap();
+		let lp = Parser::lp_from_file(&mut input_file, Builder::new());
+
+		let unchecked = Solver::new().solve(&lp);
+		let checked = Solver::with_options(SolverOptions::default().with_debug_checks(true)).solve(&lp);
+		assert_eq!(checked.status, unchecked.status, "status of {}", file);
+		assert_eq!(checked.objective, unchecked.objective, "objective of {}", file);
+		assert_eq!(checked.iterations, unchecked.iterations, "iterations of {}", file);
+	}
+
+	// The bounds of the values found are checked as well
+	let lp = Parser::lp_from_text("
+		var a;
+		var b binary;
+		maximize obj: a + b;
+		subject to a_cap: a <= 4;
+		subject to total: a + 2*b <= 10;
+	", Builder::new());
+	let checked = Solver::with_options(SolverOptions::default().with_debug_checks(true)).solve(&lp);
+	assert_eq!(checked.status, Status::Optimal);
+	assert!(checked.bounds_violations(&lp, 1e-9).is_empty());
+}
+
+#[test]
+fn implied_variable_bounds_test() {
+	// a and b lie in the quadrilateral with corners (2, 0), (3, 1),
+	// (1.5, 2.5) and (0.5, 1.5), while c is only bounded below by a
+	let lp = Parser::lp_from_text("
+		var a;
+		var b;
+		var c;
+		maximize obj: a + b;
+		subject to top: a + b <= 4;
+		subject to bottom: a + b >= 2;
+		subject to right: a + -1*b <= 2;
+		subject to left: b + -1*a <= 1;
+		subject to above: c + -1*a >= 0;
+	", Builder::new());
+
+	let bounds = lp.implied_variable_bounds();
+	assert_eq!(bounds.len(), 3);
+	let (min, max) = bounds["a"];
+	assert_approx_eq!(min, 0.5);
+	assert_approx_eq!(max, 3.);
+	let (min, max) = bounds["b"];
+	assert_approx_eq!(min, 0.);
+	assert_approx_eq!(max, 2.5);
+	let (min, max) = bounds["c"];
+	assert_approx_eq!(min, 0.5);
+	assert_eq!(max, f64::INFINITY);
+
+	let infeasible = Parser::lp_from_text("var a; maximize obj: a; subject to c1: a <= 1; subject to c2: a >= 2;", Builder::new());
+	assert!(infeasible.implied_variable_bounds().is_empty());
+}
+
+#[test]
+fn would_remain_optimal_test() {
+	let mut input_file = File::open("./tests/test_files/advertisement_example.lp").unwrap();
+	let lp = Parser::lp_from_file(&mut input_file, Builder::new());
+	let solution = SimplexSolver::new(lp.clone()).solve();
+	let constraint = |relation: Relation, constant: f64| Constraint {
+		name: "cut".to_string(),
+		variables: vec![
+			Variable { name: "television".to_string(), coefficient: 1. },
+			Variable { name: "radio".to_string(), coefficient: 1. },
+		],
+		constant,
+		relation
+	};
+
+	// The optimum has television = 4 and radio = 14
+	assert!(solution.would_remain_optimal(&lp, &constraint(Relation::LessThanOrEqual, 20.)));
+	assert!(solution.would_remain_optimal(&lp, &constraint(Relation::Equal, 18.)));
+	assert!(solution.would_remain_optimal(&lp, &constraint(Relation::Range(10.), 18.)));
+	assert!(!solution.would_remain_optimal(&lp, &constraint(Relation::LessThanOrEqual, 17.)));
+	assert!(!solution.would_remain_optimal(&lp, &constraint(Relation::GreaterThanOrEqual, 19.)));
+
+	// Re-solving with the violated cut does lose objective
+	let mut builder = Builder::new();
+	for name in &lp.vars[.. 3] {
+		builder.add_variable(Variable { name: name.clone(), coefficient: 0. });
+	}
+	for c in lp.to_constraints() {
+		builder.add_constraint(c);
+	}
+	builder.add_constraint(constraint(Relation::LessThanOrEqual, 17.));
+	builder.add_objective(Objective {
+		name: "objective".to_string(),
+		variables: lp.vars[.. 3].iter().zip(lp.c.iter()).map(|(name, &c)| Variable { name: name.clone(), coefficient: c }).collect(),
+		maximize: true
+	});
+	let cut = SimplexSolver::new(builder.build_lp()).solve();
+	assert!(cut.objective.unwrap() < solution.objective.unwrap() - 1.);
+}
+
+#[test]
+fn at_bound_variables_test() {
+	let lp = Parser::lp_from_text("
+		var a;
+		var b;
+		var c;
+		maximize obj: 3*a + 2*b + -1*c;
+		subject to a_cap: a <= 2;
+		subject to total: a + b <= 5;
+		subject to b_min: b >= 1;
+	", Builder::new());
+	let solution = SimplexSolver::new(lp.clone()).solve();
+	assert_eq!(solution.values.clone().unwrap()[.. 3].to_vec(), vec![2., 3., 0.]);
+
+	// a is held by its cap, c by non-negativity; b is set by `total`
+	assert_eq!(solution.at_bound_variables(&lp, 1e-9), vec![
+		("a".to_string(), Bound::Upper),
+		("c".to_string(), Bound::Lower),
+	]);
+}
+
+#[cfg(feature = "toml")]
+#[test]
+fn lp_from_toml_test() {
+	let toml = r#"
+		# The bakery model
+		variables = ["bagels", "doughnuts", "x[1]"]
+
+		[objective]
+		name = "profits"
+		sense = "maximize"
+		coefficients = { bagels = 3, doughnuts = 1.25, "x[1]" = 0.5 }
+
+		[[constraints]]
+		name = "flour"
+		coefficients = { bagels = 12, doughnuts = 6.5, "x[1]" = 1 }
+		relation = "<="
+		rhs = 400
+
+		[[constraints]]
+		name = "bagel_min"
+		coefficients = { bagels = 1 }
+		relation = ">="
+		rhs = 12
+
+		[[constraints]]
+		name = "pairing"
+		coefficients = { doughnuts = 1, "x[1]" = -2 }
+		relation = "=="
+		rhs = 0
+	"#;
+	let algebraic = Parser::lp_from_text("
+		var bagels;
+		var doughnuts;
+		var x[1];
+		maximize profits: 3*bagels + 1.25*doughnuts + 0.5*x[1];
+		subject to flour: 12*bagels + 6.5*doughnuts + x[1] <= 400;
+		subject to bagel_min: bagels >= 12;
+		subject to pairing: doughnuts + -2*x[1] == 0;
+	", Builder::new());
+
+	assert_eq!(Parser::lp_from_toml(toml).unwrap(), algebraic);
+	assert!(Parser::lp_from_toml("variables = [\"a\"]\n[objective]\nsense = \"maximize\"\ncoefficients = { b = 1 }").is_err());
+}
+
+#[test]
+fn split_objective_declaration_test() {
+	let split = Parser::lp_from_text("
+		var bagels;
+		var doughnuts;
+
+		maximize profits;
+
+		subject to flour: 12*bagels + 6.5*doughnuts <= 400;
+		subject to milk: bagels + .5*doughnuts <= 200;
+		subject to sugar: 2*doughnuts + 0.25*bagels <= 200;
+
+		profits: 3*bagels + 1.25*doughnuts;
+	", Builder::new());
+	let joined = Parser::lp_from_text("
+		var bagels;
+		var doughnuts;
+		maximize profits: 3*bagels + 1.25*doughnuts;
+		subject to flour: 12*bagels + 6.5*doughnuts <= 400;
+		subject to milk: bagels + .5*doughnuts <= 200;
+		subject to sugar: 2*doughnuts + 0.25*bagels <= 200;
+	", Builder::new());
+
+	assert_eq!(split, joined);
+	let solution = SimplexSolver::new(split).solve();
+	assert_approx_eq!(solution.objective.unwrap(), SimplexSolver::new(joined).solve().objective.unwrap());
+}
+
+#[test]
+fn assert_optimal_value_test() {
+	let mut input_file = File::open("./tests/test_files/radiation_example.lp").unwrap();
+	let lp = Parser::lp_from_file(&mut input_file, Builder::new());
+	let solution = SimplexSolver::new(lp).solve();
+	solution.assert_optimal_value(5.25, 1e-9);
+
+	let message = |result: std::thread::Result<()>| result.unwrap_err().downcast_ref::<String>().unwrap().clone();
+	assert_eq!(
+		message(std::panic::catch_unwind(|| solution.assert_optimal_value(5., 0.1))),
+		"Expected an optimal objective of 5 (within 0.1), but it is 5.25, off by 0.25"
+	);
+
+	let mut input_file = File::open("./tests/test_files/infeasible_example.lp").unwrap();
+	let infeasible = SimplexSolver::new(Parser::lp_from_file(&mut input_file, Builder::new())).solve();
+	assert_eq!(
+		message(std::panic::catch_unwind(|| infeasible.assert_optimal_value(5., 0.1))),
+		"Expected an optimal objective of 5, but the solution is Infeasible"
+	);
+}
+
+#[test]
+fn has_alternative_optima_test() {
+	// The objective is parallel to `total`, so every point on it between
+	// (1, 3) and (3, 1) is optimal
+	let alternative = Parser::lp_from_text("
+		var a;
+		var b;
+		maximize obj: 2*a + 2*b;
+		subject to total: a + b <= 4;
+		subject to a_cap: a <= 3;
+		subject to b_cap: b <= 3;
+	", Builder::new());
+	assert!(alternative.has_alternative_optima());
+	assert!(alternative.to_minimization().has_alternative_optima());
+
+	let strict = Parser::lp_from_text("
+		var a;
+		var b;
+		maximize obj: 3*a + 2*b;
+		subject to total: a + b <= 4;
+		subject to a_cap: a <= 3;
+		subject to b_cap: b <= 3;
+	", Builder::new());
+	assert!(!strict.has_alternative_optima());
+
+	let mut input_file = File::open("./tests/test_files/infeasible_example.lp").unwrap();
+	assert!(!Parser::lp_from_file(&mut input_file, Builder::new()).has_alternative_optima());
+}
+
+#[test]
+fn explain_infeasibility_test() {
+	let lp = Parser::lp_from_text("
+		var a;
+		var b;
+		maximize obj: a + b;
+		subject to c1: a + b <= 5;
+		subject to spare: a + -2*b <= 8;
+		subject to c2: a + b >= 10;
+		subject to b_cap: b <= 20;
+	", Builder::new());
+
+	assert_eq!(lp.infeasible_subset(), Some(vec!["c1".to_string(), "c2".to_string()]));
+	assert_eq!(lp.explain_infeasibility().unwrap(), "Constraints c1 (a + b <= 5) and c2 (a + b >= 10) cannot both hold.");
+
+	let negative = Parser::lp_from_text("
+		var a;
+		var b;
+		maximize obj: a;
+		subject to c1: a + 2*b <= 4;
+		subject to low: a + -1*b <= -6;
+	", Builder::new());
+	assert_eq!(negative.explain_infeasibility().unwrap(), "Constraints c1 (a + 2*b <= 4) and low (a - b <= -6) cannot both hold.");
+
+	let mut input_file = File::open("./tests/test_files/advertisement_example.lp").unwrap();
+	assert_eq!(Parser::lp_from_file(&mut input_file, Builder::new()).explain_infeasibility(), None);
+}
+
+#[test]
+fn constraint_normalize_test() {
+	let satisfied = |constraint: &Constraint, point: &[f64]| {
+		let activity: f64 = constraint.variables.iter().zip(point.iter()).map(|(var, x)| var.coefficient * x).sum();
+		match constraint.relation {
+			Relation::LessThanOrEqual => activity <= constraint.constant + 1e-9,
+			Relation::GreaterThanOrEqual => activity >= constraint.constant - 1e-9,
+			Relation::Equal => (activity - constraint.constant).abs() <= 1e-9,
+			Relation::Range(lower) => activity >= lower - 1e-9 && activity <= constraint.constant + 1e-9
+		}
+	};
+	let terms = || vec![("a".to_string(), 3.), ("b".to_string(), -6.), ("c".to_string(), 1.5)];
+
+	for relation in [Relation::LessThanOrEqual, Relation::GreaterThanOrEqual, Relation::Equal, Relation::Range(-12.)] {
+		let original = Constraint::from_terms("c1", terms(), relation, 6.);
+		let mut normalized = original.clone();
+		normalized.normalize();
+
+		assert_eq!(normalized.variables[1].coefficient, 1.);
+		assert!(normalized.variables.iter().all(|var| var.coefficient.abs() <= 1.));
+		for point in [[0., 0., 0.], [2., 0., 0.], [0., -1., 0.], [4., 1., -2.], [-2., 1., 0.], [0., 3., -4.]] {
+			assert_eq!(satisfied(&original, &point), satisfied(&normalized, &point));
+		}
+	}
+
+	// -6 leads, so the bounds of the range swap sides
+	let mut flipped = Constraint::from_terms("c1", terms(), Relation::Range(-12.), 6.);
+	flipped.normalize();
+	assert_eq!(flipped, Constraint::from_terms("c1",
+		vec![("a".to_string(), -0.5), ("b".to_string(), 1.), ("c".to_string(), -0.25)], Relation::Range(-1.), 2.));
+}
+
+#[test]
+fn solve_anytime_test() {
+	use std::time::{Duration, Instant};
+
+	let lp = Parser::lp_from_text("
+		var x[1..12] integer;
+		maximize value: 12*x[1] + 11*x[2] + 10*x[3] + 9*x[4] + 8*x[5] + 7*x[6] + 13*x[7] + 6*x[8] + 5*x[9] + 14*x[10] + 4*x[11] + 3*x[12];
+		subject to weight: 7*x[1] + 6*x[2] + 6*x[3] + 5*x[4] + 5*x[5] + 4*x[6] + 8*x[7] + 4*x[8] + 3*x[9] + 9*x[10] + 3*x[11] + 2*x[12] <= 31.5;
+		subject to pairs: x[1] + x[2] + x[3] + x[4] + x[5] + x[6] + x[7] + x[8] + x[9] + x[10] + x[11] + x[12] <= 5.5;
+	", Builder::new());
+	let optimum = Solver::new().solve_mip(&lp).objective.unwrap();
+
+	// Past the deadline from the start, not even the root is solved
+	let (solution, bound) = Solver::new().solve_anytime(&lp, Instant::now());
+	assert_eq!(solution.status, Status::Interrupted);
+	assert_eq!(bound, f64::INFINITY);
+
+	// Whatever a short deadline leaves is a valid partial result
+	let (solution, bound) = Solver::new().solve_anytime(&lp, Instant::now() + Duration::from_millis(2));
+	assert!(bound >= optimum - 1e-6);
+	match solution.status {
+		Status::Optimal => assert_approx_eq!(solution.objective.unwrap(), optimum),
+		Status::Interrupted => if let Some(objective) = solution.objective {
+			// An incumbent that isn't proven optimal yet
+			assert!(objective <= optimum + 1e-6 && objective <= bound + 1e-6);
+			assert!(solution.values.unwrap()[.. 12].iter().all(|x| (x - x.round()).abs() < 1e-6));
+		},
+		ref status => panic!("Unexpected status {:?}", status)
+	}
+
+	let (solution, bound) = Solver::new().solve_anytime(&lp, Instant::now() + Duration::from_secs(60));
+	solution.assert_optimal_value(optimum, 1e-6);
+	assert_approx_eq!(bound, optimum);
+}
+
+#[test]
+fn add_column_test() {
+	let mut input_file = File::open("./tests/test_files/advertisement_example.lp").unwrap();
+	let row_wise = Parser::lp_from_file(&mut input_file, Builder::new());
+	let constraints = row_wise.to_constraints();
+
+	let mut builder = Builder::new();
+	builder.add_objective(Objective { name: "objective".to_string(), variables: vec![], maximize: true });
+	for constraint in &constraints {
+		builder.add_constraint(Constraint::from_terms(&constraint.name, vec![], constraint.relation.clone(), constraint.constant));
+	}
+	let num_structural = row_wise.vars.len() - row_wise.num_artificial_vars;
+	for col in 0 .. num_structural {
+		let name = &row_wise.vars[col];
+		let column: Vec<(&str, f64)> = constraints.iter()
+			.filter_map(|constraint| constraint.variables.iter()
+				.find(|var| var.name == *name)
+				.map(|var| (constraint.name.as_str(), var.coefficient)))
+			.collect();
+		builder.add_column(name, row_wise.c[col], &column);
+	}
+
+	assert_eq!(builder.build_lp(), row_wise);
+}
+
+#[test]
+fn presolve_test() {
+	let lp = Parser::lp_from_text("
+		var a;
+		var b integer;
+		var c;
+		maximize obj: a + 2*b + c;
+		subject to same: 2*a + -2*b == 0;
+		subject to cap: a + b + c <= 7;
+		subject to a_cap: a <= 3;
+		subject to c_cap: c >= 0.5;
+	", Builder::new());
+
+	let presolved = lp.presolve();
+	assert_eq!(presolved.substitutions, vec![("b".to_string(), "a".to_string())]);
+	assert_eq!(presolved.lp.vars.len() - presolved.lp.num_artificial_vars, 2);
+	assert_eq!(presolved.lp.constraint_names, vec!["cap", "a_cap", "c_cap"]);
+	assert_eq!(presolved.lp.kinds[0], VariableKind::Integer);
+
+	let solution = presolved.restore(&Solver::new().solve(&presolved.lp));
+	let direct = Solver::new().solve(&lp);
+	assert_eq!(solution.status, Status::Optimal);
+	assert_approx_eq!(solution.objective.unwrap(), direct.objective.unwrap());
+	let values = solution.values.unwrap();
+	assert_eq!(values.len(), lp.vars.len());
+	let value_of = |name: &str| values[lp.vars.iter().position(|var| var == name).unwrap()];
+	assert_approx_eq!(value_of("a"), 3.);
+	assert_approx_eq!(value_of("b"), 3.);
+	assert_approx_eq!(value_of("c"), 1.);
+	// The slack and excess variables make up the rows of the original Lp
+	use rulinalg::matrix::BaseMatrix;
+	for row in 0 .. lp.A.rows() {
+		let lhs: f64 = (0 .. lp.A.cols()).map(|col| lp.A[[row, col]] * values[col]).sum();
+		assert_approx_eq!(lhs, lp.b[row]);
+	}
+}
+
+#[test]
+fn presolve_chain_test() {
+	let lp = Parser::lp_from_text("
+		var a;
+		var b;
+		var c;
+		var d;
+		var e;
+		minimize obj: a + b + c + 3*d + e;
+		subject to c_b: c + -1*b == 0;
+		subject to b_d: 0.5*b + -0.5*d == 0;
+		subject to a_e: a + -1*e == 0;
+		subject to d_low: d >= 2;
+		subject to a_low: a >= 1;
+	", Builder::new());
+
+	let presolved = lp.presolve();
+	assert_eq!(presolved.substitutions, vec![
+		("c".to_string(), "b".to_string()),
+		("d".to_string(), "b".to_string()),
+		("e".to_string(), "a".to_string()),
+	]);
+	assert_eq!(presolved.lp.vars.len() - presolved.lp.num_artificial_vars, 2);
+	assert_eq!(presolved.lp.constraint_names, vec!["d_low", "a_low"]);
+	assert_eq!(&presolved.lp.c[.. 2], &[2., 5.]);
+
+	let solution = presolved.restore(&Solver::new().solve(&presolved.lp));
+	assert_approx_eq!(solution.objective.unwrap(), 12.);
+	let values = solution.values.unwrap();
+	for (name, expected) in [("a", 1.), ("b", 2.), ("c", 2.), ("d", 2.), ("e", 1.)] {
+		assert_approx_eq!(values[lp.vars.iter().position(|var| var == name).unwrap()], expected);
+	}
+}
@@ -0,0 +1,226 @@
+// Randomized parse -> build -> solve round trips.
+//
+// Every case generates LP text covering the supported grammar (comments,
+// declarations, both objective senses, every relation spelling, optional
+// coefficients, negative coefficients and assorted spacing) and checks that
+// the parsed and built Lp agrees with what was generated.
+extern crate rulinalg;
+extern crate rulp;
+
+use rulinalg::matrix::BaseMatrix;
+use rulp::builder::{Builder, BuilderBase};
+use rulp::lp::Optimization;
+use rulp::parser::{Parser, ParserBase};
+use rulp::solver::{SimplexSolver, SolverBase, Status};
+
+const CASES: u64 = 200;
+const TOLERANCE: f64 = 1e-6;
+
+// The xorshift generator of `Lp::random`, so each seed gives the same case
+struct XorShiftRng {
+	state: u64
+}
+
+impl XorShiftRng {
+	fn new(seed: u64) -> Self {
+		XorShiftRng { state: seed ^ 0x9E37_79B9_7F4A_7C15 }
+	}
+
+	fn next_u64(&mut self) -> u64 {
+		let mut x = self.state;
+		x ^= x << 13;
+		x ^= x >> 7;
+		x ^= x << 17;
+		self.state = x;
+		x
+	}
+
+	fn below(&mut self, n: u64) -> u64 {
+		self.next_u64() % n
+	}
+
+	fn chance(&mut self, percent: u64) -> bool {
+		self.below(100) < percent
+	}
+
+	fn pick<'a>(&mut self, options: &[&'a str]) -> &'a str {
+		options[self.below(options.len() as u64) as usize]
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Kind {
+	Less,
+	Greater,
+	Equal
+}
+
+struct GeneratedConstraint {
+	terms: Vec<(usize, f64)>,
+	kind: Kind,
+	constant: f64
+}
+
+struct GeneratedLp {
+	text: String,
+	num_vars: usize,
+	maximize: bool,
+	objective: Vec<(usize, f64)>,
+	constraints: Vec<GeneratedConstraint>
+}
+
+// Formats a number the way the grammar accepts it and returns the value the
+// parser is expected to read back
+fn gen_number(rng: &mut XorShiftRng) -> (String, f64) {
+	let whole = rng.below(50);
+	let text = match rng.below(3) {
+		0 => format!("{}", whole),
+		1 => format!("{}.", whole),
+		_ => format!("{}.{}", whole, rng.below(100)),
+	};
+	let value = text.parse::<f64>().unwrap();
+	(text, value)
+}
+
+fn gen_space(rng: &mut XorShiftRng) -> &'static str {
+	rng.pick(&["", " ", "  ", "\t"])
+}
+
+// Generates `c1*x_i + c2*x_j + ...` over distinct variables
+fn gen_expression(rng: &mut XorShiftRng, num_vars: usize) -> (String, Vec<(usize, f64)>) {
+	let mut indices: Vec<usize> = (0 .. num_vars).collect();
+	for i in (1 .. indices.len()).rev() {
+		let j = rng.below(i as u64 + 1) as usize;
+		indices.swap(i, j);
+	}
+	let len = 1 + rng.below(num_vars as u64) as usize;
+
+	let mut text = String::new();
+	let mut terms = vec![];
+	for (n, &var) in indices[.. len].iter().enumerate() {
+		if n > 0 {
+			text.push_str(gen_space(rng));
+			text.push('+');
+			text.push_str(gen_space(rng));
+		}
+
+		if rng.chance(20) {
+			text.push_str(&format!("x_{}", var));
+			terms.push((var, 1.));
+		} else {
+			let (number, mut value) = gen_number(rng);
+			if rng.chance(30) {
+				text.push('-');
+				value = -value;
+			}
+			text.push_str(&format!("{}{}*{}x_{}", number, gen_space(rng), gen_space(rng), var));
+			terms.push((var, value));
+		}
+	}
+
+	(text, terms)
+}
+
+fn gen_lp(rng: &mut XorShiftRng) -> GeneratedLp {
+	let num_vars = 1 + rng.below(5) as usize;
+	let num_constraints = rng.below(5) as usize;
+	let mut text = String::new();
+
+	if rng.chance(50) {
+		text.push_str("# generated problem;\n");
+	}
+
+	for i in 0 .. num_vars {
+		text.push_str(&format!("var{}x_{}{};\n", rng.pick(&[" ", "  ", "\t"]), i, gen_space(rng)));
+	}
+
+	let maximize = rng.chance(50);
+	let (expression, objective) = gen_expression(rng, num_vars);
+	text.push_str(&format!("{} obj{}:{}{};\n",
+		if maximize { "maximize" } else { "minimize" },
+		gen_space(rng), gen_space(rng), expression));
+
+	let mut constraints = vec![];
+	for i in 0 .. num_constraints {
+		if rng.chance(20) {
+			text.push_str("# a comment between constraints;\n");
+		}
+
+		let (expression, terms) = gen_expression(rng, num_vars);
+		let (operator, kind) = match rng.below(5) {
+			0 => ("<=", Kind::Less),
+			1 => ("=<", Kind::Less),
+			2 => (">=", Kind::Greater),
+			3 => ("=>", Kind::Greater),
+			_ => ("==", Kind::Equal),
+		};
+		let (number, constant) = gen_number(rng);
+
+		text.push_str(&format!("subject to c_{}:{}{} {} {};\n",
+			i, gen_space(rng), expression, operator, number));
+		constraints.push(GeneratedConstraint { terms, kind, constant });
+	}
+
+	GeneratedLp {
+		text,
+		num_vars,
+		maximize,
+		objective,
+		constraints
+	}
+}
+
+#[test]
+fn parse_build_solve_round_trip_test() {
+	for seed in 0 .. CASES {
+		let mut rng = XorShiftRng::new(seed);
+		let generated = gen_lp(&mut rng);
+		let lp = Parser::lp_from_text(&generated.text, Builder::new());
+
+		// Constraints whose coefficients are all zero are dropped when 0 satisfies them
+		let constraints: Vec<&GeneratedConstraint> = generated.constraints.iter()
+			.filter(|c| c.terms.iter().any(|&(_, coeff)| coeff != 0.) || !match c.kind {
+				Kind::Less => 0. <= c.constant,
+				Kind::Greater | Kind::Equal => c.constant == 0.,
+			})
+			.collect();
+		let num_artificial = constraints.iter().filter(|c| c.kind != Kind::Equal).count();
+		let num_cols = generated.num_vars + num_artificial;
+
+		assert_eq!(lp.A.rows(), constraints.len(), "rows for:\n{}", generated.text);
+		assert_eq!(lp.A.cols(), num_cols, "columns for:\n{}", generated.text);
+		assert_eq!(lp.b.len(), constraints.len());
+		assert_eq!(lp.c.len(), num_cols);
+		assert_eq!(lp.vars.len(), num_cols);
+		assert_eq!(lp.num_artificial_vars, num_artificial);
+		assert_eq!(lp.optimization, if generated.maximize { Optimization::Max } else { Optimization::Min });
+
+		let mut expected_c = vec![0.; num_cols];
+		for &(var, coeff) in &generated.objective {
+			expected_c[var] = coeff;
+		}
+		assert_eq!(lp.c, expected_c, "objective for:\n{}", generated.text);
+
+		for (row, constraint) in constraints.iter().enumerate() {
+			assert_eq!(lp.b[row], constraint.constant);
+			for var in 0 .. generated.num_vars {
+				let expected = constraint.terms.iter()
+					.find(|&&(v, _)| v == var)
+					.map_or(0., |&(_, coeff)| coeff);
+				assert_eq!(lp.A[[row, var]], expected, "row {} for:\n{}", row, generated.text);
+			}
+		}
+
+		let solution = SimplexSolver::new(lp.clone()).solve();
+		if solution.status == Status::Optimal {
+			let values = solution.values.unwrap();
+			for row in 0 .. lp.A.rows() {
+				let lhs: f64 = (0 .. lp.A.cols()).map(|col| lp.A[[row, col]] * values[col]).sum();
+				assert!((lhs - lp.b[row]).abs() < TOLERANCE, "row {} violated for:\n{}", row, generated.text);
+			}
+			for value in values {
+				assert!(value > -TOLERANCE, "negative value for:\n{}", generated.text);
+			}
+		}
+	}
+}
@@ -0,0 +1,28 @@
+* Variable kinds from integer markers and BV, LI, UI and FR bounds
+NAME          kinds
+OBJSENSE
+    MAX
+ROWS
+ N  value
+ L  budget
+ G  floor
+COLUMNS
+    MARKER                 'MARKER'                 'INTORG'
+    crates    value     3          budget    2
+    MARKER                 'MARKER'                 'INTEND'
+    bonus     value     5          budget    4
+    extra     value     1          budget    1
+    pallets   value     2          budget    3
+    shift     value     -1         budget    1
+    shift     floor     1
+RHS
+    RHS       budget    20         floor     -4
+BOUNDS
+ UP BND       crates    4
+ BV BND       bonus
+ LI BND       pallets   1
+ UI BND       pallets   3
+ LO BND       extra     0.5
+ UP BND       extra     2.5
+ FR BND       shift
+ENDATA